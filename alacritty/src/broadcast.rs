@@ -0,0 +1,56 @@
+//! Input broadcasting to a group of windows.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use winit::window::WindowId;
+
+use alacritty_terminal::event::Notify;
+use alacritty_terminal::event_loop::Notifier;
+
+/// Windows which receive the input of every member.
+#[derive(Default)]
+pub struct BroadcastGroup {
+    members: HashMap<WindowId, Member>,
+}
+
+/// Window which is part of a broadcast group.
+struct Member {
+    notifier: Notifier,
+
+    /// Read-only members do not receive any input.
+    read_only: bool,
+}
+
+impl BroadcastGroup {
+    /// Check if a window is part of the group.
+    #[inline]
+    pub fn contains(&self, window_id: WindowId) -> bool {
+        self.members.contains_key(&window_id)
+    }
+
+    /// Add a window to the group.
+    pub fn insert(&mut self, window_id: WindowId, notifier: &Notifier, read_only: bool) {
+        let member = Member { notifier: Notifier(notifier.0.clone()), read_only };
+        self.members.insert(window_id, member);
+    }
+
+    /// Update whether a member should ignore the group's input.
+    pub fn set_read_only(&mut self, window_id: WindowId, read_only: bool) {
+        if let Some(member) = self.members.get_mut(&window_id) {
+            member.read_only = read_only;
+        }
+    }
+
+    /// Remove a window from the group.
+    pub fn remove(&mut self, window_id: WindowId) {
+        self.members.remove(&window_id);
+    }
+
+    /// Write input to the PTY of every member which is not read-only.
+    pub fn write(&self, bytes: Cow<'static, [u8]>) {
+        for member in self.members.values().filter(|member| !member.read_only) {
+            member.notifier.notify(bytes.clone());
+        }
+    }
+}
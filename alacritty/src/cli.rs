@@ -11,6 +11,8 @@ use serde_yaml::Value;
 
 use alacritty_terminal::config::{Program, PtyConfig};
 
+#[cfg(unix)]
+use crate::config::export::ExportFormat;
 use crate::config::window::{Class, Identity};
 use crate::config::{serde_utils, UiConfig};
 
@@ -288,6 +290,12 @@ pub enum SocketMessage {
     /// Update the Alacritty configuration.
     Config(IpcConfig),
 
+    /// Write the scrollback history of a window to a file.
+    ExportScrollback(IpcExport),
+
+    /// Send the input of a group of windows to all of them.
+    BroadcastInput(IpcBroadcastInput),
+
     /// Send takeover message.
     #[cfg(feature = "takeover")]
     Takeover(Takeover),
@@ -303,6 +311,10 @@ pub struct WindowOptions {
     #[clap(flatten)]
     /// Window options which could be passed via IPC.
     pub window_identity: WindowIdentity,
+
+    /// Start the window in read-only mode, without forwarding any input to the shell.
+    #[clap(long)]
+    pub read_only: bool,
 }
 
 /// Parameters to the `config` IPC subcommand.
@@ -324,6 +336,40 @@ pub struct IpcConfig {
     pub reset: bool,
 }
 
+/// Parameters to the `export-scrollback` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct IpcExport {
+    /// Output file [default: alacritty-<TIMESTAMP>.<EXTENSION> in the export directory].
+    #[clap(value_hint = ValueHint::FilePath)]
+    pub path: Option<PathBuf>,
+
+    /// Format of the exported history [default: export.format from the config].
+    #[clap(short, long, value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Window ID whose history should be exported.
+    ///
+    /// The focused window is used when no ID is specified.
+    #[clap(short, long, env = "ALACRITTY_WINDOW_ID")]
+    pub window_id: Option<i128>,
+}
+
+/// Parameters to the `broadcast-input` IPC subcommand.
+#[cfg(unix)]
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct IpcBroadcastInput {
+    /// IDs of the windows whose input is sent to all of them.
+    ///
+    /// Broadcasting is stopped for all other windows, without IDs it is stopped entirely.
+    #[clap(value_name = "WINDOW_ID", conflicts_with = "all")]
+    pub window_ids: Vec<u64>,
+
+    /// Broadcast input between all windows.
+    #[clap(short, long)]
+    pub all: bool,
+}
+
 /// Message for the takeover feature.
 #[cfg(feature = "takeover")]
 #[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
@@ -458,6 +504,13 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn read_only_window_option() {
+        let options = Options::parse_from(["alacritty", "--read-only"]);
+        assert!(options.window_options.read_only);
+        assert!(!Options::parse_from(["alacritty"]).window_options.read_only);
+    }
+
     #[cfg(target_os = "linux")]
     #[test]
     fn completions() {
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, VecDeque};
 #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
 use std::ffi::c_void;
 
@@ -15,9 +16,30 @@ use copypasta::x11_clipboard::{Primary as X11SelectionClipboard, X11ClipboardCon
 use copypasta::ClipboardContext;
 use copypasta::ClipboardProvider;
 
+use crate::config::clipboard::DEFAULT_HISTORY_SIZE;
+
 pub struct Clipboard {
     clipboard: Box<dyn ClipboardProvider>,
     selection: Option<Box<dyn ClipboardProvider>>,
+
+    /// Recently copied text, newest first.
+    history: VecDeque<String>,
+
+    /// Maximum number of entries in the history.
+    history_size: usize,
+
+    /// Named vi mode registers.
+    registers: BTreeMap<char, String>,
+}
+
+/// Entry of the clipboard history or a named register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardEntry {
+    /// Name of the register holding the text.
+    pub register: Option<char>,
+
+    /// Copied text.
+    pub text: String,
 }
 
 impl Clipboard {
@@ -32,7 +54,7 @@ impl Clipboard {
             Some(display) => {
                 let (selection, clipboard) =
                     wayland_clipboard::create_clipboards_from_external(display);
-                Self { clipboard: Box::new(clipboard), selection: Some(Box::new(selection)) }
+                Self::with_providers(Box::new(clipboard), Some(Box::new(selection)))
             },
             None => Self::default(),
         }
@@ -42,20 +64,33 @@ impl Clipboard {
     /// feature.
     #[cfg(any(test, not(any(feature = "x11", target_os = "macos", windows))))]
     pub fn new_nop() -> Self {
-        Self { clipboard: Box::new(NopClipboardContext::new().unwrap()), selection: None }
+        Self::with_providers(Box::new(NopClipboardContext::new().unwrap()), None)
+    }
+
+    fn with_providers(
+        clipboard: Box<dyn ClipboardProvider>,
+        selection: Option<Box<dyn ClipboardProvider>>,
+    ) -> Self {
+        Self {
+            clipboard,
+            selection,
+            history: Default::default(),
+            history_size: DEFAULT_HISTORY_SIZE,
+            registers: Default::default(),
+        }
     }
 }
 
 impl Default for Clipboard {
     fn default() -> Self {
         #[cfg(any(target_os = "macos", windows))]
-        return Self { clipboard: Box::new(ClipboardContext::new().unwrap()), selection: None };
+        return Self::with_providers(Box::new(ClipboardContext::new().unwrap()), None);
 
         #[cfg(all(feature = "x11", not(any(target_os = "macos", windows))))]
-        return Self {
-            clipboard: Box::new(ClipboardContext::new().unwrap()),
-            selection: Some(Box::new(X11ClipboardContext::<X11SelectionClipboard>::new().unwrap())),
-        };
+        return Self::with_providers(
+            Box::new(ClipboardContext::new().unwrap()),
+            Some(Box::new(X11ClipboardContext::<X11SelectionClipboard>::new().unwrap())),
+        );
 
         #[cfg(not(any(feature = "x11", target_os = "macos", windows)))]
         return Self::new_nop();
@@ -64,17 +99,95 @@ impl Default for Clipboard {
 
 impl Clipboard {
     pub fn store(&mut self, ty: ClipboardType, text: impl Into<String>) {
+        let text = text.into();
+
+        // Keep a history of everything copied to the clipboard.
+        if ty == ClipboardType::Clipboard {
+            self.remember(text.clone());
+        }
+
+        self.set_contents(ty, text);
+    }
+
+    /// Store text written by the application running in the terminal.
+    ///
+    /// Unlike the primary selection changing with every selected text, stores to either clipboard
+    /// are explicit copies, so they are always added to the clipboard history.
+    pub fn store_from_application(&mut self, ty: ClipboardType, text: String) {
+        self.remember(text.clone());
+        self.set_contents(ty, text);
+    }
+
+    /// Pass text to the clipboard provider.
+    fn set_contents(&mut self, ty: ClipboardType, text: String) {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
             (ClipboardType::Selection, None) => return,
             _ => &mut self.clipboard,
         };
 
-        clipboard.set_contents(text.into()).unwrap_or_else(|err| {
+        clipboard.set_contents(text).unwrap_or_else(|err| {
             warn!("Unable to store text in clipboard: {}", err);
         });
     }
 
+    /// Add text to the clipboard history.
+    ///
+    /// Text which is already in the history is moved to the front instead.
+    pub fn remember(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(index) = self.history.iter().position(|entry| *entry == text) {
+            self.history.remove(index);
+        }
+
+        self.history.push_front(text);
+        self.history.truncate(self.history_size);
+    }
+
+    /// Update the maximum number of entries in the clipboard history.
+    pub fn set_history_size(&mut self, history_size: usize) {
+        self.history_size = history_size;
+        self.history.truncate(history_size);
+    }
+
+    /// Store text in a named register.
+    ///
+    /// Uppercase register names append to the lowercase register, like in vi.
+    pub fn store_register(&mut self, name: char, text: String) {
+        if !name.is_ascii_alphabetic() {
+            return;
+        }
+
+        let register = self.registers.entry(name.to_ascii_lowercase()).or_default();
+        if name.is_ascii_uppercase() {
+            register.push_str(&text);
+        } else {
+            *register = text;
+        }
+
+        let text = register.clone();
+        self.remember(text);
+    }
+
+    /// Text stored in a named register.
+    pub fn register(&self, name: char) -> Option<&str> {
+        self.registers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// All named registers followed by the clipboard history.
+    pub fn entries(&self) -> Vec<ClipboardEntry> {
+        let registers = self
+            .registers
+            .iter()
+            .map(|(name, text)| ClipboardEntry { register: Some(*name), text: text.clone() });
+        let history =
+            self.history.iter().map(|text| ClipboardEntry { register: None, text: text.clone() });
+        registers.chain(history).collect()
+    }
+
     pub fn load(&mut self, ty: ClipboardType) -> String {
         let clipboard = match (ty, &mut self.selection) {
             (ClipboardType::Selection, Some(provider)) => provider,
@@ -90,3 +203,51 @@ impl Clipboard {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_ring() {
+        let mut clipboard = Clipboard::new_nop();
+        clipboard.set_history_size(2);
+
+        clipboard.store(ClipboardType::Clipboard, "a");
+        clipboard.store(ClipboardType::Selection, "b");
+        clipboard.store(ClipboardType::Clipboard, "c");
+        clipboard.store(ClipboardType::Clipboard, "a");
+        clipboard.store(ClipboardType::Clipboard, "d");
+
+        let texts: Vec<_> = clipboard.entries().into_iter().map(|entry| entry.text).collect();
+        assert_eq!(texts, ["d", "a"]);
+    }
+
+    #[test]
+    fn application_stores_remembered() {
+        let mut clipboard = Clipboard::new_nop();
+
+        clipboard.store(ClipboardType::Clipboard, "a");
+        clipboard.store_from_application(ClipboardType::Clipboard, String::from("b"));
+        clipboard.store_from_application(ClipboardType::Selection, String::from("c"));
+
+        let texts: Vec<_> = clipboard.entries().into_iter().map(|entry| entry.text).collect();
+        assert_eq!(texts, ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn registers() {
+        let mut clipboard = Clipboard::new_nop();
+
+        clipboard.store_register('a', String::from("foo"));
+        clipboard.store_register('A', String::from("bar"));
+        clipboard.store_register('1', String::from("ignored"));
+
+        assert_eq!(clipboard.register('a'), Some("foobar"));
+        assert_eq!(clipboard.register('1'), None);
+        assert_eq!(
+            clipboard.entries()[0],
+            ClipboardEntry { register: Some('a'), text: String::from("foobar") }
+        );
+    }
+}
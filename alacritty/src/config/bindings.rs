@@ -1,13 +1,14 @@
 #![allow(clippy::enum_glob_use)]
 
 use std::fmt::{self, Debug, Display};
+use std::slice;
 
 use bitflags::bitflags;
 use serde::de::{self, Error as SerdeError, MapAccess, Unexpected, Visitor};
 use serde::{Deserialize, Deserializer};
 use serde_yaml::Value as SerdeValue;
 use winit::event::VirtualKeyCode::*;
-use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
+use winit::event::{KeyboardInput, ModifiersState, MouseButton, VirtualKeyCode};
 
 use alacritty_config_derive::{ConfigDeserialize, SerdeReplace};
 
@@ -38,6 +39,15 @@ pub struct Binding<T> {
     ///
     /// For example, this might be a key like "G", or a mouse button.
     pub trigger: T,
+
+    /// Key presses required before the trigger, for bindings made of a sequence of keys.
+    pub sequence: Vec<KeyPress>,
+
+    /// Whether the key sequence starts with the configured leader key.
+    pub leader: bool,
+
+    /// User-defined binding mode required to activate binding.
+    pub user_mode: Option<String>,
 }
 
 /// Bindings that are triggered by a keyboard key.
@@ -58,6 +68,19 @@ impl<T: Eq> Binding<T> {
             && !mode.intersects(self.notmode)
     }
 
+    /// Check if the binding is available while the user-defined mode `user_mode` is active.
+    ///
+    /// Bindings without a user-defined mode are available in all of them, unless they write to
+    /// the PTY.
+    #[inline]
+    pub fn is_available_in(&self, user_mode: Option<&str>) -> bool {
+        match (&self.user_mode, user_mode) {
+            (Some(mode), user_mode) => Some(mode.as_str()) == user_mode,
+            (None, Some(_)) => !self.action.actions().iter().any(|action| action.writes_to_pty()),
+            (None, None) => true,
+        }
+    }
+
     #[inline]
     pub fn triggers_match(&self, binding: &Binding<T>) -> bool {
         // Check the binding's key and modifiers.
@@ -65,6 +88,16 @@ impl<T: Eq> Binding<T> {
             return false;
         }
 
+        // Bindings with different key sequences are triggered by different keys.
+        if self.leader != binding.leader || self.sequence != binding.sequence {
+            return false;
+        }
+
+        // Bindings of user-defined modes don't replace bindings outside of them.
+        if self.user_mode != binding.user_mode {
+            return false;
+        }
+
         let selfmode = if self.mode.is_empty() { BindingMode::all() } else { self.mode };
         let bindingmode = if binding.mode.is_empty() { BindingMode::all() } else { binding.mode };
 
@@ -96,6 +129,22 @@ pub enum Action {
     #[config(skip)]
     Hint(Hint),
 
+    /// Enter a user-defined binding mode.
+    #[config(skip)]
+    EnterMode(String),
+
+    /// Execute multiple actions in order.
+    #[config(skip)]
+    List(Vec<Action>),
+
+    /// Stop the macro recording, optionally saving the macro with a name.
+    #[config(skip)]
+    StopMacroRecording(Option<String>),
+
+    /// Replay the last recorded macro or the macro with the given name.
+    #[config(skip)]
+    ReplayMacro(Option<String>),
+
     /// Move vi mode cursor.
     #[config(skip)]
     ViMotion(ViMotion),
@@ -161,6 +210,18 @@ pub enum Action {
     /// Clear the display buffer(s) to remove history.
     ClearHistory,
 
+    /// Write the scrollback history to a file.
+    ExportScrollback,
+
+    /// Show or hide the line timestamp gutter.
+    ToggleTimestamps,
+
+    /// Switch between absolute and relative line timestamps.
+    ToggleTimestampFormat,
+
+    /// Pick an entry of the clipboard history to paste.
+    ClipboardHistory,
+
     /// Hide the Alacritty window.
     Hide,
 
@@ -196,6 +257,12 @@ pub enum Action {
     /// Clear active selection.
     ClearSelection,
 
+    /// Grow the selection to the next larger word, string, line or paragraph.
+    ExpandSelectionSemantic,
+
+    /// Undo the last selection expansion.
+    ShrinkSelection,
+
     /// Toggle vi mode.
     ToggleViMode,
 
@@ -208,10 +275,70 @@ pub enum Action {
     /// Start a backward buffer search.
     SearchBackward,
 
+    /// Leave the active user-defined binding mode.
+    LeaveMode,
+
+    /// Start recording input as a macro.
+    StartMacroRecording,
+
+    /// Send the input of this window to all windows broadcasting their input.
+    ToggleBroadcastInput,
+
+    /// Stop forwarding input to the PTY, while still allowing scrolling and selection.
+    ToggleReadOnly,
+
     /// No action.
     None,
 }
 
+impl Action {
+    /// Check if the action sends text to the PTY.
+    pub fn writes_to_pty(&self) -> bool {
+        matches!(self, Action::Esc(_))
+    }
+
+    /// Actions which are executed by this action, in order.
+    pub fn actions(&self) -> &[Action] {
+        match self {
+            Action::List(actions) => actions,
+            action => slice::from_ref(action),
+        }
+    }
+
+    /// Check if the action enters or leaves a mode, or moves focus away from the window.
+    ///
+    /// Actions in a list which follow such an action are not executed.
+    pub fn changes_focus_or_mode(&self) -> bool {
+        #[cfg(target_os = "macos")]
+        if *self == Action::HideOtherApplications {
+            return true;
+        }
+
+        matches!(
+            self,
+            Action::Hint(_)
+                | Action::EnterMode(_)
+                | Action::LeaveMode
+                | Action::ToggleViMode
+                | Action::SearchForward
+                | Action::SearchBackward
+                | Action::Search(
+                    SearchAction::SearchConfirm
+                        | SearchAction::SearchCancel
+                        | SearchAction::SearchFilter
+                )
+                | Action::ClipboardHistory
+                | Action::ToggleBroadcastInput
+                | Action::ToggleReadOnly
+                | Action::Hide
+                | Action::Minimize
+                | Action::Quit
+                | Action::SpawnNewInstance
+                | Action::CreateNewWindow
+        )
+    }
+}
+
 impl From<&'static str> for Action {
     fn from(s: &'static str) -> Action {
         Action::Esc(s.into())
@@ -277,6 +404,40 @@ pub enum ViAction {
     Open,
     /// Centers the screen around the vi mode cursor.
     CenterAroundViCursor,
+    /// Set a mark at the vi mode cursor, named by the next character.
+    SetMark,
+    /// Jump to the mark named by the next character.
+    GotoMark,
+    /// Jump to the line of the mark named by the next character.
+    GotoMarkLine,
+    /// Jump to the previous position in the jump list.
+    JumpOlder,
+    /// Jump to the next position in the jump list.
+    JumpNewer,
+    /// Copy the selection, or the text covered by the next motion.
+    Yank,
+    /// Start a `g` prefixed command, like `gg`.
+    GPrefix,
+    /// Jump to the next occurrence of a character in the current line.
+    InlineSearchForward,
+    /// Jump to the previous occurrence of a character in the current line.
+    InlineSearchBackward,
+    /// Jump in front of the next occurrence of a character in the current line.
+    InlineSearchForwardShort,
+    /// Jump behind the previous occurrence of a character in the current line.
+    InlineSearchBackwardShort,
+    /// Repeat the last inline search.
+    InlineSearchNext,
+    /// Repeat the last inline search in the opposite direction.
+    InlineSearchPrevious,
+    /// Search forward for the word below the vi mode cursor.
+    SearchWordForward,
+    /// Search backward for the word below the vi mode cursor.
+    SearchWordBackward,
+    /// Use the register named by the next character for the next yank or paste.
+    SelectRegister,
+    /// Paste the selected register or the clipboard.
+    Paste,
 }
 
 /// Search mode specific actions.
@@ -299,6 +460,14 @@ pub enum SearchAction {
     SearchHistoryPrevious,
     /// Go to the next regex in the search history.
     SearchHistoryNext,
+    /// Toggle ignoring case for regexes without uppercase characters.
+    SearchToggleSmartCase,
+    /// Toggle matching the regex text literally.
+    SearchToggleLiteral,
+    /// Toggle only matching entire words.
+    SearchToggleWholeWord,
+    /// Show only the lines matching the search regex.
+    SearchFilter,
 }
 
 /// Mouse binding specific actions.
@@ -358,6 +527,9 @@ macro_rules! bindings {
                 mode: _mode,
                 notmode: _notmode,
                 action: $action.into(),
+                sequence: Vec::new(),
+                leader: false,
+                user_mode: None,
             });
         )*
 
@@ -381,6 +553,7 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         Copy,  +BindingMode::VI; Action::ClearSelection;
         Paste, ~BindingMode::VI; Action::Paste;
         L, ModifiersState::CTRL; Action::ClearLogNotice;
+        Escape, +BindingMode::USER; Action::LeaveMode;
         L,    ModifiersState::CTRL,  ~BindingMode::VI, ~BindingMode::SEARCH;
             Action::Esc("\x0c".into());
         Tab,  ModifiersState::SHIFT, ~BindingMode::VI, ~BindingMode::SEARCH;
@@ -468,9 +641,9 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         E,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ScrollLineDown;
         G,                             +BindingMode::VI, ~BindingMode::SEARCH;
-            Action::ScrollToTop;
+            ViAction::GPrefix;
         G,      ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
-            Action::ScrollToBottom;
+            ViMotion::Bottom;
         B,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ScrollPageUp;
         F,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
@@ -479,9 +652,12 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             Action::ScrollHalfPageUp;
         D,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
             Action::ScrollHalfPageDown;
-        Y,                             +BindingMode::VI, ~BindingMode::SEARCH; Action::Copy;
         Y,                             +BindingMode::VI, ~BindingMode::SEARCH;
-            Action::ClearSelection;
+            ViAction::Yank;
+        Apostrophe, ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::SelectRegister;
+        P,                             +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::Paste;
         Slash,                         +BindingMode::VI, ~BindingMode::SEARCH;
             Action::SearchForward;
         Slash,  ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
@@ -498,10 +674,36 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             ViAction::SearchNext;
         N,      ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
             ViAction::SearchPrevious;
+        Key8,   ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::SearchWordForward;
+        Key3,   ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::SearchWordBackward;
         Return,                        +BindingMode::VI, ~BindingMode::SEARCH;
             ViAction::Open;
         Z,                             +BindingMode::VI, ~BindingMode::SEARCH;
             ViAction::CenterAroundViCursor;
+        M,                             +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::SetMark;
+        Grave,                         +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::GotoMark;
+        Apostrophe,                    +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::GotoMarkLine;
+        O,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpOlder;
+        I,      ModifiersState::CTRL,  +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::JumpNewer;
+        F,                             +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchForward;
+        F,      ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchBackward;
+        T,                             +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchForwardShort;
+        T,      ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchBackwardShort;
+        Semicolon,                     +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchNext;
+        Comma,                         +BindingMode::VI, ~BindingMode::SEARCH;
+            ViAction::InlineSearchPrevious;
         K,                             +BindingMode::VI, ~BindingMode::SEARCH;
             ViMotion::Up;
         J,                             +BindingMode::VI, ~BindingMode::SEARCH;
@@ -544,6 +746,10 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
             ViMotion::WordRightEnd;
         Key5,   ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
             ViMotion::Bracket;
+        LBracket, ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViMotion::ParagraphUp;
+        RBracket, ModifiersState::SHIFT, +BindingMode::VI, ~BindingMode::SEARCH;
+            ViMotion::ParagraphDown;
         Return,                        +BindingMode::SEARCH, +BindingMode::VI;
             SearchAction::SearchConfirm;
         Escape,                        +BindingMode::SEARCH; SearchAction::SearchCancel;
@@ -554,6 +760,10 @@ pub fn default_key_bindings() -> Vec<KeyBinding> {
         N,      ModifiersState::CTRL,  +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
         Up,                            +BindingMode::SEARCH; SearchAction::SearchHistoryPrevious;
         Down,                          +BindingMode::SEARCH; SearchAction::SearchHistoryNext;
+        C,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleSmartCase;
+        R,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleLiteral;
+        W,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchToggleWholeWord;
+        F,      ModifiersState::ALT,   +BindingMode::SEARCH; SearchAction::SearchFilter;
         Return,                        +BindingMode::SEARCH, ~BindingMode::VI;
             SearchAction::SearchFocusNext;
         Return, ModifiersState::SHIFT, +BindingMode::SEARCH, ~BindingMode::VI;
@@ -756,6 +966,27 @@ impl<'a> Deserialize<'a> for Key {
     }
 }
 
+/// Key press with its modifiers, which is part of a key sequence.
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    pub key: Key,
+    #[serde(default)]
+    pub mods: ModsWrapper,
+}
+
+impl KeyPress {
+    /// Check if the key press is matched by keyboard input with the modifiers `mods`.
+    pub fn matches(&self, input: &KeyboardInput, mods: ModifiersState) -> bool {
+        let key = match (self.key, input.virtual_keycode) {
+            (Key::Scancode(_), _) => Key::Scancode(input.scancode),
+            (_, Some(key)) => Key::Keycode(key),
+            _ => return false,
+        };
+
+        self.key == key && self.mods.0 == mods
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct ModeWrapper {
     pub mode: BindingMode,
@@ -770,21 +1001,45 @@ bitflags! {
         const ALT_SCREEN          = 0b0000_0100;
         const VI                  = 0b0000_1000;
         const SEARCH              = 0b0001_0000;
+        /// Any user-defined binding mode is active.
+        const USER                = 0b0010_0000;
     }
 }
 
 impl BindingMode {
-    pub fn new(mode: &TermMode, search: bool) -> BindingMode {
+    pub fn new(mode: &TermMode, search: bool, user_mode: bool) -> BindingMode {
         let mut binding_mode = BindingMode::empty();
         binding_mode.set(BindingMode::APP_CURSOR, mode.contains(TermMode::APP_CURSOR));
         binding_mode.set(BindingMode::APP_KEYPAD, mode.contains(TermMode::APP_KEYPAD));
         binding_mode.set(BindingMode::ALT_SCREEN, mode.contains(TermMode::ALT_SCREEN));
         binding_mode.set(BindingMode::VI, mode.contains(TermMode::VI));
         binding_mode.set(BindingMode::SEARCH, search);
+        binding_mode.set(BindingMode::USER, user_mode);
         binding_mode
     }
 }
 
+impl ModeWrapper {
+    /// Add a built-in mode like `Vi` or `~Alt`, returning `false` if the name is unknown.
+    fn insert(&mut self, name: &str) -> bool {
+        match name.trim().to_lowercase().as_str() {
+            "appcursor" => self.mode |= BindingMode::APP_CURSOR,
+            "~appcursor" => self.not_mode |= BindingMode::APP_CURSOR,
+            "appkeypad" => self.mode |= BindingMode::APP_KEYPAD,
+            "~appkeypad" => self.not_mode |= BindingMode::APP_KEYPAD,
+            "alt" => self.mode |= BindingMode::ALT_SCREEN,
+            "~alt" => self.not_mode |= BindingMode::ALT_SCREEN,
+            "vi" => self.mode |= BindingMode::VI,
+            "~vi" => self.not_mode |= BindingMode::VI,
+            "search" => self.mode |= BindingMode::SEARCH,
+            "~search" => self.not_mode |= BindingMode::SEARCH,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
 impl Default for ModeWrapper {
     fn default() -> Self {
         Self { mode: BindingMode::empty(), not_mode: BindingMode::empty() }
@@ -811,22 +1066,11 @@ impl<'a> Deserialize<'a> for ModeWrapper {
             where
                 E: de::Error,
             {
-                let mut res =
-                    ModeWrapper { mode: BindingMode::empty(), not_mode: BindingMode::empty() };
+                let mut res = ModeWrapper::default();
 
                 for modifier in value.split('|') {
-                    match modifier.trim().to_lowercase().as_str() {
-                        "appcursor" => res.mode |= BindingMode::APP_CURSOR,
-                        "~appcursor" => res.not_mode |= BindingMode::APP_CURSOR,
-                        "appkeypad" => res.mode |= BindingMode::APP_KEYPAD,
-                        "~appkeypad" => res.not_mode |= BindingMode::APP_KEYPAD,
-                        "alt" => res.mode |= BindingMode::ALT_SCREEN,
-                        "~alt" => res.not_mode |= BindingMode::ALT_SCREEN,
-                        "vi" => res.mode |= BindingMode::VI,
-                        "~vi" => res.not_mode |= BindingMode::VI,
-                        "search" => res.mode |= BindingMode::SEARCH,
-                        "~search" => res.not_mode |= BindingMode::SEARCH,
-                        _ => return Err(E::invalid_value(Unexpected::Str(modifier), &self)),
+                    if !res.insert(modifier) {
+                        return Err(E::invalid_value(Unexpected::Str(modifier), &self));
                     }
                 }
 
@@ -837,6 +1081,80 @@ impl<'a> Deserialize<'a> for ModeWrapper {
     }
 }
 
+/// Deserialize the action of a binding, which can also be a list of actions.
+fn deserialize_action<E: de::Error>(value: SerdeValue) -> Result<Action, E> {
+    match value {
+        SerdeValue::Sequence(values) => {
+            let mut actions = Vec::with_capacity(values.len());
+            for value in values {
+                if value.is_sequence() {
+                    return Err(E::custom("action lists can't be nested"));
+                }
+
+                actions.push(deserialize_action(value)?);
+            }
+            Ok(Action::List(actions))
+        },
+        value => deserialize_single_action(value),
+    }
+}
+
+/// Deserialize a single binding action.
+fn deserialize_single_action<E: de::Error>(value: SerdeValue) -> Result<Action, E> {
+    if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
+        return Ok(vi_action.into());
+    } else if let Ok(vi_motion) = ViMotion::deserialize(value.clone()) {
+        return Ok(vi_motion.into());
+    } else if let Ok(search_action) = SearchAction::deserialize(value.clone()) {
+        return Ok(search_action.into());
+    } else if let Ok(mouse_action) = MouseAction::deserialize(value.clone()) {
+        return Ok(mouse_action.into());
+    } else if let Some(action) = action_with_argument(&value) {
+        return Ok(action);
+    }
+
+    // Actions with optional arguments.
+    match value.as_str().map(str::to_lowercase).as_deref() {
+        Some("stopmacrorecording") => return Ok(Action::StopMacroRecording(None)),
+        Some("replaymacro") => return Ok(Action::ReplayMacro(None)),
+        _ => (),
+    }
+
+    match Action::deserialize(value.clone()).map_err(E::custom) {
+        Ok(action) => Ok(action),
+        Err(err) => {
+            let value = match value {
+                SerdeValue::String(string) => string,
+                SerdeValue::Mapping(map) if map.len() == 1 => match map.into_iter().next() {
+                    Some((SerdeValue::String(string), SerdeValue::Null)) => string,
+                    _ => return Err(err),
+                },
+                _ => return Err(err),
+            };
+            Err(E::custom(format!("unknown keyboard action `{}`", value)))
+        },
+    }
+}
+
+/// Action taking an argument, like `EnterMode: Window` or `Esc: "\x0c"`.
+fn action_with_argument(value: &SerdeValue) -> Option<Action> {
+    let map = value.as_mapping().filter(|map| map.len() == 1)?;
+    let (name, argument) = map.iter().next()?;
+    let argument = argument.clone();
+    match name.as_str()?.to_lowercase().as_str() {
+        "entermode" => String::deserialize(argument).ok().map(Action::EnterMode),
+        "esc" => String::deserialize(argument).ok().map(Action::Esc),
+        "command" => Program::deserialize(argument).ok().map(Action::Command),
+        "stopmacrorecording" => {
+            String::deserialize(argument).ok().map(|name| Action::StopMacroRecording(Some(name)))
+        },
+        "replaymacro" => {
+            String::deserialize(argument).ok().map(|name| Action::ReplayMacro(Some(name)))
+        },
+        _ => None,
+    }
+}
+
 struct MouseButtonWrapper(MouseButton);
 
 impl MouseButtonWrapper {
@@ -896,6 +1214,9 @@ struct RawBinding {
     mode: BindingMode,
     notmode: BindingMode,
     action: Action,
+    sequence: Vec<KeyPress>,
+    leader: bool,
+    user_mode: Option<String>,
 }
 
 impl RawBinding {
@@ -907,6 +1228,9 @@ impl RawBinding {
                 action: self.action,
                 mode: self.mode,
                 notmode: self.notmode,
+                sequence: self.sequence,
+                leader: self.leader,
+                user_mode: self.user_mode,
             })
         } else {
             Err(Box::new(self))
@@ -921,6 +1245,9 @@ impl RawBinding {
                 action: self.action,
                 mode: self.mode,
                 notmode: self.notmode,
+                sequence: self.sequence,
+                leader: self.leader,
+                user_mode: self.user_mode,
             })
         } else {
             Err(Box::new(self))
@@ -933,7 +1260,8 @@ impl<'a> Deserialize<'a> for RawBinding {
     where
         D: Deserializer<'a>,
     {
-        const FIELDS: &[&str] = &["key", "mods", "mode", "action", "chars", "mouse", "command"];
+        const FIELDS: &[&str] =
+            &["key", "mods", "mode", "action", "chars", "mouse", "command", "sequence", "leader"];
 
         enum Field {
             Key,
@@ -943,6 +1271,8 @@ impl<'a> Deserialize<'a> for RawBinding {
             Chars,
             Mouse,
             Command,
+            Sequence,
+            Leader,
         }
 
         impl<'a> Deserialize<'a> for Field {
@@ -971,6 +1301,8 @@ impl<'a> Deserialize<'a> for RawBinding {
                             "chars" => Ok(Field::Chars),
                             "mouse" => Ok(Field::Mouse),
                             "command" => Ok(Field::Command),
+                            "sequence" => Ok(Field::Sequence),
+                            "leader" => Ok(Field::Leader),
                             _ => Err(E::unknown_field(value, FIELDS)),
                         }
                     }
@@ -1000,6 +1332,9 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let mut not_mode: Option<BindingMode> = None;
                 let mut mouse: Option<MouseButton> = None;
                 let mut command: Option<Program> = None;
+                let mut sequence: Option<Vec<KeyPress>> = None;
+                let mut leader: Option<bool> = None;
+                let mut user_mode: Option<String> = None;
 
                 use de::Error;
 
@@ -1037,9 +1372,30 @@ impl<'a> Deserialize<'a> for RawBinding {
                                 return Err(<V::Error as Error>::duplicate_field("mode"));
                             }
 
-                            let mode_deserializer = map.next_value::<ModeWrapper>()?;
-                            mode = Some(mode_deserializer.mode);
-                            not_mode = Some(mode_deserializer.not_mode);
+                            // Names which aren't built-in modes refer to a user-defined mode.
+                            let mut modes = ModeWrapper::default();
+                            for name in map.next_value::<String>()?.split('|') {
+                                if modes.insert(name) {
+                                    continue;
+                                }
+
+                                let name = name.trim();
+                                if let Some(name) = name.strip_prefix('~') {
+                                    return Err(V::Error::custom(format!(
+                                        "user-defined mode `{}` can't be negated",
+                                        name,
+                                    )));
+                                } else if user_mode.is_some() {
+                                    return Err(V::Error::custom(
+                                        "bindings can only use one user-defined mode",
+                                    ));
+                                }
+
+                                user_mode = Some(name.into());
+                            }
+
+                            mode = Some(modes.mode);
+                            not_mode = Some(modes.not_mode);
                         },
                         Field::Action => {
                             if action.is_some() {
@@ -1047,42 +1403,7 @@ impl<'a> Deserialize<'a> for RawBinding {
                             }
 
                             let value = map.next_value::<SerdeValue>()?;
-
-                            action = if let Ok(vi_action) = ViAction::deserialize(value.clone()) {
-                                Some(vi_action.into())
-                            } else if let Ok(vi_motion) = ViMotion::deserialize(value.clone()) {
-                                Some(vi_motion.into())
-                            } else if let Ok(search_action) =
-                                SearchAction::deserialize(value.clone())
-                            {
-                                Some(search_action.into())
-                            } else if let Ok(mouse_action) = MouseAction::deserialize(value.clone())
-                            {
-                                Some(mouse_action.into())
-                            } else {
-                                match Action::deserialize(value.clone()).map_err(V::Error::custom) {
-                                    Ok(action) => Some(action),
-                                    Err(err) => {
-                                        let value = match value {
-                                            SerdeValue::String(string) => string,
-                                            SerdeValue::Mapping(map) if map.len() == 1 => {
-                                                match map.into_iter().next() {
-                                                    Some((
-                                                        SerdeValue::String(string),
-                                                        SerdeValue::Null,
-                                                    )) => string,
-                                                    _ => return Err(err),
-                                                }
-                                            },
-                                            _ => return Err(err),
-                                        };
-                                        return Err(V::Error::custom(format!(
-                                            "unknown keyboard action `{}`",
-                                            value
-                                        )));
-                                    },
-                                }
-                            };
+                            action = Some(deserialize_action(value)?);
                         },
                         Field::Chars => {
                             if chars.is_some() {
@@ -1105,6 +1426,20 @@ impl<'a> Deserialize<'a> for RawBinding {
 
                             command = Some(map.next_value::<Program>()?);
                         },
+                        Field::Sequence => {
+                            if sequence.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("sequence"));
+                            }
+
+                            sequence = Some(map.next_value()?);
+                        },
+                        Field::Leader => {
+                            if leader.is_some() {
+                                return Err(<V::Error as Error>::duplicate_field("leader"));
+                            }
+
+                            leader = Some(map.next_value()?);
+                        },
                     }
                 }
 
@@ -1113,51 +1448,67 @@ impl<'a> Deserialize<'a> for RawBinding {
                 let mods = mods.unwrap_or_default();
 
                 let action = match (action, chars, command) {
-                    (Some(action @ Action::ViMotion(_)), None, None)
-                    | (Some(action @ Action::Vi(_)), None, None) => {
-                        if !mode.intersects(BindingMode::VI) || not_mode.intersects(BindingMode::VI)
+                    (Some(action), None, None) => action,
+                    (None, Some(chars), None) => Action::Esc(chars),
+                    (None, None, Some(cmd)) => Action::Command(cmd),
+                    _ => {
+                        return Err(V::Error::custom(
+                            "must specify exactly one of chars, action or command",
+                        ));
+                    },
+                };
+
+                for action in action.actions() {
+                    match action {
+                        Action::ViMotion(_) | Action::Vi(_)
+                            if !mode.intersects(BindingMode::VI)
+                                || not_mode.intersects(BindingMode::VI) =>
                         {
                             return Err(V::Error::custom(format!(
                                 "action `{}` is only available in vi mode, try adding `mode: Vi`",
                                 action,
                             )));
-                        }
-                        action
-                    },
-                    (Some(action @ Action::Search(_)), None, None) => {
-                        if !mode.intersects(BindingMode::SEARCH) {
+                        },
+                        Action::Search(_) if !mode.intersects(BindingMode::SEARCH) => {
                             return Err(V::Error::custom(format!(
                                 "action `{}` is only available in search mode, try adding `mode: \
                                  Search`",
                                 action,
                             )));
-                        }
-                        action
-                    },
-                    (Some(action @ Action::Mouse(_)), None, None) => {
-                        if mouse.is_none() {
+                        },
+                        Action::Mouse(_) if mouse.is_none() => {
                             return Err(V::Error::custom(format!(
                                 "action `{}` is only available for mouse bindings",
                                 action,
                             )));
-                        }
-                        action
-                    },
-                    (Some(action), None, None) => action,
-                    (None, Some(chars), None) => Action::Esc(chars),
-                    (None, None, Some(cmd)) => Action::Command(cmd),
-                    _ => {
-                        return Err(V::Error::custom(
-                            "must specify exactly one of chars, action or command",
-                        ));
-                    },
-                };
+                        },
+                        _ => (),
+                    }
+                }
 
                 if mouse.is_none() && key.is_none() {
                     return Err(V::Error::custom("bindings require mouse button or key"));
                 }
 
-                Ok(RawBinding { mode, notmode: not_mode, action, key, mouse, mods })
+                let sequence = sequence.unwrap_or_default();
+                let leader = leader.unwrap_or_default();
+                if mouse.is_some() && (leader || !sequence.is_empty()) {
+                    return Err(V::Error::custom(
+                        "key sequences are only available for key bindings",
+                    ));
+                }
+
+                Ok(RawBinding {
+                    mode,
+                    notmode: not_mode,
+                    action,
+                    key,
+                    mouse,
+                    mods,
+                    sequence,
+                    leader,
+                    user_mode,
+                })
             }
         }
 
@@ -1254,6 +1605,9 @@ mod tests {
                 mode: BindingMode::empty(),
                 notmode: BindingMode::empty(),
                 trigger: Default::default(),
+                sequence: Vec::new(),
+                leader: false,
+                user_mode: None,
             }
         }
     }
@@ -1443,4 +1797,86 @@ mod tests {
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN, mods, &t));
         assert!(!binding.is_triggered_by(BindingMode::ALT_SCREEN | BindingMode::VI, mods, &t));
     }
+
+    #[test]
+    fn key_sequence_bindings() {
+        let yaml = "{ key: C, sequence: [{ key: A, mods: Control }], action: SpawnNewInstance }";
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        let press = KeyPress { key: Key::Keycode(A), mods: ModsWrapper(ModifiersState::CTRL) };
+        assert_eq!(binding.sequence, [press]);
+        assert!(!binding.leader);
+
+        // Bindings are only replaced by bindings with the same sequence.
+        let single: KeyBinding = serde_yaml::from_str("{ key: C, action: Copy }").unwrap();
+        assert!(!binding.triggers_match(&single));
+
+        let leader: KeyBinding =
+            serde_yaml::from_str("{ key: C, leader: true, chars: c }").unwrap();
+        assert!(leader.leader && leader.sequence.is_empty());
+
+        let mouse = serde_yaml::from_str::<MouseBinding>("{ mouse: Left, leader: true, chars: c }");
+        assert!(mouse.is_err());
+    }
+
+    #[test]
+    fn action_lists() {
+        let yaml = r#"{ key: L, action: [ScrollToBottom, ClearSelection, Esc: "\x0c"] }"#;
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        let actions = [Action::ScrollToBottom, Action::ClearSelection, Action::from("\x0c")];
+        assert_eq!(binding.action.actions(), actions);
+
+        // Mode specific actions are validated for every action in the list.
+        let yaml = "{ key: L, action: [ClearSelection, ToggleNormalSelection] }";
+        assert!(serde_yaml::from_str::<KeyBinding>(yaml).is_err());
+        let yaml = "{ key: L, mode: Vi, action: [ClearSelection, ToggleNormalSelection] }";
+        assert!(serde_yaml::from_str::<KeyBinding>(yaml).is_ok());
+
+        let yaml = "{ key: L, action: [ClearSelection, [Copy]] }";
+        assert!(serde_yaml::from_str::<KeyBinding>(yaml).is_err());
+    }
+
+    #[test]
+    fn macro_actions() {
+        let binding: KeyBinding = serde_yaml::from_str("{ key: R, action: ReplayMacro }").unwrap();
+        assert_eq!(binding.action, Action::ReplayMacro(None));
+
+        let yaml = "{ key: R, action: { ReplayMacro: install } }";
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(binding.action, Action::ReplayMacro(Some(String::from("install"))));
+
+        let yaml = "{ key: S, action: { StopMacroRecording: install } }";
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(binding.action, Action::StopMacroRecording(Some(String::from("install"))));
+
+        let yaml = "{ key: S, action: StartMacroRecording }";
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(binding.action, Action::StartMacroRecording);
+    }
+
+    #[test]
+    fn user_mode_bindings() {
+        let yaml = "{ key: H, mode: Window | ~Vi, action: { EnterMode: Resize } }";
+        let binding: KeyBinding = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(binding.user_mode.as_deref(), Some("Window"));
+        assert_eq!(binding.notmode, BindingMode::VI);
+        assert_eq!(binding.action, Action::EnterMode(String::from("Resize")));
+
+        assert!(binding.is_available_in(Some("Window")));
+        assert!(!binding.is_available_in(Some("Resize")));
+        assert!(!binding.is_available_in(None));
+
+        // Bindings outside of user-defined modes are available in all of them.
+        let global: KeyBinding = serde_yaml::from_str("{ key: H, action: LeaveMode }").unwrap();
+        assert!(global.is_available_in(Some("Window")));
+        assert!(!binding.triggers_match(&global));
+
+        // Except for bindings writing to the PTY.
+        let global: KeyBinding = serde_yaml::from_str("{ key: H, chars: h }").unwrap();
+        assert!(!global.is_available_in(Some("Window")));
+        assert!(global.is_available_in(None));
+
+        assert!(serde_yaml::from_str::<KeyBinding>("{ key: H, mode: ~Window, chars: h }").is_err());
+        let yaml = "{ key: H, mode: Window | Resize, chars: h }";
+        assert!(serde_yaml::from_str::<KeyBinding>(yaml).is_err());
+    }
 }
@@ -0,0 +1,17 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Default number of entries in the clipboard history.
+pub const DEFAULT_HISTORY_SIZE: usize = 20;
+
+/// Clipboard history configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardConfig {
+    /// Number of copied texts kept in the clipboard history.
+    pub history_size: usize,
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self { history_size: DEFAULT_HISTORY_SIZE }
+    }
+}
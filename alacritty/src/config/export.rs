@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Scrollback export configuration.
+#[derive(ConfigDeserialize, Default, Clone, Debug, PartialEq, Eq)]
+pub struct ExportConfig {
+    /// Format used for exporting the scrollback history.
+    pub format: ExportFormat,
+
+    /// Directory in which exported files are created.
+    directory: Option<PathBuf>,
+}
+
+impl ExportConfig {
+    /// Candidate paths for a new export file.
+    ///
+    /// The first path is `alacritty-<TIMESTAMP>.<EXTENSION>`, followed by paths with an additional
+    /// counter to avoid overwriting exports created within the same second.
+    pub fn paths(&self, format: ExportFormat) -> impl Iterator<Item = PathBuf> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let directory = self.directory.clone().or_else(dirs::home_dir).unwrap_or_default();
+        let extension = format.extension();
+
+        (0..).map(move |index| {
+            let file_name = match index {
+                0 => format!("alacritty-{}.{}", timestamp, extension),
+                _ => format!("alacritty-{}-{}.{}", timestamp, index, extension),
+            };
+            directory.join(file_name)
+        })
+    }
+}
+
+/// Format of the exported scrollback history.
+#[derive(ConfigDeserialize, ValueEnum, Serialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Text without any formatting.
+    Plain,
+
+    /// Text with colors and attributes as SGR escape sequences.
+    Ansi,
+
+    /// Self-contained HTML document using the current color palette.
+    Html,
+}
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        Self::Plain
+    }
+}
+
+impl ExportFormat {
+    /// File extension for the format.
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Plain => "txt",
+            Self::Ansi => "ansi",
+            Self::Html => "html",
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use alacritty_config_derive::ConfigDeserialize;
+
+use crate::config::bindings::KeyPress;
+
+/// Key sequence binding configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeySequenceConfig {
+    /// Key starting the sequences of bindings with `leader: true`.
+    pub leader: Option<KeyPress>,
+
+    /// Time in milliseconds to wait for the next key of a sequence.
+    timeout: u16,
+}
+
+impl Default for KeySequenceConfig {
+    fn default() -> Self {
+        Self { leader: None, timeout: 1000 }
+    }
+}
+
+impl KeySequenceConfig {
+    /// Time to wait for the next key of a sequence, `None` to wait indefinitely.
+    pub fn timeout(&self) -> Option<Duration> {
+        (self.timeout != 0).then(|| Duration::from_millis(self.timeout as u64))
+    }
+}
@@ -10,11 +10,17 @@ use serde_yaml::Value;
 use alacritty_terminal::config::LOG_TARGET_CONFIG;
 
 pub mod bell;
+pub mod clipboard;
 pub mod color;
 pub mod debug;
+pub mod export;
 pub mod font;
+pub mod key_sequence;
 pub mod monitor;
+pub mod paste;
+pub mod search;
 pub mod serde_utils;
+pub mod timestamps;
 pub mod ui_config;
 pub mod window;
 
@@ -23,7 +29,8 @@ mod mouse;
 
 use crate::cli::Options;
 pub use crate::config::bindings::{
-    Action, Binding, BindingMode, Key, MouseAction, SearchAction, ViAction,
+    Action, Binding, BindingMode, KeyPress, ModsWrapper, MouseAction, MouseBinding,
+    SearchAction, ViAction,
 };
 #[cfg(test)]
 pub use crate::config::mouse::{ClickHandler, Mouse};
@@ -143,6 +150,14 @@ fn after_loading(config: &mut UiConfig, options: &Options) {
 
     // Create key bindings for regex hints.
     config.generate_hint_bindings();
+
+    // Drop bindings using unknown binding modes.
+    config.validate_binding_modes();
+
+    // Reset history sizes which require compression.
+    if let Err(err) = config.terminal_config.scrolling.validate_history() {
+        error!(target: LOG_TARGET_CONFIG, "Config error: {}", err);
+    }
 }
 
 /// Load configuration file and log errors.
@@ -2,9 +2,25 @@ use std::time::Duration;
 
 use alacritty_config_derive::ConfigDeserialize;
 
+use crate::config::ui_config::{LazyRegex, URL_REGEX};
+
+/// Regexes tried at the clicked point before falling back to semantic selection.
+#[rustfmt::skip]
+const DOUBLE_CLICK_REGEXES: [&str; 5] = [
+    URL_REGEX,
+    // File paths, optionally followed by line and column.
+    r"[\w.~+-]*(/[\w.~+-]+)+(:\d+){0,2}|[\w.~+-]+(:\d+){1,2}",
+    // IPv4 addresses, optionally followed by a port.
+    r"\d{1,3}(\.\d{1,3}){3}(:\d+)?",
+    // UUIDs.
+    r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+    // Double quoted strings, including the quotes.
+    r#""[^"]*""#,
+];
+
 #[derive(ConfigDeserialize, Default, Clone, Debug, PartialEq, Eq)]
 pub struct Mouse {
-    pub double_click: ClickHandler,
+    pub double_click: DoubleClickHandler,
     pub triple_click: ClickHandler,
     pub hide_when_typing: bool,
     #[config(deprecated = "use `hints` section instead")]
@@ -27,3 +43,64 @@ impl ClickHandler {
         Duration::from_millis(self.threshold as u64)
     }
 }
+
+#[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DoubleClickHandler {
+    threshold: u16,
+
+    /// Regexes selecting the match at the clicked point, in order of priority.
+    pub regex: Vec<LazyRegex>,
+}
+
+impl Default for DoubleClickHandler {
+    fn default() -> Self {
+        Self {
+            threshold: ClickHandler::default().threshold,
+            regex: DOUBLE_CLICK_REGEXES.iter().map(|regex| LazyRegex::new(*regex)).collect(),
+        }
+    }
+}
+
+impl DoubleClickHandler {
+    pub fn threshold(&self) -> Duration {
+        Duration::from_millis(self.threshold as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::index::{Column, Line, Point};
+    use alacritty_terminal::term::test::mock_term;
+
+    use super::*;
+    use crate::display::hint;
+
+    #[test]
+    fn double_click_regex_matches() {
+        let term = mock_term(
+            "see src/foo.rs:120:5 at 10.0.0.1:80\r\n1b4e28ba-2fa1-11d2-883f-0016d3cca427 \"a b\"",
+        );
+        let regex = DoubleClickHandler::default().regex;
+
+        let match_at = |line, column| {
+            let point = Point::new(Line(line), Column(column));
+            regex.iter().find_map(|regex| {
+                regex.with_compiled(|regex| hint::regex_match_at(&term, point, regex, false))
+            })
+        };
+
+        let path = match_at(0, 8).unwrap();
+        assert_eq!(path, Point::new(Line(0), Column(4))..=Point::new(Line(0), Column(19)));
+
+        let ip = match_at(0, 26).unwrap();
+        assert_eq!(ip, Point::new(Line(0), Column(24))..=Point::new(Line(0), Column(34)));
+
+        let uuid = match_at(1, 12).unwrap();
+        assert_eq!(uuid, Point::new(Line(1), Column(0))..=Point::new(Line(1), Column(35)));
+
+        let quoted = match_at(1, 39).unwrap();
+        assert_eq!(quoted, Point::new(Line(1), Column(37))..=Point::new(Line(1), Column(41)));
+
+        assert_eq!(match_at(0, 1), None);
+    }
+}
@@ -0,0 +1,100 @@
+use std::borrow::Cow;
+
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Paste safety configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PasteConfig {
+    /// Ask for confirmation before pasting text which could run commands.
+    pub confirm: bool,
+
+    /// Number of characters above which pastes require confirmation, `0` for no limit.
+    pub confirm_length: usize,
+
+    /// Remove line breaks at the end of pasted text.
+    pub strip_trailing_newlines: bool,
+
+    /// Number of spaces replacing each tab, `0` to keep tabs.
+    pub tabs_to_spaces: usize,
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            confirm: true,
+            confirm_length: 4096,
+            strip_trailing_newlines: false,
+            tabs_to_spaces: 0,
+        }
+    }
+}
+
+impl PasteConfig {
+    /// Apply the configured transforms to pasted text.
+    pub fn transform<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let text =
+            if self.strip_trailing_newlines { text.trim_end_matches(['\r', '\n']) } else { text };
+
+        if self.tabs_to_spaces > 0 && text.contains('\t') {
+            Cow::Owned(text.replace('\t', &" ".repeat(self.tabs_to_spaces)))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Description of the risky content which requires confirmation before pasting the text.
+    pub fn risk(&self, text: &str, bracketed: bool) -> Option<String> {
+        if !self.confirm {
+            return None;
+        }
+
+        let text_len = text.chars().count();
+        let line_break = |c: char| c == '\n' || c == '\r';
+        if self.confirm_length > 0 && text_len > self.confirm_length {
+            Some(format!("{} characters", text_len))
+        } else if text.ends_with(line_break) {
+            Some(String::from("a trailing newline"))
+        } else if !bracketed && text.contains(line_break) {
+            Some(format!("{} lines", text.lines().count()))
+        } else if text.chars().any(|c| c.is_control() && !line_break(c) && c != '\t') {
+            Some(String::from("control characters"))
+        } else {
+            None
+        }
+    }
+}
+
+/// Join the lines of a text with spaces.
+pub fn single_line(text: &str) -> String {
+    let lines: Vec<&str> =
+        text.lines().map(str::trim_end).filter(|line| !line.is_empty()).collect();
+    lines.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_transforms() {
+        let config =
+            PasteConfig { strip_trailing_newlines: true, tabs_to_spaces: 2, ..Default::default() };
+        assert_eq!(config.transform("a\tb\r\n\n"), "a  b");
+        assert_eq!(PasteConfig::default().transform("a\tb\n"), "a\tb\n");
+        assert_eq!(single_line("echo a\r\n\necho b  \n"), "echo a echo b");
+    }
+
+    #[test]
+    fn paste_risks() {
+        let config = PasteConfig { confirm_length: 5, ..Default::default() };
+        assert_eq!(config.risk("ls", false), None);
+        assert_eq!(config.risk("ls\n", true).as_deref(), Some("a trailing newline"));
+        assert_eq!(config.risk("a\nb", false).as_deref(), Some("2 lines"));
+        assert_eq!(config.risk("a\nb", true), None);
+        assert_eq!(config.risk("a\x1bb", true).as_deref(), Some("control characters"));
+        assert_eq!(config.risk("abcdef", true).as_deref(), Some("6 characters"));
+
+        let config = PasteConfig { confirm: false, ..config };
+        assert_eq!(config.risk("a\nb\n", false), None);
+    }
+}
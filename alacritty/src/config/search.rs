@@ -0,0 +1,17 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Regex search configuration.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// Maximum number of regexes in the search history.
+    pub history_size: usize,
+
+    /// Keep the search history in a file shared by all Alacritty instances.
+    pub persistent_history: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self { history_size: 255, persistent_history: true }
+    }
+}
@@ -0,0 +1,57 @@
+use alacritty_config_derive::ConfigDeserialize;
+
+/// Line timestamp configuration.
+#[derive(ConfigDeserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimestampsConfig {
+    /// Show the timestamp gutter by default.
+    enabled: bool,
+
+    /// Format of the timestamps shown in the gutter.
+    format: TimestampFormat,
+}
+
+impl TimestampsConfig {
+    /// Initial format of the timestamp gutter, `None` when it is hidden.
+    pub fn gutter(&self) -> Option<TimestampFormat> {
+        self.enabled.then(|| self.format)
+    }
+
+    /// Format used when the timestamp gutter is enabled.
+    pub fn format(&self) -> TimestampFormat {
+        self.format
+    }
+}
+
+/// Format of the timestamps shown in the gutter.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Local time of day, like `13:37:00`.
+    Absolute,
+
+    /// Time elapsed since the line was written, like `5m`.
+    Relative,
+}
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        Self::Absolute
+    }
+}
+
+impl TimestampFormat {
+    /// Number of columns required for a timestamp.
+    pub const fn width(self) -> usize {
+        match self {
+            Self::Absolute => 8,
+            Self::Relative => 4,
+        }
+    }
+
+    /// The other timestamp format.
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Absolute => Self::Relative,
+            Self::Relative => Self::Absolute,
+        }
+    }
+}
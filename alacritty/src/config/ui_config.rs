@@ -19,15 +19,21 @@ use crate::config::bell::BellConfig;
 use crate::config::bindings::{
     self, Action, Binding, Key, KeyBinding, ModeWrapper, ModsWrapper, MouseBinding,
 };
+use crate::config::clipboard::ClipboardConfig;
 use crate::config::color::Colors;
 use crate::config::debug::Debug;
+use crate::config::export::ExportConfig;
 use crate::config::font::Font;
+use crate::config::key_sequence::KeySequenceConfig;
 use crate::config::mouse::Mouse;
+use crate::config::paste::PasteConfig;
+use crate::config::search::SearchConfig;
+use crate::config::timestamps::TimestampsConfig;
 use crate::config::window::WindowConfig;
 
 /// Regex used for the default URL hint.
 #[rustfmt::skip]
-const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini:|gopher:|https:|http:|news:|file:|git:|ssh:|ftp:)\
+pub const URL_REGEX: &str = "(ipfs:|ipns:|magnet:|mailto:|gemini:|gopher:|https:|http:|news:|file:|git:|ssh:|ftp:)\
                          [^\u{0000}-\u{001F}\u{007F}-\u{009F}<>\"\\s{-}\\^⟨⟩`]+";
 
 #[derive(ConfigDeserialize, Clone, Debug, PartialEq)]
@@ -54,6 +60,21 @@ pub struct UiConfig {
     /// Bell configuration.
     pub bell: BellConfig,
 
+    /// Scrollback export configuration.
+    pub export: ExportConfig,
+
+    /// Line timestamp configuration.
+    pub timestamps: TimestampsConfig,
+
+    /// Regex search configuration.
+    pub search: SearchConfig,
+
+    /// Clipboard history configuration.
+    pub clipboard: ClipboardConfig,
+
+    /// Paste safety configuration.
+    pub paste: PasteConfig,
+
     /// RGB values for colors.
     pub colors: Colors,
 
@@ -78,6 +99,12 @@ pub struct UiConfig {
     /// Keybindings.
     key_bindings: KeyBindings,
 
+    /// Bindings made of multiple keys.
+    pub key_sequence: KeySequenceConfig,
+
+    /// Names of the user-defined binding modes.
+    binding_modes: Vec<String>,
+
     /// Bindings for the mouse.
     mouse_bindings: MouseBindings,
 
@@ -99,10 +126,17 @@ impl Default for UiConfig {
             debug: Default::default(),
             config_paths: Default::default(),
             key_bindings: Default::default(),
+            key_sequence: Default::default(),
+            binding_modes: Default::default(),
             mouse_bindings: Default::default(),
             terminal_config: Default::default(),
             background_opacity: Default::default(),
             bell: Default::default(),
+            export: Default::default(),
+            timestamps: Default::default(),
+            search: Default::default(),
+            clipboard: Default::default(),
+            paste: Default::default(),
             colors: Default::default(),
             draw_bold_text_with_bright_colors: Default::default(),
             hints: Default::default(),
@@ -125,12 +159,22 @@ impl UiConfig {
                 mode: binding.mode.mode,
                 notmode: binding.mode.not_mode,
                 action: Action::Hint(hint.clone()),
+                sequence: Vec::new(),
+                leader: false,
+                user_mode: None,
             };
 
             self.key_bindings.0.push(binding);
         }
     }
 
+    /// Remove all bindings using binding modes which haven't been declared.
+    pub fn validate_binding_modes(&mut self) {
+        let modes = &self.binding_modes;
+        self.key_bindings.0.retain(|binding| uses_declared_modes(binding, modes));
+        self.mouse_bindings.0.retain(|binding| uses_declared_modes(binding, modes));
+    }
+
     #[inline]
     pub fn window_opacity(&self) -> f32 {
         self.background_opacity.unwrap_or(self.window.opacity).as_f32()
@@ -147,6 +191,26 @@ impl UiConfig {
     }
 }
 
+/// Check that all user-defined modes used by a binding have been declared.
+fn uses_declared_modes<T>(binding: &Binding<T>, modes: &[String]) -> bool {
+    let entered_modes = binding.action.actions().iter().filter_map(|action| match action {
+        Action::EnterMode(mode) => Some(mode),
+        _ => None,
+    });
+
+    for mode in binding.user_mode.iter().chain(entered_modes) {
+        if !modes.contains(mode) {
+            error!(
+                target: LOG_TARGET_CONFIG,
+                "Config error: binding mode `{}` is not declared in `binding_modes`", mode
+            );
+            return false;
+        }
+    }
+
+    true
+}
+
 #[derive(SerdeReplace, Clone, Debug, PartialEq, Eq)]
 struct KeyBindings(Vec<KeyBinding>);
 
@@ -230,6 +294,9 @@ pub struct Hints {
     /// Characters for the hint labels.
     alphabet: HintsAlphabet,
 
+    /// Number of lines above the viewport searched by hints with the scrollback scope.
+    pub scrollback_lines: usize,
+
     /// All configured terminal hints.
     pub enabled: Vec<Hint>,
 }
@@ -237,8 +304,7 @@ pub struct Hints {
 impl Default for Hints {
     fn default() -> Self {
         // Add URL hint by default when no other hint is present.
-        let pattern = LazyRegexVariant::Pattern(String::from(URL_REGEX));
-        let regex = LazyRegex(Rc::new(RefCell::new(pattern)));
+        let regex = LazyRegex::new(URL_REGEX);
         let content = HintContent::new(Some(regex), true);
 
         #[cfg(not(any(target_os = "macos", windows)))]
@@ -256,6 +322,7 @@ impl Default for Hints {
                 content,
                 action,
                 post_processing: true,
+                scope: Default::default(),
                 mouse: Some(HintMouse { enabled: true, mods: Default::default() }),
                 binding: Some(HintBinding {
                     key: Key::Keycode(VirtualKeyCode::U),
@@ -264,6 +331,7 @@ impl Default for Hints {
                 }),
             }],
             alphabet: Default::default(),
+            scrollback_lines: 1000,
         }
     }
 }
@@ -307,6 +375,22 @@ impl<'de> Deserialize<'de> for HintsAlphabet {
     }
 }
 
+/// Lines searched for hint matches.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HintScope {
+    /// Only the visible lines.
+    Viewport,
+
+    /// The visible lines and `hints.scrollback_lines` lines above them.
+    Scrollback,
+}
+
+impl Default for HintScope {
+    fn default() -> Self {
+        Self::Viewport
+    }
+}
+
 /// Built-in actions for hint mode.
 #[derive(ConfigDeserialize, Clone, Debug, PartialEq, Eq)]
 pub enum HintInternalAction {
@@ -347,6 +431,10 @@ pub struct Hint {
     #[serde(default)]
     pub post_processing: bool,
 
+    /// Lines searched for matches.
+    #[serde(default)]
+    pub scope: HintScope,
+
     /// Hint mouse highlighting.
     pub mouse: Option<HintMouse>,
 
@@ -453,6 +541,11 @@ pub struct HintMouse {
 pub struct LazyRegex(Rc<RefCell<LazyRegexVariant>>);
 
 impl LazyRegex {
+    /// Create a regex which is compiled on first use.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self(Rc::new(RefCell::new(LazyRegexVariant::Pattern(pattern.into()))))
+    }
+
     /// Execute a function with the compiled regex DFAs as parameter.
     pub fn with_compiled<T, F>(&self, mut f: F) -> T
     where
@@ -467,8 +560,7 @@ impl<'de> Deserialize<'de> for LazyRegex {
     where
         D: Deserializer<'de>,
     {
-        let regex = LazyRegexVariant::Pattern(String::deserialize(deserializer)?);
-        Ok(Self(Rc::new(RefCell::new(regex))))
+        Ok(Self::new(String::deserialize(deserializer)?))
     }
 }
 
@@ -495,7 +587,7 @@ impl LazyRegexVariant {
         let regex_search = match RegexSearch::new(regex) {
             Ok(regex_search) => regex_search,
             Err(error) => {
-                error!("hint regex is invalid: {}", error);
+                error!("regex is invalid: {}", error);
                 RegexSearch::new("").unwrap()
             },
         };
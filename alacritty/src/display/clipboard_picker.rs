@@ -0,0 +1,132 @@
+//! Overlay for picking an entry from the clipboard history.
+
+use std::cmp::min;
+use std::mem;
+
+use crate::clipboard::ClipboardEntry;
+use crate::string::{ShortenDirection, StrShortener};
+
+/// Character used to shorten entries which don't fit into a single line.
+const SHORTENER: char = '…';
+
+/// Clipboard history picker state.
+#[derive(Default, Debug)]
+pub struct ClipboardPicker {
+    /// Entries which can be picked, empty while the picker is inactive.
+    entries: Vec<ClipboardEntry>,
+
+    /// Index of the selected entry.
+    selected: usize,
+}
+
+impl ClipboardPicker {
+    /// Open the picker with the available entries.
+    pub fn start(&mut self, entries: Vec<ClipboardEntry>) {
+        self.entries = entries;
+        self.selected = 0;
+    }
+
+    /// Check if the picker is open.
+    pub fn active(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Close the picker, returning the text of the selected entry.
+    pub fn stop(&mut self) -> Option<String> {
+        let mut entries = mem::take(&mut self.entries);
+        (self.selected < entries.len()).then(|| entries.swap_remove(self.selected).text)
+    }
+
+    /// Move the selection by `delta` entries, wrapping around at the ends.
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = self.entries.len() as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Select an entry of the clipboard history, skipping named registers.
+    ///
+    /// Returns `false` if there is no history entry with this index.
+    pub fn select_history(&mut self, index: usize) -> bool {
+        let registers = self.entries.iter().filter(|entry| entry.register.is_some()).count();
+        if registers + index >= self.entries.len() {
+            return false;
+        }
+
+        self.selected = registers + index;
+        true
+    }
+
+    /// Text for each visible line of the picker, and whether it shows the selected entry.
+    ///
+    /// At most `max_lines` entries are shown, scrolled to keep the selection visible.
+    pub fn lines(&self, columns: usize, max_lines: usize) -> Vec<(String, bool)> {
+        let height = min(self.entries.len(), max_lines);
+        let offset = (self.selected + 1).saturating_sub(height);
+
+        let mut history_index = 0;
+        let mut lines = Vec::with_capacity(height);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let label = match entry.register {
+                Some(name) => format!("\"{}", name),
+                None => {
+                    history_index += 1;
+                    history_index.to_string()
+                },
+            };
+
+            if i < offset || i >= offset + height {
+                continue;
+            }
+
+            // Only show the first line of each entry.
+            let mut text_lines = entry.text.lines();
+            let first_line = text_lines.next().unwrap_or_default().replace('\t', " ");
+            let more =
+                if text_lines.next().is_some() { SHORTENER.to_string() } else { String::new() };
+
+            let text = format!("{:>3} {}{}", label, first_line, more);
+            let shortened: String =
+                StrShortener::new(&text, columns, ShortenDirection::Right, Some(SHORTENER))
+                    .collect();
+            lines.push((format!("{:<1$}", shortened, columns), i == self.selected));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(register: Option<char>, text: &str) -> ClipboardEntry {
+        ClipboardEntry { register, text: text.into() }
+    }
+
+    #[test]
+    fn picker_lines() {
+        let mut picker = ClipboardPicker::default();
+        picker.start(vec![entry(Some('a'), "foo"), entry(None, "bar\nbaz"), entry(None, "x")]);
+
+        assert_eq!(
+            picker.lines(10, 5),
+            [
+                (String::from(" \"a foo   "), true),
+                (String::from("  1 bar…  "), false),
+                (String::from("  2 x     "), false),
+            ]
+        );
+
+        // Scroll to keep the selection visible.
+        picker.move_selection(-1);
+        let lines = picker.lines(10, 2);
+        assert_eq!(lines[1], (String::from("  2 x     "), true));
+
+        assert!(picker.select_history(0));
+        assert!(!picker.select_history(2));
+        assert_eq!(picker.stop(), Some(String::from("bar\nbaz")));
+        assert!(!picker.active());
+    }
+}
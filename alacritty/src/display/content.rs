@@ -201,8 +201,9 @@ pub struct RenderableCellExtra {
 impl RenderableCell {
     fn new<'a>(content: &mut RenderableContent<'a>, cell: Indexed<&Cell>) -> Self {
         // Lookup RGB values.
-        let mut fg = Self::compute_fg_rgb(content, cell.fg, cell.flags);
-        let mut bg = Self::compute_bg_rgb(content, cell.bg);
+        let color = |index| content.color(index);
+        let mut fg = Self::compute_fg_rgb(content.config, color, cell.fg, cell.flags);
+        let mut bg = Self::compute_bg_rgb(color, cell.bg);
 
         let mut bg_alpha = if cell.flags.contains(Flags::INVERSE) {
             mem::swap(&mut fg, &mut bg);
@@ -261,9 +262,9 @@ impl RenderableCell {
         let point = term::point_to_viewport(display_offset, cell_point).unwrap();
 
         let flags = cell.flags;
-        let underline = cell
-            .underline_color()
-            .map_or(fg, |underline| Self::compute_fg_rgb(content, underline, flags));
+        let underline = cell.underline_color().map_or(fg, |underline| {
+            Self::compute_fg_rgb(content.config, |i| content.color(i), underline, flags)
+        });
 
         let zerowidth = cell.zerowidth();
         let hyperlink = cell.hyperlink();
@@ -303,8 +304,10 @@ impl RenderableCell {
     }
 
     /// Get the RGB color from a cell's foreground color.
-    fn compute_fg_rgb(content: &mut RenderableContent<'_>, fg: Color, flags: Flags) -> Rgb {
-        let config = &content.config;
+    pub fn compute_fg_rgb<C>(config: &UiConfig, color: C, fg: Color, flags: Flags) -> Rgb
+    where
+        C: Fn(usize) -> Rgb,
+    {
         match fg {
             Color::Spec(rgb) => match flags & Flags::DIM {
                 Flags::DIM => rgb * DIM_FACTOR,
@@ -317,16 +320,14 @@ impl RenderableCell {
                         if ansi == NamedColor::Foreground
                             && config.colors.primary.bright_foreground.is_none() =>
                     {
-                        content.color(NamedColor::DimForeground as usize)
+                        color(NamedColor::DimForeground as usize)
                     },
                     // Draw bold text in bright colors *and* contains bold flag.
-                    (true, Flags::BOLD) => content.color(ansi.to_bright() as usize),
+                    (true, Flags::BOLD) => color(ansi.to_bright() as usize),
                     // Cell is marked as dim and not bold.
-                    (_, Flags::DIM) | (false, Flags::DIM_BOLD) => {
-                        content.color(ansi.to_dim() as usize)
-                    },
+                    (_, Flags::DIM) | (false, Flags::DIM_BOLD) => color(ansi.to_dim() as usize),
                     // None of the above, keep original color..
-                    _ => content.color(ansi as usize),
+                    _ => color(ansi as usize),
                 }
             },
             Color::Indexed(idx) => {
@@ -341,18 +342,18 @@ impl RenderableCell {
                     _ => idx as usize,
                 };
 
-                content.color(idx)
+                color(idx)
             },
         }
     }
 
     /// Get the RGB color from a cell's background color.
     #[inline]
-    fn compute_bg_rgb(content: &mut RenderableContent<'_>, bg: Color) -> Rgb {
+    pub fn compute_bg_rgb<C: Fn(usize) -> Rgb>(color: C, bg: Color) -> Rgb {
         match bg {
             Color::Spec(rgb) => rgb,
-            Color::Named(ansi) => content.color(ansi as usize),
-            Color::Indexed(idx) => content.color(idx as usize),
+            Color::Named(ansi) => color(ansi as usize),
+            Color::Indexed(idx) => color(idx as usize),
         }
     }
 
@@ -0,0 +1,374 @@
+//! Overlay showing only the terminal lines matching a regex.
+
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+
+use alacritty_terminal::ansi::{Handler, Mode};
+use alacritty_terminal::config::Config as TerminalConfig;
+use alacritty_terminal::event::VoidListener;
+use alacritty_terminal::grid::{Dimensions, Row};
+use alacritty_terminal::index::{Column, Direction, Line, Point, Side};
+use alacritty_terminal::selection::{Selection, SelectionType};
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::term::search::{RegexIter, RegexSearch};
+use alacritty_terminal::term::{ScrollMarker, Term};
+
+use crate::display::SizeInfo;
+
+/// Text shown in place of the lines skipped between two results.
+const SEPARATOR: &str = "--";
+
+/// Terminal line shown in the filter view.
+struct FilterLine {
+    /// Content of the line.
+    row: Row<Cell>,
+
+    /// Position the vi mode cursor jumps to, `None` for separators.
+    target: Option<Point>,
+
+    /// Whether the line contains a match, rather than just surrounding it.
+    matched: bool,
+}
+
+impl FilterLine {
+    /// Line marking skipped terminal lines.
+    fn separator(columns: usize) -> Self {
+        let mut row = Row::<Cell>::new(columns);
+        for (i, c) in SEPARATOR.chars().take(columns).enumerate() {
+            row[Column(i)].c = c;
+            row[Column(i)].flags.insert(Flags::DIM);
+        }
+
+        Self { row, target: None, matched: false }
+    }
+}
+
+/// Filter view state.
+pub struct FilterView {
+    /// Regex text shown in the footer.
+    text: String,
+
+    /// Regex the lines are filtered with.
+    regex: RegexSearch,
+
+    /// Number of lines shown before and after every matching line.
+    context: usize,
+
+    /// All lines shown in the filter view.
+    lines: Vec<FilterLine>,
+
+    /// Index of the selected line.
+    selected: usize,
+
+    /// Index of the first visible line.
+    offset: usize,
+
+    /// Position of the terminal's content when the lines were filtered.
+    marker: ScrollMarker,
+
+    /// Temporary terminal holding the visible lines, which is rendered in place of the terminal.
+    term: Term<VoidListener>,
+}
+
+impl FilterView {
+    /// Filter the lines of `term`, selecting the most recent match.
+    pub fn new<T>(
+        term: &Term<T>,
+        text: String,
+        regex: RegexSearch,
+        config: &TerminalConfig,
+        size: SizeInfo,
+    ) -> Self {
+        let mut filter = Self {
+            text,
+            regex,
+            context: 0,
+            lines: Vec::new(),
+            selected: 0,
+            offset: 0,
+            marker: term.scroll_marker(),
+            term: grid_term(config, grid_size(size)),
+        };
+        filter.rebuild(term);
+        filter
+    }
+
+    /// Terminal holding the visible lines.
+    pub fn term(&self) -> &Term<VoidListener> {
+        &self.term
+    }
+
+    /// Number of lines visible at once.
+    pub fn page_lines(&self) -> usize {
+        self.term.screen_lines()
+    }
+
+    /// Position the vi mode cursor should jump to for the selected line.
+    ///
+    /// The position is moved with the content of `term` scrolled since the lines were filtered.
+    /// Returns `None` if the lines are stale, since the active grid has been swapped.
+    pub fn selected_point<T>(&self, term: &Term<T>) -> Option<Point> {
+        let mut point = self.lines.get(self.selected).and_then(|line| line.target)?;
+        let delta = term.scrolled_since(self.marker)?;
+        point.line = Line(point.line.0.checked_sub(delta)?);
+        Some(point)
+    }
+
+    /// Filter the lines again if the active grid has been swapped since they were filtered.
+    ///
+    /// Returns `true` if the lines have changed.
+    pub fn update<T>(&mut self, term: &Term<T>) -> bool {
+        if term.scrolled_since(self.marker).is_some() {
+            return false;
+        }
+
+        self.rebuild(term);
+        true
+    }
+
+    /// Move the selection by `delta` lines, skipping separators.
+    pub fn move_selection(&mut self, delta: isize) {
+        if self.lines.is_empty() {
+            return;
+        }
+
+        let last = self.lines.len() as isize - 1;
+        let mut selected = (self.selected as isize).saturating_add(delta).clamp(0, last) as usize;
+
+        // Separators are always surrounded by lines, so stepping over them stays in bounds.
+        while self.lines[selected].target.is_none() {
+            selected = if delta < 0 { selected - 1 } else { selected + 1 };
+        }
+
+        self.selected = selected;
+        self.update_grid();
+    }
+
+    /// Change the number of lines shown around every match.
+    pub fn change_context<T>(&mut self, term: &Term<T>, delta: isize) {
+        self.context = max(self.context as isize + delta, 0) as usize;
+        self.rebuild(term);
+    }
+
+    /// Adjust the visible lines to a new window size.
+    pub fn resize<T>(&mut self, term: &Term<T>, config: &TerminalConfig, size: SizeInfo) {
+        // Terminal lines only move when its dimensions change.
+        let size = grid_size(size);
+        if size.screen_lines() == self.term.screen_lines() && size.columns() == self.term.columns()
+        {
+            return;
+        }
+
+        self.term = grid_term(config, size);
+        self.rebuild(term);
+    }
+
+    /// Regex text and the status shown at the end of the footer.
+    pub fn footer(&self) -> (&str, String) {
+        let total = self.lines.iter().filter(|line| line.matched).count();
+        let position =
+            self.lines.iter().take(self.selected + 1).filter(|line| line.matched).count();
+
+        let status = if self.context > 0 {
+            format!("-C{} {}/{}", self.context, position, total)
+        } else {
+            format!("{}/{}", position, total)
+        };
+
+        (&self.text, status)
+    }
+
+    /// Filter the terminal's lines again, keeping the selected line if it's still present.
+    fn rebuild<T>(&mut self, term: &Term<T>) {
+        let selected_line = self.selected_point(term).map(|point| point.line);
+        self.lines = filter_lines(term, &self.regex, self.context);
+        self.marker = term.scroll_marker();
+
+        let selected = selected_line.and_then(|selected_line| {
+            self.lines
+                .iter()
+                .position(|line| line.target.map(|point| point.line) == Some(selected_line))
+        });
+        self.selected = selected
+            .or_else(|| self.lines.iter().rposition(|line| line.matched))
+            .unwrap_or_default();
+
+        self.update_grid();
+    }
+
+    /// Copy the visible lines into the temporary terminal.
+    fn update_grid(&mut self) {
+        let screen_lines = self.term.screen_lines();
+        let columns = self.term.columns();
+
+        // Scroll to keep the selection visible.
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + screen_lines {
+            self.offset = self.selected + 1 - screen_lines;
+        }
+        self.offset = min(self.offset, self.lines.len().saturating_sub(screen_lines));
+
+        let grid = self.term.grid_mut();
+        for i in 0..screen_lines {
+            grid[Line(i as i32)] = match self.lines.get(self.offset + i) {
+                Some(line) if line.row.len() == columns => line.row.clone(),
+                _ => Row::new(columns),
+            };
+        }
+
+        // Highlight the selected line.
+        self.term.selection = (!self.lines.is_empty()).then(|| {
+            let point = Point::new(Line((self.selected - self.offset) as i32), Column(0));
+            Selection::new(SelectionType::Lines, point, Side::Left)
+        });
+    }
+}
+
+/// Size of the visible lines, leaving room for the footer.
+fn grid_size(mut size: SizeInfo) -> SizeInfo {
+    size.reserve_lines(1);
+    size
+}
+
+/// Create the terminal holding the visible lines.
+fn grid_term(config: &TerminalConfig, size: SizeInfo) -> Term<VoidListener> {
+    let mut term = Term::new(config, &size, VoidListener);
+    term.unset_mode(Mode::ShowCursor);
+    term
+}
+
+/// Collect all lines containing a match and `context` lines around them.
+///
+/// Matches wrapping over multiple lines always include the entire wrapped line.
+fn filter_lines<T>(term: &Term<T>, regex: &RegexSearch, context: usize) -> Vec<FilterLine> {
+    let start = Point::new(term.topmost_line(), Column(0));
+    let end = Point::new(term.bottommost_line(), term.last_column());
+
+    // Map every included line to its jump target and whether it matched.
+    let mut included = BTreeMap::new();
+    for regex_match in RegexIter::new(start, end, Direction::Right, term, regex) {
+        let first = term.line_search_left(*regex_match.start()).line;
+        let last = term.line_search_right(*regex_match.end()).line;
+        for line in first.0..=last.0 {
+            included.entry(Line(line)).or_insert((*regex_match.start(), true));
+        }
+    }
+
+    let context = context as i32;
+    let matched_lines: Vec<Line> = included.keys().copied().collect();
+    for line in matched_lines {
+        let first = max(line - context, term.topmost_line());
+        let last = min(line + context, term.bottommost_line());
+        for line in first.0..=last.0 {
+            let line = Line(line);
+            included.entry(line).or_insert((Point::new(line, Column(0)), false));
+        }
+    }
+
+    let columns = term.columns();
+    let mut lines: Vec<FilterLine> = Vec::with_capacity(included.len());
+    let mut previous: Option<Line> = None;
+    for (line, (target, matched)) in included {
+        if previous.map_or(false, |previous| previous + 1 != line) {
+            unwrap_last(&mut lines);
+            lines.push(FilterLine::separator(columns));
+        }
+
+        lines.push(FilterLine { row: term.grid()[line].clone(), target: Some(target), matched });
+        previous = Some(line);
+    }
+    unwrap_last(&mut lines);
+
+    lines
+}
+
+/// Prevent the last line from continuing on the lines added after it.
+fn unwrap_last(lines: &mut [FilterLine]) {
+    if let Some(cell) = lines.last_mut().and_then(|line| line.row.last_mut()) {
+        cell.flags.remove(Flags::WRAPLINE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alacritty_terminal::term::test::mock_term;
+
+    fn text(line: &FilterLine) -> String {
+        line.row[Column(0)..Column(line.row.len())].iter().map(|cell| cell.c).collect()
+    }
+
+    #[test]
+    fn filter_context() {
+        let term = mock_term("ok a\r\nfail\r\nok b\r\nok c\r\nok d\r\nfail");
+        let regex = RegexSearch::new("fail").unwrap();
+
+        let lines = filter_lines(&term, &regex, 0);
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(texts, ["fail", "--  ", "fail"]);
+        assert_eq!(lines[0].target, Some(Point::new(Line(1), Column(0))));
+        assert_eq!(lines[1].target, None);
+
+        let lines = filter_lines(&term, &regex, 1);
+        let texts: Vec<String> = lines.iter().map(text).collect();
+        assert_eq!(texts, ["ok a", "fail", "ok b", "--  ", "ok d", "fail"]);
+        assert!(!lines[0].matched);
+        assert!(lines[1].matched);
+
+        let size = SizeInfo::new(40., 100., 10., 10., 0., 0., false);
+        let mut filter = FilterView::new(&term, "fail".into(), regex, &Default::default(), size);
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(5), Column(0))));
+        assert_eq!(filter.footer().1, "2/2");
+
+        filter.move_selection(-1);
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(1), Column(0))));
+        assert_eq!(filter.footer().1, "1/2");
+
+        filter.change_context(&term, 1);
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(1), Column(0))));
+        assert_eq!(filter.footer().1, "-C1 1/2");
+    }
+
+    #[test]
+    fn filter_targets_follow_output() {
+        let mut term = mock_term("fail\r\nok\r\nok");
+        let regex = RegexSearch::new("fail").unwrap();
+
+        let size = SizeInfo::new(40., 100., 10., 10., 0., 0., false);
+        let filter = FilterView::new(&term, "fail".into(), regex, &Default::default(), size);
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(0), Column(0))));
+
+        // New output scrolls the matching line into the history.
+        term.goto(Line(2), Column(0));
+        term.linefeed();
+        term.linefeed();
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(-2), Column(0))));
+    }
+
+    #[test]
+    fn filter_rebuilds_on_alt_screen() {
+        let mut term = mock_term("fail\r\nok\r\nok");
+        let regex = RegexSearch::new("fail").unwrap();
+
+        let size = SizeInfo::new(40., 100., 10., 10., 0., 0., false);
+        let mut filter = FilterView::new(&term, "fail".into(), regex, &Default::default(), size);
+        assert!(!filter.update(&term));
+
+        // Output on the alternate screen leaves the filtered lines stale.
+        term.swap_alt();
+        term.goto(Line(2), Column(0));
+        term.linefeed();
+        assert_eq!(filter.selected_point(&term), None);
+
+        assert!(filter.update(&term));
+        assert_eq!(filter.footer().1, "0/0");
+        assert_eq!(filter.selected_point(&term), None);
+
+        // Leaving the alternate screen shows the primary screen's matches again.
+        term.swap_alt();
+        assert!(filter.update(&term));
+        assert_eq!(filter.selected_point(&term), Some(Point::new(Line(0), Column(0))));
+    }
+}
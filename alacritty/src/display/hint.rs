@@ -1,4 +1,4 @@
-use std::cmp::Reverse;
+use std::cmp::{max, min, Reverse};
 use std::collections::HashSet;
 use std::iter;
 
@@ -10,7 +10,7 @@ use alacritty_terminal::term::cell::Hyperlink;
 use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
 use alacritty_terminal::term::{Term, TermMode};
 
-use crate::config::ui_config::{Hint, HintAction};
+use crate::config::ui_config::{Hint, HintAction, HintScope, LazyRegex};
 use crate::config::UiConfig;
 
 /// Maximum number of linewraps followed outside of the viewport during search highlighting.
@@ -35,6 +35,12 @@ pub struct HintState {
 
     /// Keys pressed for hint selection.
     keys: Vec<char>,
+
+    /// Number of lines above the viewport searched for regex matches.
+    history_lines: usize,
+
+    /// Number of entries the list of labels above the viewport is scrolled up by.
+    list_offset: usize,
 }
 
 impl HintState {
@@ -46,6 +52,8 @@ impl HintState {
             matches: Default::default(),
             labels: Default::default(),
             keys: Default::default(),
+            history_lines: Default::default(),
+            list_offset: Default::default(),
         }
     }
 
@@ -55,7 +63,14 @@ impl HintState {
     }
 
     /// Start the hint selection process.
-    pub fn start(&mut self, hint: Hint) {
+    ///
+    /// Hints with the scrollback scope also search `scrollback_lines` lines above the viewport.
+    pub fn start(&mut self, hint: Hint, scrollback_lines: usize) {
+        self.history_lines = match hint.scope {
+            HintScope::Viewport => 0,
+            HintScope::Scrollback => scrollback_lines,
+        };
+        self.list_offset = 0;
         self.hint = Some(hint);
     }
 
@@ -84,8 +99,9 @@ impl HintState {
 
         // Add visible regex matches.
         if let Some(regex) = hint.content.regex.as_ref() {
+            let history_lines = self.history_lines;
             regex.with_compiled(|regex| {
-                let matches = visible_regex_match_iter(term, regex);
+                let matches = regex_match_iter(term, regex, history_lines);
 
                 // Apply post-processing and search for sub-matches if necessary.
                 if hint.post_processing {
@@ -149,12 +165,13 @@ impl HintState {
         if label.len() == 1 {
             let bounds = self.matches[index].clone();
             let action = hint.action.clone();
+            let regex = hint.content.regex.clone();
 
             self.stop();
 
             // Hyperlinks take precedence over regex matches.
             let hyperlink = term.grid()[*bounds.start()].hyperlink();
-            Some(HintMatch { action, bounds, hyperlink })
+            Some(HintMatch { action, bounds, hyperlink, regex })
         } else {
             // Store character to preserve the selection.
             self.keys.push(c);
@@ -173,6 +190,58 @@ impl HintState {
         &self.matches
     }
 
+    /// Scroll the list of labels above the viewport up by `delta` entries.
+    pub fn scroll_list(&mut self, delta: isize) {
+        self.list_offset = max(self.list_offset as isize + delta, 0) as usize;
+    }
+
+    /// Labels and text of the matches which can't be labeled in place.
+    ///
+    /// This includes the matches above the viewport and the matches in the bottom lines of the
+    /// viewport, which are covered by the list itself. At most `max_lines` entries are
+    /// returned, ordered from top to bottom.
+    pub fn offscreen_labels<T>(
+        &mut self,
+        term: &Term<T>,
+        max_lines: usize,
+    ) -> Vec<(String, String)> {
+        let display_offset = term.grid().display_offset() as i32;
+        let viewport_start = Line(-display_offset);
+
+        // Grow the list until it no longer covers any labels it doesn't contain.
+        let mut height = 0;
+        let offscreen = loop {
+            let covered_start = Line(term.screen_lines() as i32 - height as i32 - display_offset);
+            let offscreen: Vec<_> = self
+                .matches
+                .iter()
+                .zip(&self.labels)
+                .filter(|(bounds, label)| {
+                    let line = bounds.start().line;
+                    (line < viewport_start || line >= covered_start) && !label.is_empty()
+                })
+                .collect();
+
+            let new_height = min(offscreen.len(), max_lines);
+            if new_height == height {
+                break offscreen;
+            }
+            height = new_height;
+        };
+
+        // Keep the list scrolled within its entries.
+        self.list_offset = min(self.list_offset, offscreen.len() - height);
+
+        let end = offscreen.len() - self.list_offset;
+        offscreen[end - height..end]
+            .iter()
+            .map(|(bounds, label)| {
+                let text = term.bounds_to_string(*bounds.start(), *bounds.end());
+                (label.iter().collect(), text.replace('\n', " "))
+            })
+            .collect()
+    }
+
     /// Update the alphabet used for hint labels.
     pub fn update_alphabet(&mut self, alphabet: &str) {
         if self.alphabet != alphabet {
@@ -192,6 +261,9 @@ pub struct HintMatch {
     bounds: Match,
 
     hyperlink: Option<Hyperlink>,
+
+    /// Regex which matched the hint's text.
+    regex: Option<LazyRegex>,
 }
 
 impl HintMatch {
@@ -210,8 +282,20 @@ impl HintMatch {
         &self.bounds
     }
 
-    pub fn hyperlink(&self) -> Option<&Hyperlink> {
-        self.hyperlink.as_ref()
+    /// Text passed to the hint's action, which is the URI for hyperlinks.
+    pub fn text<T>(&self, term: &Term<T>) -> String {
+        match &self.hyperlink {
+            Some(hyperlink) => hyperlink.uri().to_owned(),
+            None => term.bounds_to_string(*self.bounds.start(), *self.bounds.end()),
+        }
+    }
+
+    /// Names of the capture groups with the text they matched in the hint's text.
+    pub fn captures(&self, text: &str) -> Vec<(String, Option<String>)> {
+        match (&self.regex, &self.hyperlink) {
+            (Some(regex), None) => regex.with_compiled(|regex| regex.captures(text)),
+            _ => Vec::new(),
+        }
     }
 }
 
@@ -284,16 +368,27 @@ impl HintLabels {
 pub fn visible_regex_match_iter<'a, T>(
     term: &'a Term<T>,
     regex: &'a RegexSearch,
+) -> impl Iterator<Item = Match> + 'a {
+    regex_match_iter(term, regex, 0)
+}
+
+/// Iterate over all regex matches in the viewport and the `history_lines` lines above it.
+pub fn regex_match_iter<'a, T>(
+    term: &'a Term<T>,
+    regex: &'a RegexSearch,
+    history_lines: usize,
 ) -> impl Iterator<Item = Match> + 'a {
     let viewport_start = Line(-(term.grid().display_offset() as i32));
     let viewport_end = viewport_start + term.bottommost_line();
-    let mut start = term.line_search_left(Point::new(viewport_start, Column(0)));
+    let history_lines = min(history_lines, term.total_lines()) as i32;
+    let search_start = max(viewport_start - history_lines, term.topmost_line());
+    let mut start = term.line_search_left(Point::new(search_start, Column(0)));
     let mut end = term.line_search_right(Point::new(viewport_end, Column(0)));
-    start.line = start.line.max(viewport_start - MAX_SEARCH_LINES);
+    start.line = start.line.max(search_start - MAX_SEARCH_LINES);
     end.line = end.line.min(viewport_end + MAX_SEARCH_LINES);
 
     RegexIter::new(start, end, Direction::Right, term, regex)
-        .skip_while(move |rm| rm.end().line < viewport_start)
+        .skip_while(move |rm| rm.end().line < search_start)
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
@@ -335,7 +430,7 @@ pub fn visible_unique_hyperlinks_iter<T>(term: &Term<T>) -> impl Iterator<Item =
 }
 
 /// Retrieve the match, if the specified point is inside the content matching the regex.
-fn regex_match_at<T>(
+pub fn regex_match_at<T>(
     term: &Term<T>,
     point: Point,
     regex: &RegexSearch,
@@ -378,19 +473,73 @@ pub fn highlighted_at<T>(
                 bounds,
                 action: hint.action.clone(),
                 hyperlink: Some(hyperlink),
+                regex: None,
             });
         }
 
         if let Some(bounds) = hint.content.regex.as_ref().and_then(|regex| {
             regex.with_compiled(|regex| regex_match_at(term, point, regex, hint.post_processing))
         }) {
-            return Some(HintMatch { bounds, action: hint.action.clone(), hyperlink: None });
+            return Some(HintMatch {
+                bounds,
+                action: hint.action.clone(),
+                hyperlink: None,
+                regex: hint.content.regex.clone(),
+            });
         }
 
         None
     })
 }
 
+/// Arguments for launching a hint's command.
+///
+/// Placeholders are the names of `values` in braces, like `{match}`, which are replaced with their
+/// value. Arguments with placeholders that have no value, like capture groups which didn't match,
+/// are dropped. All other text is kept as is, including braces. Without any placeholders, the
+/// hint's text is appended to the arguments.
+pub fn command_args(
+    args: &[String],
+    text: &str,
+    values: &[(String, Option<String>)],
+) -> Vec<String> {
+    let mut has_placeholders = false;
+    let mut command_args: Vec<String> = args
+        .iter()
+        .filter_map(|arg| {
+            let mut command_arg = String::with_capacity(arg.len());
+            let mut rest = arg.as_str();
+            while let Some(start) = rest.find('{') {
+                command_arg.push_str(&rest[..start]);
+                rest = &rest[start..];
+
+                let placeholder = rest
+                    .find('}')
+                    .and_then(|end| values.iter().find(|(name, _)| *name == rest[1..end]));
+                match placeholder {
+                    Some((name, value)) => {
+                        has_placeholders = true;
+                        command_arg.push_str(value.as_ref()?);
+                        rest = &rest[name.len() + 2..];
+                    },
+                    None => {
+                        command_arg.push('{');
+                        rest = &rest[1..];
+                    },
+                }
+            }
+            command_arg.push_str(rest);
+            Some(command_arg)
+        })
+        .collect();
+
+    if !has_placeholders {
+        command_args.push(text.into());
+    }
+
+    command_args
+}
+
 /// Retrieve the hyperlink with its range, if there is one at the specified point.
 fn hyperlink_at<T>(term: &Term<T>, point: Point) -> Option<(Hyperlink, Match)> {
     let hyperlink = term.grid()[point].hyperlink()?;
@@ -588,6 +737,52 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn command_placeholders() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let values = [
+            ("match".into(), Some("src/main.rs:12".into())),
+            ("file".into(), Some("src/main.rs".into())),
+            ("line".into(), Some("12".into())),
+            ("column".into(), None),
+        ];
+
+        let substituted =
+            command_args(&args(&["+{line}", "{file}", "{print $1}"]), "text", &values);
+        assert_eq!(substituted, ["+12", "src/main.rs", "{print $1}"]);
+
+        // Arguments with unresolved placeholders are dropped.
+        let dropped = command_args(&args(&["{file}", "+{column}"]), "text", &values);
+        assert_eq!(dropped, ["src/main.rs"]);
+
+        // Braces around other words are literal text.
+        let literal = command_args(&args(&["awk", "{print}", "{file}"]), "text", &values);
+        assert_eq!(literal, ["awk", "{print}", "src/main.rs"]);
+
+        // The text is only appended without placeholders.
+        let appended = command_args(&args(&["awk", "{print}", "{}"]), "text", &values);
+        assert_eq!(appended, ["awk", "{print}", "{}", "text"]);
+    }
+
+    #[test]
+    fn list_labels_covered_by_list() {
+        let mut term = mock_term("a\r\nb\r\nc\r\nd");
+        term.goto(Line(3), Column(0));
+        term.linefeed();
+
+        // Matches in the history, the first line and the last line of the viewport.
+        let mut hint_state = HintState::new("abc");
+        for line in [-1, 0, 3] {
+            let point = Point::new(Line(line), Column(0));
+            hint_state.matches.push(Match::new(point, point));
+        }
+        hint_state.labels = vec![vec!['x'], vec!['y'], vec!['z']];
+
+        // The last line is covered by the list, so its label has to be listed too.
+        let labels = hint_state.offscreen_labels(&term, 2);
+        assert_eq!(labels, [("x".into(), "a".into()), ("z".into(), String::new())]);
+    }
+
     #[test]
     fn hint_label_generation() {
         let mut generator = HintLabels::new("0123", 0.5);
@@ -692,4 +887,23 @@ mod tests {
         // The interator should match everything in the viewport.
         assert_eq!(visible_regex_match_iter(&term, &regex).count(), 4096);
     }
+
+    #[test]
+    fn regex_match_includes_history_lines() {
+        let mut term = mock_term("    \r\n    ");
+        let regex = RegexSearch::new("a+").unwrap();
+
+        // Write one line per character, pushing the first lines into history.
+        for c in ['a', 'a', 'b', 'a'] {
+            term.input(c);
+            term.carriage_return();
+            term.linefeed();
+        }
+        assert_eq!(term.history_size(), 3);
+
+        assert_eq!(visible_regex_match_iter(&term, &regex).count(), 1);
+        assert_eq!(regex_match_iter(&term, &regex, 1).count(), 1);
+        assert_eq!(regex_match_iter(&term, &regex, 2).count(), 2);
+        assert_eq!(regex_match_iter(&term, &regex, 100).count(), 3);
+    }
 }
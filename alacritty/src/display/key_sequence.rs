@@ -0,0 +1,79 @@
+//! Key presses of partially entered key sequence bindings.
+
+use std::mem;
+
+use winit::event::{KeyboardInput, ModifiersState};
+
+/// Key press held back while waiting for the rest of a key sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingKey {
+    pub input: KeyboardInput,
+    pub mods: ModifiersState,
+
+    /// Characters received for the key press, which are sent if the sequence is abandoned.
+    pub chars: String,
+}
+
+/// Pending key sequence state.
+#[derive(Default, Debug)]
+pub struct KeySequence {
+    keys: Vec<PendingKey>,
+}
+
+impl KeySequence {
+    /// Check if a key sequence is being entered.
+    pub fn active(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Pending key presses, in the order they were pressed.
+    pub fn keys(&self) -> &[PendingKey] {
+        &self.keys
+    }
+
+    /// Add a key press to the sequence.
+    pub fn push(&mut self, input: KeyboardInput, mods: ModifiersState) {
+        self.keys.push(PendingKey { input, mods, chars: String::new() });
+    }
+
+    /// Store a character received for the last key press.
+    pub fn push_char(&mut self, c: char) {
+        if let Some(key) = self.keys.last_mut() {
+            key.chars.push(c);
+        }
+    }
+
+    /// Stop the sequence, returning its pending key presses.
+    pub fn take(&mut self) -> Vec<PendingKey> {
+        mem::take(&mut self.keys)
+    }
+
+    /// Text describing the pending key presses, like `Ctrl+A G`.
+    pub fn label(&self) -> String {
+        let keys: Vec<String> = self.keys.iter().map(key_label).collect();
+        keys.join(" ")
+    }
+}
+
+/// Text describing a key press with its modifiers.
+fn key_label(key: &PendingKey) -> String {
+    let mut label = String::new();
+    let modifiers = [
+        (ModifiersState::CTRL, "Ctrl+"),
+        (ModifiersState::ALT, "Alt+"),
+        (ModifiersState::SHIFT, "Shift+"),
+        (ModifiersState::LOGO, "Super+"),
+    ];
+    for (modifier, name) in modifiers {
+        if key.mods.contains(modifier) {
+            label.push_str(name);
+        }
+    }
+
+    match key.input.virtual_keycode {
+        Some(keycode) => label.push_str(&format!("{:?}", keycode)),
+        None => label.push_str(&key.input.scancode.to_string()),
+    }
+
+    label
+}
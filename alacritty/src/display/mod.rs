@@ -24,26 +24,29 @@ use crossfont::{self, Rasterize, Rasterizer};
 use unicode_width::UnicodeWidthChar;
 
 use alacritty_terminal::ansi::{CursorShape, NamedColor};
-use alacritty_terminal::config::MAX_SCROLLBACK_LINES;
 use alacritty_terminal::event::{EventListener, OnResize, WindowSize};
-use alacritty_terminal::grid::Dimensions as TermDimensions;
+use alacritty_terminal::grid::{self, Dimensions as TermDimensions, Timestamp};
 use alacritty_terminal::index::{Column, Direction, Line, Point};
 use alacritty_terminal::selection::{Selection, SelectionRange};
 use alacritty_terminal::term::cell::Flags;
 use alacritty_terminal::term::color::Rgb;
+use alacritty_terminal::term::search::SearchFlags;
 use alacritty_terminal::term::{self, Term, TermDamage, TermMode, MIN_COLUMNS, MIN_SCREEN_LINES};
 
 use crate::config::font::Font;
+use crate::config::timestamps::TimestampFormat;
 use crate::config::window::Dimensions;
 #[cfg(not(windows))]
 use crate::config::window::StartupMode;
 use crate::config::UiConfig;
 use crate::display::bell::VisualBell;
+use crate::display::clipboard_picker::ClipboardPicker;
 use crate::display::color::List;
 use crate::display::content::{RenderableContent, RenderableCursor};
 use crate::display::cursor::IntoRects;
 use crate::display::damage::RenderDamageIterator;
 use crate::display::hint::{HintMatch, HintState};
+use crate::display::key_sequence::KeySequence;
 use crate::display::meter::Meter;
 use crate::display::window::Window;
 use crate::event::{Event, EventType, Mouse, SearchState};
@@ -53,9 +56,13 @@ use crate::renderer::{self, GlyphCache, Renderer};
 use crate::scheduler::{Scheduler, TimerId, Topic};
 use crate::string::{ShortenDirection, StrShortener};
 
+pub mod clipboard_picker;
 pub mod content;
 pub mod cursor;
+pub mod filter;
 pub mod hint;
+pub mod key_sequence;
+pub mod timestamp;
 pub mod window;
 
 mod bell;
@@ -69,6 +76,9 @@ const FORWARD_SEARCH_LABEL: &str = "Search: ";
 /// Label for the backward terminal search bar.
 const BACKWARD_SEARCH_LABEL: &str = "Backward Search: ";
 
+/// Label for the filter view footer.
+const FILTER_LABEL: &str = "Filter: ";
+
 /// The character used to shorten the visible text like uri preview or search regex.
 const SHORTENER: char = '…';
 
@@ -152,8 +162,13 @@ pub struct SizeInfo<T = f32> {
     cell_height: T,
 
     /// Horizontal window padding.
+    ///
+    /// This is the padding left of the terminal grid, which includes the gutter.
     padding_x: T,
 
+    /// Width of the reserved area between the left window padding and the terminal grid.
+    gutter: T,
+
     /// Vertical window padding.
     padding_y: T,
 
@@ -173,6 +188,7 @@ impl From<SizeInfo<f32>> for SizeInfo<u32> {
             cell_height: size_info.cell_height as u32,
             padding_x: size_info.padding_x as u32,
             padding_y: size_info.padding_y as u32,
+            gutter: size_info.gutter as u32,
             screen_lines: size_info.screen_lines,
             columns: size_info.screen_lines,
         }
@@ -220,6 +236,11 @@ impl<T: Clone + Copy> SizeInfo<T> {
     pub fn padding_y(&self) -> T {
         self.padding_y
     }
+
+    #[inline]
+    pub fn gutter(&self) -> T {
+        self.gutter
+    }
 }
 
 impl SizeInfo<f32> {
@@ -251,6 +272,7 @@ impl SizeInfo<f32> {
             cell_height,
             padding_x: padding_x.floor(),
             padding_y: padding_y.floor(),
+            gutter: 0.,
             screen_lines,
             columns,
         }
@@ -261,6 +283,23 @@ impl SizeInfo<f32> {
         self.screen_lines = cmp::max(self.screen_lines.saturating_sub(count), MIN_SCREEN_LINES);
     }
 
+    /// Reserve columns for the gutter on the left side of the terminal grid.
+    #[inline]
+    pub fn reserve_columns(&mut self, count: usize) {
+        let count = cmp::min(count, self.columns.saturating_sub(MIN_COLUMNS));
+        let gutter = count as f32 * self.cell_width;
+
+        self.columns -= count;
+        self.padding_x += gutter;
+        self.gutter += gutter;
+    }
+
+    /// Horizontal window padding right of the terminal grid.
+    #[inline]
+    pub fn padding_right(&self) -> f32 {
+        self.padding_x - self.gutter
+    }
+
     /// Check if coordinates are inside the terminal grid.
     ///
     /// The padding, message bar or search are not counted as part of the grid.
@@ -359,9 +398,21 @@ pub struct Display {
     /// State of the keyboard hints.
     pub hint_state: HintState,
 
+    /// State of the clipboard history picker.
+    pub clipboard_picker: ClipboardPicker,
+
+    /// Keys of a partially entered key sequence binding.
+    pub key_sequence: KeySequence,
+
     /// Unprocessed display updates.
     pub pending_update: DisplayUpdate,
 
+    /// Format of the timestamp gutter, hidden when `None`.
+    pub timestamps: Option<TimestampFormat>,
+
+    /// Number of columns reserved for the gutter.
+    gutter_columns: usize,
+
     /// The renderer update that takes place only once before the actual rendering.
     pub pending_renderer_update: Option<RendererUpdate>,
 
@@ -436,7 +487,7 @@ impl Display {
         let viewport_size = window.inner_size();
 
         // Create new size with at least one column and row.
-        let size_info = SizeInfo::new(
+        let mut size_info = SizeInfo::new(
             viewport_size.width as f32,
             viewport_size.height as f32,
             cell_width,
@@ -446,6 +497,11 @@ impl Display {
             config.window.dynamic_padding && config.window.dimensions().is_none(),
         );
 
+        // Reserve space for the timestamp gutter.
+        let timestamps = config.timestamps.gutter();
+        let gutter_columns = if timestamps.is_some() { timestamp::GUTTER_COLUMNS } else { 0 };
+        size_info.reserve_columns(gutter_columns);
+
         info!("Cell size: {} x {}", cell_width, cell_height);
         info!("Padding: {} x {}", size_info.padding_x(), size_info.padding_y());
         info!("Width: {}, Height: {}", size_info.width(), size_info.height());
@@ -508,6 +564,8 @@ impl Display {
             renderer: ManuallyDrop::new(renderer),
             glyph_cache,
             hint_state,
+            clipboard_picker: Default::default(),
+            key_sequence: Default::default(),
             meter: Meter::new(),
             size_info,
             ime: Ime::new(),
@@ -519,6 +577,8 @@ impl Display {
             visual_bell: VisualBell::from(&config.bell),
             colors: List::from(&config.colors),
             pending_update: Default::default(),
+            timestamps,
+            gutter_columns,
             pending_renderer_update: Default::default(),
             debug_damage,
             damage_rects,
@@ -528,6 +588,31 @@ impl Display {
     }
 
     #[inline]
+    /// Show the timestamp gutter in `format`, or hide it when `None`.
+    ///
+    /// The gutter's columns are reserved when it is first shown and kept afterwards, so toggling
+    /// the gutter doesn't resize the terminal.
+    pub fn set_timestamps(&mut self, format: Option<TimestampFormat>) {
+        if format.is_some() {
+            self.reserve_gutter(timestamp::GUTTER_COLUMNS);
+        }
+
+        self.timestamps = format;
+    }
+
+    /// Show the gutter's last column, which contains the vi mode marks.
+    pub fn show_marks(&mut self) {
+        self.reserve_gutter(1);
+    }
+
+    /// Reserve at least `columns` for the gutter.
+    fn reserve_gutter(&mut self, columns: usize) {
+        if columns > self.gutter_columns {
+            self.gutter_columns = columns;
+            self.pending_update.dirty = true;
+        }
+    }
+
     pub fn gl_context(&self) -> &PossiblyCurrentContext {
         self.context.get()
     }
@@ -647,6 +732,9 @@ impl Display {
         let search_lines = usize::from(search_active);
         new_size.reserve_lines(message_bar_lines + search_lines);
 
+        // Reserve space for the gutter.
+        new_size.reserve_columns(self.gutter_columns);
+
         // Update resize increments.
         if config.window.resize_increments {
             self.window.set_resize_increments(PhysicalSize::new(cell_width, cell_height));
@@ -728,7 +816,9 @@ impl Display {
     ) {
         let requires_full_damage = self.visual_bell.intensity() != 0.
             || self.hint_state.active()
-            || search_state.regex().is_some();
+            || self.clipboard_picker.active()
+            || search_state.regex().is_some()
+            || search_state.filter.is_some();
         if requires_full_damage {
             terminal.mark_fully_damaged();
         }
@@ -783,7 +873,11 @@ impl Display {
         search_state: &SearchState,
     ) {
         // Collect renderable content before the terminal is dropped.
-        let mut content = RenderableContent::new(config, self, &terminal, search_state);
+        let filter = search_state.filter.as_ref();
+        let mut content = match filter {
+            Some(filter) => RenderableContent::new(config, self, filter.term(), search_state),
+            None => RenderableContent::new(config, self, &terminal, search_state),
+        };
         let mut grid_cells = Vec::new();
         for cell in &mut content {
             grid_cells.push(cell);
@@ -791,17 +885,57 @@ impl Display {
         let selection_range = content.selection_range();
         let foreground_color = content.color(NamedColor::Foreground as usize);
         let background_color = content.color(NamedColor::Background as usize);
+        let dim_foreground_color = content.color(NamedColor::DimForeground as usize);
         let display_offset = content.display_offset();
         let cursor = content.cursor();
 
+        // Collect the hint labels which can't be shown in place.
+        let mut hint_list = Vec::new();
+        if self.hint_state.active() {
+            let max_lines = cmp::max(self.size_info.screen_lines() / 2, 1);
+            hint_list = self.hint_state.offscreen_labels(&terminal, max_lines);
+        }
+
+        // Collect the targets of the hints below the mouse and vi mode cursor.
+        let mut hint_previews: Vec<String> = Vec::new();
+        if filter.is_none() {
+            for hint in self.highlighted_hint.iter().chain(&self.vi_highlighted_hint) {
+                let preview = hint.text(&terminal);
+                if !hint_previews.contains(&preview) {
+                    hint_previews.push(preview);
+                }
+            }
+        }
+
         let cursor_point = terminal.grid().cursor.point;
         let total_lines = terminal.grid().total_lines();
         let metrics = self.glyph_cache.font_metrics();
         let size_info = self.size_info;
 
-        let vi_mode = terminal.mode().contains(TermMode::VI);
+        let vi_mode = terminal.mode().contains(TermMode::VI) && filter.is_none();
         let vi_cursor_point = if vi_mode { Some(terminal.vi_mode_cursor.point) } else { None };
 
+        // Collect the timestamps and vi mode marks of all visible lines.
+        let mut gutter = Vec::new();
+        if self.gutter_columns > 0 {
+            let grid = filter.map_or_else(|| terminal.grid(), |filter| filter.term().grid());
+            gutter = (0..grid.screen_lines())
+                .map(|line| Line(line as i32 - display_offset as i32))
+                .map(|line| (grid.timestamp(line), None))
+                .collect();
+
+            // Marks are positioned in the terminal, not the filter view.
+            let marks = terminal.vi_marks.iter().filter(|_| filter.is_none());
+            for (name, point) in marks {
+                let mark = term::point_to_viewport(display_offset, point)
+                    .and_then(|point| gutter.get_mut(point.line))
+                    .map(|(_, mark)| mark);
+                if let Some(mark @ None) = mark {
+                    *mark = Some(name);
+                }
+            }
+        }
+
         if self.collect_damage() {
             self.update_damage(&mut terminal, selection_range, search_state);
         }
@@ -816,8 +950,8 @@ impl Display {
         let mut lines = RenderLines::new();
 
         // Optimize loop hint comparator.
-        let has_highlighted_hint =
-            self.highlighted_hint.is_some() || self.vi_highlighted_hint.is_some();
+        let has_highlighted_hint = filter.is_none()
+            && (self.highlighted_hint.is_some() || self.vi_highlighted_hint.is_some());
 
         // Draw grid.
         {
@@ -862,18 +996,34 @@ impl Display {
 
         let mut rects = lines.rects(&metrics, &size_info);
 
+        if self.gutter_columns > 0 {
+            self.draw_gutter(
+                scheduler,
+                self.timestamps,
+                &gutter,
+                dim_foreground_color,
+                background_color,
+            );
+        }
+
+        let mut indicator_column = Column(size_info.columns());
         if let Some(vi_cursor_point) = vi_cursor_point {
             // Indicate vi mode by showing the cursor's position in the top right corner.
             let line = (-vi_cursor_point.line.0 + size_info.bottommost_line().0) as usize;
             let obstructed_column = Some(vi_cursor_point)
                 .filter(|point| point.line == -(display_offset as i32))
                 .map(|point| point.column);
-            self.draw_line_indicator(config, total_lines, obstructed_column, line);
+            indicator_column =
+                self.draw_line_indicator(config, total_lines, obstructed_column, line);
         } else if search_state.regex().is_some() {
             // Show current display offset in vi-less search to indicate match position.
-            self.draw_line_indicator(config, total_lines, None, display_offset);
+            indicator_column = self.draw_line_indicator(config, total_lines, None, display_offset);
         };
 
+        // Show how input is handled next to the line indicator, since the title isn't always
+        // visible.
+        self.draw_input_indicator(config, indicator_column);
+
         // Draw cursor.
         rects.extend(cursor.rects(&size_info, config.terminal_config.cursor.thickness()));
 
@@ -901,8 +1051,15 @@ impl Display {
 
                 let search_text = Self::format_search(regex, search_label, size_info.columns());
 
+                // Show the enabled flags and the position of the focused match.
+                let focused_point = search_state.focused_match().map(|m| *m.start());
+                let match_count =
+                    search_state.match_counter.label(focused_point.or(vi_cursor_point));
+                let flags = search_state.flags().unwrap_or_default();
+                let status = Self::format_search_status(flags, match_count);
+
                 // Render the search bar.
-                self.draw_search(config, &search_text);
+                self.draw_footer(config, size_info.screen_lines(), &search_text, &status);
 
                 // Draw search bar cursor.
                 let line = size_info.screen_lines();
@@ -927,6 +1084,13 @@ impl Display {
             },
         };
 
+        // Draw the filter view footer below its lines.
+        if let Some(filter) = filter {
+            let (regex, status) = filter.footer();
+            let text = Self::format_search(regex, FILTER_LABEL, size_info.columns());
+            self.draw_footer(config, filter.page_lines(), &text, &status);
+        }
+
         // Handle IME.
         if self.ime.is_enabled() {
             if let Some(point) = ime_position {
@@ -987,10 +1151,25 @@ impl Display {
 
         self.draw_render_timer(config);
 
-        // Draw hyperlink uri preview.
-        if has_highlighted_hint {
+        // Draw hyperlink uri and hint text preview.
+        if !hint_previews.is_empty() {
             let cursor_point = vi_cursor_point.or(Some(cursor_point));
-            self.draw_hyperlink_preview(config, cursor_point, display_offset);
+            self.draw_hint_preview(config, &hint_previews, cursor_point, display_offset);
+        }
+
+        // Draw clipboard history picker.
+        if self.clipboard_picker.active() {
+            self.draw_clipboard_picker(config);
+        }
+
+        // Draw labels of hints which can't be shown in place.
+        if !hint_list.is_empty() {
+            self.draw_hint_list(config, &hint_list);
+        }
+
+        // Draw the keys of a partially entered key sequence.
+        if self.key_sequence.active() {
+            self.draw_key_sequence(config);
         }
 
         // Frame event should be requested before swapping buffers on Wayland, since it requires
@@ -1196,27 +1375,25 @@ impl Display {
         bar_text
     }
 
-    /// Draw preview for the currently highlighted `Hyperlink`.
+    /// Draw preview for the currently highlighted hints.
+    ///
+    /// This shows the `Hyperlink` URI or the hint's text, which is where the hint will lead when
+    /// triggered.
     #[inline(never)]
-    fn draw_hyperlink_preview(
+    fn draw_hint_preview(
         &mut self,
         config: &UiConfig,
+        previews: &[String],
         cursor_point: Option<Point>,
         display_offset: usize,
     ) {
         let num_cols = self.size_info.columns();
-        let uris: Vec<_> = self
-            .highlighted_hint
+        let uris: Vec<_> = previews
             .iter()
-            .chain(&self.vi_highlighted_hint)
-            .filter_map(|hint| hint.hyperlink().map(|hyperlink| hyperlink.uri()))
             .map(|uri| StrShortener::new(uri, num_cols, ShortenDirection::Right, Some(SHORTENER)))
+            .map(|uri| uri.map(|c| if c.is_control() { ' ' } else { c }))
             .collect();
 
-        if uris.is_empty() {
-            return;
-        }
-
         // The maximum amount of protected lines including the ones we'll show preview on.
         let max_protected_lines = uris.len() * 2;
 
@@ -1263,14 +1440,118 @@ impl Display {
         }
     }
 
-    /// Draw current search regex.
+    /// Draw the clipboard history picker over the bottom lines of the terminal.
     #[inline(never)]
-    fn draw_search(&mut self, config: &UiConfig, text: &str) {
+    fn draw_clipboard_picker(&mut self, config: &UiConfig) {
+        let screen_lines = self.size_info.screen_lines();
+        let max_lines = cmp::max(screen_lines / 2, 1);
+        let lines = self.clipboard_picker.lines(self.size_info.columns(), max_lines);
+
+        let fg = config.colors.footer_bar_foreground();
+        let bg = config.colors.footer_bar_background();
+
+        let start_line = screen_lines - lines.len();
+        for (i, (text, selected)) in lines.iter().enumerate() {
+            // Highlight the selected entry by inverting its colors.
+            let (fg, bg) = if *selected { (bg, fg) } else { (fg, bg) };
+
+            let point = Point::new(start_line + i, Column(0));
+            self.renderer.draw_string(
+                point,
+                fg,
+                bg,
+                text.chars(),
+                &self.size_info,
+                &mut self.glyph_cache,
+            );
+        }
+    }
+
+    /// Draw the labels and text of hints which can't be shown in place over the bottom lines of the
+    /// terminal.
+    #[inline(never)]
+    fn draw_hint_list(&mut self, config: &UiConfig, entries: &[(String, String)]) {
+        let columns = self.size_info.columns();
+        let fg = config.colors.footer_bar_foreground();
+        let bg = config.colors.footer_bar_background();
+
+        let start_line = self.size_info.screen_lines() - entries.len();
+        for (i, (label, text)) in entries.iter().enumerate() {
+            let line = start_line + i;
+
+            // Highlight the label by inverting its colors.
+            let label_len = cmp::min(label.chars().count(), columns);
+            let point = Point::new(line, Column(0));
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, bg, fg, label.chars(), &self.size_info, glyph_cache);
+
+            if label_len + 1 >= columns {
+                continue;
+            }
+
+            let text_width = columns - label_len;
+            let text = format!(" {}", text);
+            let shortened: String =
+                StrShortener::new(&text, text_width, ShortenDirection::Right, Some(SHORTENER))
+                    .collect();
+            let text = format!("{:<1$}", shortened, text_width);
+
+            let point = Point::new(line, Column(label_len));
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, fg, bg, text.chars(), &self.size_info, glyph_cache);
+        }
+    }
+
+    /// Draw the pending keys of a key sequence over the last line of the terminal.
+    #[inline(never)]
+    fn draw_key_sequence(&mut self, config: &UiConfig) {
+        let line = self.size_info.screen_lines().saturating_sub(1);
+        let text = format!("{} {}", self.key_sequence.label(), SHORTENER);
+        self.draw_footer(config, line, &text, "");
+
+        // Damage the line for the next frame as well, to clear it once the sequence is done.
+        if self.collect_damage() {
+            let point = Point::new(line, Column(0));
+            let damage = self.damage_from_point(point, self.size_info.columns() as u32);
+            self.damage_rects.push(damage);
+            self.next_frame_damage_rects.push(damage);
+        }
+    }
+
+    /// Format the search flags and match count shown at the end of the search bar.
+    fn format_search_status(flags: SearchFlags, match_count: Option<String>) -> String {
+        let indicators = [
+            (SearchFlags::SMART_CASE, "Aa"),
+            (SearchFlags::LITERAL, "lit"),
+            (SearchFlags::WHOLE_WORD, "word"),
+        ];
+
+        let mut status: Vec<String> = indicators
+            .iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            .map(|(_, label)| label.to_string())
+            .collect();
+        status.extend(match_count);
+
+        status.join(" ")
+    }
+
+    /// Draw a footer bar, like the one showing the current search regex.
+    ///
+    /// The status is right-aligned, if there is space for it.
+    #[inline(never)]
+    fn draw_footer(&mut self, config: &UiConfig, line: usize, text: &str, status: &str) {
         // Assure text length is at least num_cols.
         let num_cols = self.size_info.columns();
-        let text = format!("{:<1$}", text, num_cols);
+        let mut text = format!("{:<1$}", text, num_cols);
+
+        let text_len = text.trim_end().chars().count() + 1;
+        let status_len = status.chars().count();
+        if status_len > 0 && text_len + status_len < num_cols {
+            text = format!("{:<2$}{}", text.trim_end(), status, num_cols - status_len);
+        }
 
-        let point = Point::new(self.size_info.screen_lines(), Column(0));
+        let point = Point::new(line, Column(0));
 
         let fg = config.colors.footer_bar_foreground();
         let bg = config.colors.footer_bar_background();
@@ -1285,6 +1566,55 @@ impl Display {
         );
     }
 
+    /// Draw the gutter left of the terminal grid.
+    ///
+    /// Vi mode marks are shown in the gutter's last column, timestamps are only shown when their
+    /// `format` is set.
+    #[inline(never)]
+    fn draw_gutter(
+        &mut self,
+        scheduler: &mut Scheduler,
+        format: Option<TimestampFormat>,
+        gutter: &[(Timestamp, Option<char>)],
+        fg: Rgb,
+        bg: Rgb,
+    ) {
+        let size_info = self.size_info;
+        self.renderer.set_gutter_viewport(&size_info);
+
+        // Cut off the start of the timestamps when the window is too small for the whole gutter.
+        let columns = (size_info.gutter() / size_info.cell_width()).round() as usize;
+        let skip = timestamp::GUTTER_COLUMNS.saturating_sub(columns);
+
+        let now = grid::timestamp_now();
+        let width = timestamp::GUTTER_COLUMNS - 1;
+        for (line, (timestamp, mark)) in gutter.iter().enumerate() {
+            let timestamp = format.map(|format| timestamp::format(*timestamp, now, format));
+            let timestamp = timestamp.unwrap_or_default();
+            let text = format!("{:>2$}{}", timestamp, mark.unwrap_or(' '), width);
+            let text = text.chars().skip(skip);
+            let point = Point::new(line, Column(0));
+            let glyph_cache = &mut self.glyph_cache;
+            self.renderer.draw_string(point, fg, bg, text, &size_info, glyph_cache);
+        }
+
+        self.renderer.set_viewport(&size_info);
+
+        if self.collect_damage() {
+            let size_info: SizeInfo<u32> = self.size_info.into();
+            let width = size_info.padding_x() as i32;
+            self.damage_rects.push(DamageRect::new(0, 0, width, size_info.height() as i32));
+        }
+
+        // Keep relative timestamps up to date.
+        let window_id = self.window.id();
+        let timer_id = TimerId::new(Topic::Timestamps, window_id);
+        if format == Some(TimestampFormat::Relative) && !scheduler.scheduled(timer_id) {
+            let event = Event::new(EventType::RedrawTimestamps, window_id);
+            scheduler.schedule(event, Duration::from_secs(1), false, timer_id);
+        }
+    }
+
     /// Draw render timer.
     #[inline(never)]
     fn draw_render_timer(&mut self, config: &UiConfig) {
@@ -1312,6 +1642,8 @@ impl Display {
     }
 
     /// Draw an indicator for the position of a line in history.
+    ///
+    /// Returns the column the indicator starts at.
     #[inline(never)]
     fn draw_line_indicator(
         &mut self,
@@ -1319,7 +1651,7 @@ impl Display {
         total_lines: usize,
         obstructed_column: Option<Column>,
         line: usize,
-    ) {
+    ) -> Column {
         const fn num_digits(mut number: u32) -> usize {
             let mut res = 0;
             loop {
@@ -1336,8 +1668,9 @@ impl Display {
         let point = Point::new(0, column);
 
         // Damage the maximum possible length of the format text, which could be achieved when
-        // using `MAX_SCROLLBACK_LINES` as current and total lines adding a `3` for formatting.
-        const MAX_SIZE: usize = 2 * num_digits(MAX_SCROLLBACK_LINES) + 3;
+        // using `u32::MAX` as current and total lines adding a `3` for formatting, since the
+        // scrollback history is unlimited with a history file.
+        const MAX_SIZE: usize = 2 * num_digits(u32::MAX) + 3;
         let damage_point = Point::new(0, Column(self.size_info.columns().saturating_sub(MAX_SIZE)));
         if self.collect_damage() {
             self.damage_rects.push(self.damage_from_point(damage_point, MAX_SIZE as u32));
@@ -1352,6 +1685,41 @@ impl Display {
             let glyph_cache = &mut self.glyph_cache;
             self.renderer.draw_string(point, fg, bg, text.chars(), &self.size_info, glyph_cache);
         }
+
+        column
+    }
+
+    /// Draw the modes changing how input is handled in the top line, ending before `end`.
+    #[inline(never)]
+    fn draw_input_indicator(&mut self, config: &UiConfig, end: Column) {
+        let mut text = String::new();
+        if self.window.read_only() {
+            text.push_str("[Read-only]");
+        }
+        if self.window.broadcast() {
+            text.push_str("[Broadcast]");
+        }
+
+        if text.is_empty() {
+            return;
+        }
+
+        let column = Column(end.saturating_sub(text.len()));
+        let point = Point::new(0, column);
+
+        // Damage the line for the next frame as well, to clear it once the mode is left.
+        if self.collect_damage() {
+            let damage = self.damage_from_point(point, text.len() as u32);
+            self.damage_rects.push(damage);
+            self.next_frame_damage_rects.push(damage);
+        }
+
+        let colors = &config.colors;
+        let fg = colors.line_indicator.foreground.unwrap_or(colors.primary.background);
+        let bg = colors.line_indicator.background.unwrap_or(colors.primary.foreground);
+
+        let glyph_cache = &mut self.glyph_cache;
+        self.renderer.draw_string(point, fg, bg, text.chars(), &self.size_info, glyph_cache);
     }
 
     /// Damage `len` starting from a `point`.
@@ -0,0 +1,175 @@
+//! Formatting and parsing of line timestamps.
+
+use alacritty_terminal::grid::Timestamp;
+
+use crate::config::timestamps::TimestampFormat;
+
+const SECOND: u64 = 1000;
+const MINUTE: u64 = 60 * SECOND;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// Maximum number of days shown in the relative format.
+const MAX_DAYS: u64 = 999;
+
+/// Columns reserved for the gutter, fitting the widest timestamp and a vi mode mark.
+pub const GUTTER_COLUMNS: usize = TimestampFormat::Absolute.width() + 1;
+
+/// Format a line's timestamp for the gutter.
+///
+/// Lines without a timestamp are formatted as an empty string.
+pub fn format(timestamp: Timestamp, now: Timestamp, format: TimestampFormat) -> String {
+    if timestamp == 0 {
+        return String::new();
+    }
+
+    match format {
+        TimestampFormat::Absolute => {
+            let time = local_time(timestamp) % DAY;
+            let (hours, minutes, seconds) =
+                (time / HOUR, time % HOUR / MINUTE, time % MINUTE / SECOND);
+            format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+        },
+        TimestampFormat::Relative => {
+            let elapsed = now.saturating_sub(timestamp);
+            match elapsed {
+                elapsed if elapsed < MINUTE => format!("{}s", elapsed / SECOND),
+                elapsed if elapsed < HOUR => format!("{}m", elapsed / MINUTE),
+                elapsed if elapsed < DAY => format!("{}h", elapsed / HOUR),
+                elapsed => format!("{}d", (elapsed / DAY).min(MAX_DAYS)),
+            }
+        },
+    }
+}
+
+/// Parse a time to jump to.
+///
+/// Both the time of day (`HH:MM` or `HH:MM:SS`) and time relative to `now` (like `30s`, `5m`,
+/// `2h` or `1d`) are supported. Times of day in the future refer to the previous day.
+pub fn parse(text: &str, now: Timestamp) -> Option<Timestamp> {
+    if let Some(unit) = text.chars().last().and_then(unit_millis) {
+        let count: u64 = text[..text.len() - 1].parse().ok()?;
+        return Some(now.saturating_sub(count.checked_mul(unit)?));
+    }
+
+    let mut components = text.split(':');
+    let hours = time_component(components.next()?, 24)?;
+    let minutes = time_component(components.next()?, 60)?;
+    let seconds = components.next().map_or(Some(0), |seconds| time_component(seconds, 60))?;
+    if components.next().is_some() {
+        return None;
+    }
+
+    // Convert the time of day to a timestamp based on the local date.
+    let local_now = local_time(now);
+    let offset = local_now as i64 - now as i64;
+    let time = hours * HOUR + minutes * MINUTE + seconds * SECOND;
+    let mut local = local_now - local_now % DAY + time;
+    if local > local_now {
+        local = local.saturating_sub(DAY);
+    }
+
+    Some((local as i64 - offset).max(1) as Timestamp)
+}
+
+/// Milliseconds in a relative time unit.
+fn unit_millis(unit: char) -> Option<u64> {
+    match unit {
+        's' => Some(SECOND),
+        'm' => Some(MINUTE),
+        'h' => Some(HOUR),
+        'd' => Some(DAY),
+        _ => None,
+    }
+}
+
+/// Parse a two digit time component below `max`.
+fn time_component(text: &str, max: u64) -> Option<u64> {
+    if text.is_empty() || text.len() > 2 || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok().filter(|value| *value < max)
+}
+
+/// Convert a UTC timestamp to the local timezone.
+#[cfg(unix)]
+fn local_time(timestamp: Timestamp) -> Timestamp {
+    let offset = utc_offset(timestamp / SECOND).unwrap_or(0);
+    (timestamp as i64 + offset * SECOND as i64).max(0) as Timestamp
+}
+
+/// Offset of the local timezone from UTC in seconds, at `seconds` since the epoch.
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)]
+fn utc_offset(seconds: u64) -> Option<i64> {
+    let seconds = libc::time_t::try_from(seconds).ok()?;
+
+    // SAFETY: `tm` is a plain C struct for which all zero bytes are a valid value, and
+    // `localtime_r` only writes to it, without keeping either pointer past the call.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&seconds, &mut tm).is_null() {
+            return None;
+        }
+
+        Some(i64::from(tm.tm_gmtoff))
+    }
+}
+
+/// Convert a UTC timestamp to the local timezone.
+#[cfg(not(unix))]
+fn local_time(timestamp: Timestamp) -> Timestamp {
+    timestamp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_relative() {
+        let now = 10 * DAY;
+        let format = |timestamp| super::format(timestamp, now, TimestampFormat::Relative);
+
+        assert_eq!(format(0), "");
+        assert_eq!(format(now), "0s");
+        assert_eq!(format(now - 59 * SECOND), "59s");
+        assert_eq!(format(now - 5 * MINUTE), "5m");
+        assert_eq!(format(now - 3 * HOUR - MINUTE), "3h");
+        assert_eq!(format(now - 2 * DAY), "2d");
+        assert_eq!(format(now + SECOND), "0s");
+    }
+
+    #[test]
+    fn parse_relative() {
+        let now = 10 * DAY;
+
+        assert_eq!(parse("30s", now), Some(now - 30 * SECOND));
+        assert_eq!(parse("5m", now), Some(now - 5 * MINUTE));
+        assert_eq!(parse("2h", now), Some(now - 2 * HOUR));
+        assert_eq!(parse("1d", now), Some(now - DAY));
+        assert_eq!(parse("m", now), None);
+        assert_eq!(parse("5x", now), None);
+    }
+
+    #[test]
+    fn parse_time_of_day() {
+        let now = 10 * DAY + 12 * HOUR;
+
+        // Parsed times are always in the past and format back to the same time of day.
+        for text in ["00:00", "11:59:30", "12:00", "23:59:59"] {
+            let timestamp = parse(text, now).unwrap();
+            assert!(timestamp <= now);
+            assert!(now - timestamp < DAY);
+
+            let formatted = format(timestamp, now, TimestampFormat::Absolute);
+            assert!(formatted.starts_with(text), "{} != {}", formatted, text);
+        }
+
+        assert_eq!(parse("24:00", now), None);
+        assert_eq!(parse("12:60", now), None);
+        assert_eq!(parse("12", now), None);
+        assert_eq!(parse("12:00:00:00", now), None);
+    }
+}
@@ -121,6 +121,15 @@ pub struct Window {
     /// Current window title.
     title: String,
 
+    /// Active user-defined binding mode, shown next to the title.
+    binding_mode: Option<String>,
+
+    /// Whether input is broadcast to other windows, shown next to the title.
+    broadcast: bool,
+
+    /// Whether input is kept from the PTY, shown next to the title.
+    read_only: bool,
+
     current_mouse_cursor: CursorIcon,
     mouse_visible: bool,
 }
@@ -207,6 +216,9 @@ impl Window {
             mouse_visible: true,
             window,
             title: identity.title,
+            binding_mode: None,
+            broadcast: false,
+            read_only: false,
             has_frame: Arc::new(AtomicBool::new(true)),
             #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
             wayland_surface,
@@ -238,7 +250,7 @@ impl Window {
     #[inline]
     pub fn set_title(&mut self, title: String) {
         self.title = title;
-        self.window.set_title(&self.title);
+        self.update_title();
     }
 
     /// Get the window title.
@@ -247,6 +259,59 @@ impl Window {
         &self.title
     }
 
+    /// Set the active user-defined binding mode.
+    pub fn set_binding_mode(&mut self, mode: Option<String>) {
+        self.binding_mode = mode;
+        self.update_title();
+    }
+
+    /// Get the active user-defined binding mode.
+    #[inline]
+    pub fn binding_mode(&self) -> Option<&str> {
+        self.binding_mode.as_deref()
+    }
+
+    /// Show whether input is broadcast to other windows.
+    pub fn set_broadcast(&mut self, broadcast: bool) {
+        self.broadcast = broadcast;
+        self.update_title();
+    }
+
+    /// Check if input is broadcast to other windows.
+    #[inline]
+    pub fn broadcast(&self) -> bool {
+        self.broadcast
+    }
+
+    /// Show whether input is kept from the PTY.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        self.update_title();
+    }
+
+    /// Check if input is kept from the PTY.
+    #[inline]
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Update the title shown by the window system.
+    fn update_title(&self) {
+        let mut title = String::new();
+        if self.read_only {
+            title.push_str("[Read-only] ");
+        }
+        if self.broadcast {
+            title.push_str("[Broadcast] ");
+        }
+        if let Some(mode) = &self.binding_mode {
+            title.push_str(&format!("[{}] ", mode));
+        }
+        title.push_str(&self.title);
+
+        self.window.set_title(&title);
+    }
+
     #[inline]
     pub fn request_redraw(&self) {
         self.window.request_redraw();
@@ -11,6 +11,7 @@ use std::os::unix::io::RawFd;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::{env, f32, mem};
 
@@ -35,27 +36,35 @@ use crossfont::{self, Size};
 use alacritty_terminal::config::LOG_TARGET_CONFIG;
 use alacritty_terminal::event::{Event as TerminalEvent, EventListener, Notify};
 use alacritty_terminal::event_loop::Notifier;
-use alacritty_terminal::grid::{Dimensions, Scroll};
+use alacritty_terminal::grid::{self, Dimensions, Scroll, Timestamp};
 use alacritty_terminal::index::{Boundary, Column, Direction, Line, Point, Side};
-use alacritty_terminal::selection::{Selection, SelectionType};
-use alacritty_terminal::term::search::{Match, RegexSearch};
+use alacritty_terminal::selection::{self, Selection, SelectionType};
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::search::{self, Match, RegexSearch, SearchFlags};
 use alacritty_terminal::term::{self, ClipboardType, Term, TermMode};
 
+use crate::broadcast::BroadcastGroup;
 #[cfg(unix)]
-use crate::cli::IpcConfig;
+use crate::cli::{IpcBroadcastInput, IpcConfig, IpcExport};
 use crate::cli::{Options as CliOptions, WindowOptions};
 use crate::clipboard::Clipboard;
 use crate::config::ui_config::{HintAction, HintInternalAction};
-use crate::config::{self, UiConfig};
+use crate::config::{self, paste, UiConfig};
 #[cfg(not(windows))]
 use crate::daemon::foreground_process_path;
 use crate::daemon::spawn_daemon;
-use crate::display::hint::HintMatch;
+use crate::display::filter::FilterView;
+use crate::display::hint::{self, HintMatch};
+use crate::display::key_sequence::KeySequence;
+use crate::display::timestamp;
 use crate::display::window::Window;
 use crate::display::{Display, Preedit, SizeInfo};
-use crate::input::{self, ActionContext as _, FONT_SIZE_STEP};
-use crate::message_bar::{Message, MessageBuffer};
+use crate::input::{self, ActionContext as _, ViInput, FONT_SIZE_STEP};
+use crate::macros::Macros;
+use crate::match_counter::MatchCounter;
+use crate::message_bar::{Message, MessageBuffer, MessageType};
 use crate::scheduler::{Scheduler, TimerId, Topic};
+use crate::search_history::SearchHistory;
 use crate::window_context::WindowContext;
 
 #[cfg(feature = "takeover")]
@@ -64,15 +73,21 @@ use crate::takeover::{Takeover, TakeoverEvent};
 /// Duration after the last user input until an unlimited search is performed.
 pub const TYPING_SEARCH_DELAY: Duration = Duration::from_millis(500);
 
+/// Minimum duration between counts of all search matches while receiving output.
+const SEARCH_RECOUNT_DELAY: Duration = Duration::from_millis(250);
+
 /// Maximum number of lines for the blocking search while still typing the search regex.
 const MAX_SEARCH_WHILE_TYPING: Option<usize> = Some(1000);
 
-/// Maximum number of search terms stored in the history.
-const MAX_SEARCH_HISTORY_SIZE: usize = 255;
+/// Search prefix for jumping to a line's timestamp.
+const TIMESTAMP_SEARCH_PREFIX: char = '@';
 
 /// Touch zoom speed.
 const TOUCH_ZOOM_FACTOR: f32 = 0.01;
 
+/// Message target of the paste confirmation prompt.
+const PASTE_PROMPT_TARGET: &str = "paste_prompt";
+
 /// Alacritty events.
 #[derive(Debug, Clone)]
 pub struct Event {
@@ -106,12 +121,23 @@ pub enum EventType {
     CreateWindow(WindowOptions),
     #[cfg(unix)]
     IpcConfig(IpcConfig),
+    #[cfg(unix)]
+    IpcExport(IpcExport),
+    #[cfg(unix)]
+    IpcBroadcastInput(IpcBroadcastInput),
     #[cfg(feature = "takeover")]
     Takeover(TakeoverEvent),
     BlinkCursor,
     BlinkCursorTimeout,
     SearchNext,
+    SearchCount(usize, Option<Vec<Match>>),
+    SearchRecount,
     Frame,
+    RedrawTimestamps,
+    KeySequenceTimeout,
+    ExportScrollback,
+    ToggleBroadcastInput,
+    ToggleReadOnly,
 }
 
 impl From<TerminalEvent> for EventType {
@@ -143,15 +169,31 @@ pub struct SearchState {
     ///
     /// While going through history, the [`SearchState::history_index`] will point to the element
     /// in history which is currently being previewed.
-    history: VecDeque<String>,
+    history: VecDeque<SearchEntry>,
 
     /// Compiled search automatons.
     dfas: Option<RegexSearch>,
+
+    /// Matches of the search regex in the entire scrollback history.
+    pub match_counter: MatchCounter,
+
+    /// View showing only the lines matching the search regex.
+    pub filter: Option<FilterView>,
 }
 
 impl SearchState {
     /// Search regex text if a search is active.
     pub fn regex(&self) -> Option<&String> {
+        self.entry().map(|entry| &entry.regex)
+    }
+
+    /// Search flags if a search is active.
+    pub fn flags(&self) -> Option<SearchFlags> {
+        self.entry().map(|entry| entry.flags)
+    }
+
+    /// Active search history entry.
+    fn entry(&self) -> Option<&SearchEntry> {
         self.history_index.and_then(|index| self.history.get(index))
     }
 
@@ -170,10 +212,55 @@ impl SearchState {
         self.dfas.as_ref()
     }
 
+    /// Count the matches of the active search in the background.
+    pub fn update_match_count(
+        &mut self,
+        terminal: &Arc<FairMutex<Term<EventProxy>>>,
+        event_proxy: EventProxy,
+    ) {
+        match &self.dfas {
+            Some(dfas) if self.history_index.is_some() => {
+                self.match_counter.update(terminal, dfas, event_proxy);
+            },
+            _ => (),
+        }
+    }
+
+    /// Replace the search dfas, discarding all counted matches.
+    fn set_dfas(&mut self, dfas: Option<RegexSearch>) {
+        self.dfas = dfas;
+        self.match_counter.reset();
+    }
+
     /// Search regex text if a search is active.
     fn regex_mut(&mut self) -> Option<&mut String> {
-        self.history_index.and_then(move |index| self.history.get_mut(index))
+        let index = self.history_index?;
+        self.history.get_mut(index).map(|entry| &mut entry.regex)
     }
+
+    /// Make the history entry being previewed the user's current input.
+    ///
+    /// Returns `false` if no search is active.
+    fn edit_entry(&mut self) -> bool {
+        match self.history_index {
+            Some(0) => (),
+            // When currently in history, replace active regex with history on change.
+            Some(index) => {
+                self.history[0] = self.history[index].clone();
+                self.history_index = Some(0);
+            },
+            None => return false,
+        }
+
+        true
+    }
+}
+
+/// Search regex and the flags it is matched with.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct SearchEntry {
+    pub regex: String,
+    pub flags: SearchFlags,
 }
 
 impl Default for SearchState {
@@ -186,6 +273,8 @@ impl Default for SearchState {
             history: Default::default(),
             origin: Default::default(),
             dfas: Default::default(),
+            match_counter: Default::default(),
+            filter: Default::default(),
         }
     }
 }
@@ -194,8 +283,15 @@ pub struct ActionContext<'a, N, T> {
     pub notifier: &'a mut N,
     pub terminal: &'a mut Term<T>,
     pub clipboard: &'a mut Clipboard,
+    pub search_history: &'a mut SearchHistory,
+    pub macros: &'a mut Macros,
+    pub broadcast: &'a BroadcastGroup,
     pub mouse: &'a mut Mouse,
     pub touch: &'a mut TouchPurpose,
+    pub vi_input: &'a mut ViInput,
+    pub selection_expansions: &'a mut Vec<(Option<Selection>, Selection)>,
+    pub macro_recording: &'a mut Option<Vec<u8>>,
+    pub pending_paste: &'a mut Option<String>,
     pub received_count: &'a mut usize,
     pub suppress_chars: &'a mut bool,
     pub modifiers: &'a mut ModifiersState,
@@ -225,6 +321,21 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.notifier.notify(val);
     }
 
+    /// Send user input to the PTY, recording it for an active macro recording.
+    #[inline]
+    fn send_input<B: Into<Cow<'static, [u8]>>>(&mut self, val: B) {
+        let val = val.into();
+        if let Some(recording) = self.macro_recording.as_mut() {
+            recording.extend_from_slice(&val);
+        }
+
+        if self.broadcast.contains(self.display.window.id()) {
+            self.broadcast.write(val);
+        } else {
+            self.write_to_pty(val);
+        }
+    }
+
     /// Request a redraw.
     #[inline]
     fn mark_dirty(&mut self) {
@@ -279,6 +390,57 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.clipboard.store(ty, text);
     }
 
+    fn expand_selection_semantic(&mut self) {
+        let current = self.terminal.selection.clone();
+
+        // Forget expansions once the selection was changed by anything else.
+        if self.selection_expansions.last().map(|(_, expanded)| expanded) != current.as_ref() {
+            self.selection_expansions.clear();
+        }
+
+        // Start at the vi mode cursor without a selection.
+        let range = match current.as_ref().and_then(|selection| selection.to_range(self.terminal)) {
+            Some(range) => range.start..=range.end,
+            None if self.terminal.mode().contains(TermMode::VI) => {
+                let point = self.terminal.vi_mode_cursor.point;
+                point..=point
+            },
+            None => return,
+        };
+
+        let expanded = match selection::expand_semantic(self.terminal, &range) {
+            Some(expanded) => expanded,
+            None => return,
+        };
+
+        let mut selection = Selection::new(SelectionType::Simple, *expanded.start(), Side::Left);
+        selection.update(*expanded.end(), Side::Right);
+
+        self.selection_expansions.push((current, selection.clone()));
+        self.terminal.selection = Some(selection);
+        *self.dirty = true;
+
+        self.copy_selection(ClipboardType::Selection);
+    }
+
+    fn shrink_selection(&mut self) {
+        let (previous, expanded) = match self.selection_expansions.pop() {
+            Some(expansion) => expansion,
+            None => return,
+        };
+
+        // Only undo expansions of the current selection.
+        if self.terminal.selection.as_ref() != Some(&expanded) {
+            self.selection_expansions.clear();
+            return;
+        }
+
+        self.terminal.selection = previous;
+        *self.dirty = true;
+
+        self.copy_selection(ClipboardType::Selection);
+    }
+
     fn selection_is_empty(&self) -> bool {
         self.terminal.selection.as_ref().map_or(true, Selection::is_empty)
     }
@@ -290,6 +452,32 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         *self.dirty |= selection.map_or(false, |s| !s.is_empty());
     }
 
+    fn export_scrollback(&mut self) {
+        // Export from the event loop, which can release the terminal lock between chunks.
+        let event = Event::new(EventType::ExportScrollback, self.display.window.id());
+        let _ = self.event_proxy.send_event(event);
+    }
+
+    fn toggle_timestamps(&mut self) {
+        let format = match self.display.timestamps {
+            Some(_) => None,
+            None => Some(self.config.timestamps.format()),
+        };
+        self.display.set_timestamps(format);
+        *self.dirty = true;
+    }
+
+    fn toggle_timestamp_format(&mut self) {
+        if let Some(format) = self.display.timestamps {
+            self.display.set_timestamps(Some(format.toggled()));
+            *self.dirty = true;
+        }
+    }
+
+    fn show_marks(&mut self) {
+        self.display.show_marks();
+    }
+
     fn update_selection(&mut self, mut point: Point, side: Side) {
         let mut selection = match self.terminal.selection.take() {
             Some(selection) => selection,
@@ -338,6 +526,7 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
     fn mouse_mode(&self) -> bool {
         self.terminal.mode().intersects(TermMode::MOUSE_MODE)
             && !self.terminal.mode().contains(TermMode::VI)
+            && !self.display.window.read_only()
     }
 
     #[inline]
@@ -355,6 +544,11 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.touch
     }
 
+    #[inline]
+    fn vi_input(&mut self) -> &mut ViInput {
+        self.vi_input
+    }
+
     #[inline]
     fn received_count(&mut self) -> &mut usize {
         self.received_count
@@ -380,6 +574,28 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.display
     }
 
+    #[inline]
+    fn key_sequence(&mut self) -> &mut KeySequence {
+        &mut self.display.key_sequence
+    }
+
+    /// Restart the timeout for entering the next key of a sequence.
+    fn schedule_key_sequence_timeout(&mut self) {
+        self.cancel_key_sequence_timeout();
+
+        if let Some(timeout) = self.config.key_sequence.timeout() {
+            let window_id = self.display.window.id();
+            let event = Event::new(EventType::KeySequenceTimeout, window_id);
+            let timer_id = TimerId::new(Topic::KeySequence, window_id);
+            self.scheduler.schedule(event, timeout, false, timer_id);
+        }
+    }
+
+    fn cancel_key_sequence_timeout(&mut self) {
+        let timer_id = TimerId::new(Topic::KeySequence, self.display.window.id());
+        self.scheduler.unschedule(timer_id);
+    }
+
     #[inline]
     fn terminal(&self) -> &Term<T> {
         self.terminal
@@ -463,6 +679,12 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn pop_message(&mut self) {
+        // Closing the paste confirmation prompt cancels the paste.
+        let message = self.message_buffer.message();
+        if message.and_then(Message::target).map_or(false, |target| target == PASTE_PROMPT_TARGET) {
+            *self.pending_paste = None;
+        }
+
         if !self.message_buffer.is_empty() {
             self.display.pending_update.dirty = true;
             self.message_buffer.pop();
@@ -471,11 +693,13 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     #[inline]
     fn start_search(&mut self, direction: Direction) {
-        // Only create new history entry if the previous regex wasn't empty.
-        if self.search_state.history.get(0).map_or(true, |regex| !regex.is_empty()) {
-            self.search_state.history.push_front(String::new());
-            self.search_state.history.truncate(MAX_SEARCH_HISTORY_SIZE);
-        }
+        // Start from the history shared by all windows, with a new entry for the user's input.
+        let history = &mut self.search_state.history;
+        *history = self.search_history.entries().cloned().collect();
+
+        // Keep the flags of the previous search.
+        let flags = history.front().map_or_else(SearchFlags::default, |entry| entry.flags);
+        history.push_front(SearchEntry { regex: String::new(), flags });
 
         self.search_state.history_index = Some(0);
         self.search_state.direction = direction;
@@ -520,6 +744,11 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             self.goto_match(None);
         }
 
+        // Record the jump away from the search origin.
+        if self.terminal.vi_mode_cursor.point != self.search_state.origin {
+            self.terminal.vi_marks.push_jump(self.search_state.origin);
+        }
+
         self.exit_search();
     }
 
@@ -537,23 +766,17 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
             self.copy_selection(ClipboardType::Selection);
         }
 
-        self.search_state.dfas = None;
+        self.search_state.set_dfas(None);
 
         self.exit_search();
     }
 
     #[inline]
     fn search_input(&mut self, c: char) {
-        match self.search_state.history_index {
-            Some(0) => (),
-            // When currently in history, replace active regex with history on change.
-            Some(index) => {
-                self.search_state.history[0] = self.search_state.history[index].clone();
-                self.search_state.history_index = Some(0);
-            },
-            None => return,
+        if !self.search_state.edit_entry() {
+            return;
         }
-        let regex = &mut self.search_state.history[0];
+        let regex = &mut self.search_state.history[0].regex;
 
         match c {
             // Handle backspace/ctrl+h.
@@ -583,6 +806,86 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         }
     }
 
+    #[inline]
+    fn toggle_search_flag(&mut self, flag: SearchFlags) {
+        if self.search_state.edit_entry() {
+            self.search_state.history[0].flags.toggle(flag);
+            self.update_search();
+        }
+    }
+
+    #[inline]
+    fn start_filter(&mut self) {
+        let (text, regex) = match (self.search_state.regex(), self.search_state.dfas()) {
+            (Some(text), Some(regex)) => (text.clone(), regex.clone()),
+            _ => return,
+        };
+
+        self.exit_search();
+
+        let config = &self.config.terminal_config;
+        let size = self.display.size_info;
+        self.search_state.filter = Some(FilterView::new(self.terminal, text, regex, config, size));
+        self.terminal.grid_mut().collapse_history();
+    }
+
+    #[inline]
+    fn confirm_filter(&mut self) {
+        // Show the lines of the new grid, rather than jumping to a line which has been swapped out.
+        if let Some(filter) = &mut self.search_state.filter {
+            if filter.update(self.terminal) {
+                self.terminal.grid_mut().collapse_history();
+                *self.dirty = true;
+                return;
+            }
+        }
+
+        let filter = self.search_state.filter.as_ref();
+        let point = match filter.and_then(|filter| filter.selected_point(self.terminal)) {
+            Some(point) => point.grid_clamp(self.terminal, Boundary::Grid),
+            None => return self.cancel_filter(),
+        };
+
+        self.search_state.filter = None;
+        self.terminal.mark_fully_damaged();
+
+        // Keep the matches highlighted, like a confirmed vi mode search.
+        if !self.terminal.mode().contains(TermMode::VI) {
+            self.toggle_vi_mode();
+        }
+
+        // Record the jump away from the previous vi cursor position.
+        let origin = self.terminal.vi_mode_cursor.point;
+        if origin != point {
+            self.terminal.vi_marks.push_jump(origin);
+        }
+        self.terminal.vi_goto_point(point);
+
+        *self.dirty = true;
+    }
+
+    #[inline]
+    fn cancel_filter(&mut self) {
+        if self.search_state.filter.take().is_some() {
+            self.search_state.set_dfas(None);
+            self.terminal.mark_fully_damaged();
+            *self.dirty = true;
+        }
+    }
+
+    #[inline]
+    fn change_filter_context(&mut self, delta: isize) {
+        if let Some(filter) = &mut self.search_state.filter {
+            filter.change_context(self.terminal, delta);
+            *self.dirty = true;
+        }
+    }
+
+    #[inline]
+    fn filter_view(&mut self) -> Option<&mut FilterView> {
+        self.search_state.filter.as_mut()
+    }
+
     /// Go to the previous regex in the search history.
     #[inline]
     fn search_history_previous(&mut self) {
@@ -608,6 +911,49 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         self.update_search();
     }
 
+    /// Search for the word below the vi mode cursor.
+    #[inline]
+    fn search_word(&mut self, direction: Direction) {
+        let point = self.terminal.vi_mode_cursor.point;
+        let c = self.terminal.grid()[point].c;
+        if c == ' ' || self.terminal.semantic_escape_chars().contains(c) {
+            return;
+        }
+
+        let start = self.terminal.semantic_search_left(point);
+        let end = self.terminal.semantic_search_right(point);
+        let word = self.terminal.bounds_to_string(start, end);
+
+        // Like vi, only require word boundaries next to word characters.
+        let mut regex = search::escape(&word);
+        if word.starts_with(search::is_word_char) {
+            regex.insert_str(0, r"\b");
+        }
+        if word.ends_with(search::is_word_char) {
+            regex.push_str(r"\b");
+        }
+
+        // Add the regex to the search history.
+        let entry = SearchEntry { regex: regex.clone(), flags: SearchFlags::default() };
+        self.search_history.push(entry);
+
+        self.search_state.direction = direction;
+        self.search_state.set_dfas(RegexSearch::new(&regex).ok());
+
+        // Update the highlighted matches.
+        self.terminal.mark_fully_damaged();
+        *self.dirty = true;
+
+        // Jump to the next match, skipping the word below the cursor.
+        let origin = match direction {
+            Direction::Right => end.add(self.terminal, Boundary::None, 1),
+            Direction::Left => start.sub(self.terminal, Boundary::None, 1),
+        };
+        if let Some(regex_match) = self.search_next(origin, direction, Side::Left) {
+            self.terminal.vi_jump(*regex_match.start());
+        }
+    }
+
     #[inline]
     fn advance_search_origin(&mut self, direction: Direction) {
         // Use focused match as new search origin if available.
@@ -655,10 +1001,16 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     /// Find the next search match.
     fn search_next(&mut self, origin: Point, direction: Direction, side: Side) -> Option<Match> {
-        self.search_state
+        let regex_match = self
+            .search_state
             .dfas
             .as_ref()
-            .and_then(|dfas| self.terminal.search_next(dfas, origin, direction, side, None))
+            .and_then(|dfas| self.terminal.search_next(dfas, origin, direction, side, None));
+
+        // Drop history lines expanded by the search.
+        self.terminal.grid_mut().collapse_history();
+
+        regex_match
     }
 
     #[inline]
@@ -714,16 +1066,28 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         }
 
         let hint_bounds = hint.bounds();
-        let text = match hint.hyperlink() {
-            Some(hyperlink) => hyperlink.uri().to_owned(),
-            None => self.terminal.bounds_to_string(*hint_bounds.start(), *hint_bounds.end()),
-        };
+        let text = hint.text(self.terminal);
 
         match &hint.action() {
             // Launch an external program.
             HintAction::Command(command) => {
-                let mut args = command.args().to_vec();
-                args.push(text);
+                let window_id = u64::from(self.display.window.id()).to_string();
+
+                #[cfg(not(windows))]
+                let cwd = foreground_process_path(self.master_fd, self.shell_pid)
+                    .ok()
+                    .map(|cwd| cwd.to_string_lossy().into_owned());
+                #[cfg(windows)]
+                let cwd = None;
+
+                let mut values = vec![
+                    (String::from("match"), Some(text.clone())),
+                    (String::from("cwd"), cwd),
+                    (String::from("window_id"), Some(window_id)),
+                ];
+                values.extend(hint.captures(&text));
+
+                let args = hint::command_args(command.args(), &text, &values);
                 self.spawn_daemon(command.program(), &args);
             },
             // Copy the text to the clipboard.
@@ -791,33 +1155,53 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 
     /// Paste a text into the terminal.
     fn paste(&mut self, text: &str) {
+        let text = self.config.paste.transform(text);
+
         if self.search_active() {
             for c in text.chars() {
                 self.search_input(c);
             }
-        } else if self.terminal().mode().contains(TermMode::BRACKETED_PASTE) {
-            self.write_to_pty(&b"\x1b[200~"[..]);
+            return;
+        }
 
-            // Write filtered escape sequences.
-            //
-            // We remove `\x1b` to ensure it's impossible for the pasted text to write the bracketed
-            // paste end escape `\x1b[201~` and `\x03` since some shells incorrectly terminate
-            // bracketed paste on its receival.
-            let filtered = text.replace('\x1b', "").replace('\x03', "");
-            self.write_to_pty(filtered.into_bytes());
+        if self.display.window.read_only() {
+            return;
+        }
 
-            self.write_to_pty(&b"\x1b[201~"[..]);
+        let bracketed = self.terminal().mode().contains(TermMode::BRACKETED_PASTE);
+        match self.config.paste.risk(&text, bracketed) {
+            Some(risk) => self.prompt_paste(text.into_owned(), &risk),
+            None => self.write_paste(&text),
+        }
+    }
+
+    /// Check if a paste is waiting for confirmation.
+    #[inline]
+    fn paste_pending(&self) -> bool {
+        self.pending_paste.is_some()
+    }
+
+    /// Paste the text waiting for confirmation, optionally joining its lines.
+    fn confirm_paste(&mut self, single_line: bool) {
+        let text = match self.pending_paste.take() {
+            Some(text) => text,
+            None => return,
+        };
+        self.close_paste_prompt();
+
+        if single_line {
+            self.write_paste(&paste::single_line(&text));
         } else {
-            // In non-bracketed (ie: normal) mode, terminal applications cannot distinguish
-            // pasted data from keystrokes.
-            // In theory, we should construct the keystrokes needed to produce the data we are
-            // pasting... since that's neither practical nor sensible (and probably an impossible
-            // task to solve in a general way), we'll just replace line breaks (windows and unix
-            // style) with a single carriage return (\r, which is what the Enter key produces).
-            self.write_to_pty(text.replace("\r\n", "\r").replace('\n', "\r").into_bytes());
+            self.write_paste(&text);
         }
     }
 
+    /// Discard the text waiting for confirmation.
+    fn cancel_paste(&mut self) {
+        *self.pending_paste = None;
+        self.close_paste_prompt();
+    }
+
     /// Toggle the vi mode status.
     #[inline]
     fn toggle_vi_mode(&mut self) {
@@ -842,11 +1226,89 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
         // We don't want IME in Vi mode.
         self.window().set_ime_allowed(was_in_vi_mode);
 
+        // Discard partially entered vi mode commands.
+        self.vi_input.reset();
+
         self.terminal.toggle_vi_mode();
 
         *self.dirty = true;
     }
 
+    /// Start recording the input of this window as a macro.
+    fn start_macro_recording(&mut self) {
+        *self.macro_recording = Some(Vec::new());
+        info!("Started macro recording");
+    }
+
+    /// Stop the macro recording and save it as the most recent macro.
+    ///
+    /// When a name is passed, the macro is also saved to the macro's file.
+    fn stop_macro_recording(&mut self, name: Option<&str>) {
+        if let Some(recording) = self.macro_recording.take() {
+            info!("Stopped macro recording of {} bytes", recording.len());
+            self.macros.set_last(recording);
+        }
+
+        if let Some(name) = name {
+            Macros::save(name, self.macros.last());
+        }
+    }
+
+    /// Write the input of a macro to the PTY.
+    ///
+    /// Without a name, the most recently recorded macro is replayed.
+    fn replay_macro(&mut self, name: Option<&str>) {
+        let input = match name {
+            Some(name) => match Macros::load(name) {
+                Some(input) => input,
+                None => return,
+            },
+            None => self.macros.last().to_vec(),
+        };
+
+        if !input.is_empty() {
+            self.on_typing_start();
+            self.clear_selection();
+            self.scroll(Scroll::Bottom);
+            self.write_input(input);
+        }
+    }
+
+    /// Add or remove this window from the windows broadcasting their input.
+    fn toggle_broadcast_input(&mut self) {
+        let event = Event::new(EventType::ToggleBroadcastInput, self.display.window.id());
+        let _ = self.event_proxy.send_event(event);
+    }
+
+    /// Check if input is kept from the PTY.
+    #[inline]
+    fn read_only(&self) -> bool {
+        self.display.window.read_only()
+    }
+
+    /// Stop or resume forwarding input to the PTY.
+    fn toggle_read_only(&mut self) {
+        let event = Event::new(EventType::ToggleReadOnly, self.display.window.id());
+        let _ = self.event_proxy.send_event(event);
+    }
+
+    /// Enter a user-defined binding mode.
+    #[inline]
+    fn enter_binding_mode(&mut self, mode: String) {
+        self.display.window.set_binding_mode(Some(mode));
+    }
+
+    /// Leave the active user-defined binding mode.
+    #[inline]
+    fn leave_binding_mode(&mut self) {
+        self.display.window.set_binding_mode(None);
+    }
+
+    #[inline]
+    fn binding_mode(&self) -> Option<&str> {
+        self.display.window.binding_mode()
+    }
+
     fn message(&self) -> Option<&Message> {
         self.message_buffer.message()
     }
@@ -869,9 +1331,56 @@ impl<'a, N: Notify + 'a, T: EventListener> input::ActionContext<T> for ActionCon
 }
 
 impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
+    /// Write pasted text to the PTY.
+    fn write_paste(&mut self, text: &str) {
+        if self.terminal().mode().contains(TermMode::BRACKETED_PASTE) {
+            self.write_input(&b"\x1b[200~"[..]);
+
+            // Write filtered escape sequences.
+            //
+            // We remove `\x1b` to ensure it's impossible for the pasted text to write the bracketed
+            // paste end escape `\x1b[201~` and `\x03` since some shells incorrectly terminate
+            // bracketed paste on its receival.
+            let filtered = text.replace('\x1b', "").replace('\x03', "");
+            self.write_input(filtered.into_bytes());
+
+            self.write_input(&b"\x1b[201~"[..]);
+        } else {
+            // In non-bracketed (ie: normal) mode, terminal applications cannot distinguish
+            // pasted data from keystrokes.
+            // In theory, we should construct the keystrokes needed to produce the data we are
+            // pasting... since that's neither practical nor sensible (and probably an impossible
+            // task to solve in a general way), we'll just replace line breaks (windows and unix
+            // style) with a single carriage return (\r, which is what the Enter key produces).
+            self.write_input(text.replace("\r\n", "\r").replace('\n', "\r").into_bytes());
+        }
+    }
+
+    /// Ask for confirmation before pasting risky text.
+    fn prompt_paste(&mut self, text: String, risk: &str) {
+        let prompt = format!(
+            "Paste contains {}: [Enter] paste, [S] paste as a single line, [Esc] cancel",
+            risk
+        );
+        let mut message = Message::new(prompt, MessageType::Warning);
+        message.set_target(PASTE_PROMPT_TARGET.into());
+
+        self.message_buffer.remove_target(PASTE_PROMPT_TARGET);
+        self.message_buffer.push_front(message);
+        self.display.pending_update.dirty = true;
+
+        *self.pending_paste = Some(text);
+    }
+
+    /// Remove the paste confirmation prompt from the message bar.
+    fn close_paste_prompt(&mut self) {
+        self.message_buffer.remove_target(PASTE_PROMPT_TARGET);
+        self.display.pending_update.dirty = true;
+    }
+
     fn update_search(&mut self) {
-        let regex = match self.search_state.regex() {
-            Some(regex) => regex,
+        let (regex, flags) = match self.search_state.entry() {
+            Some(entry) => (&entry.regex, entry.flags),
             None => return,
         };
 
@@ -880,13 +1389,21 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
             self.display.window.set_mouse_visible(false);
         }
 
+        let timestamp = regex
+            .strip_prefix(TIMESTAMP_SEARCH_PREFIX)
+            .and_then(|time| timestamp::parse(time, grid::timestamp_now()));
+
         if regex.is_empty() {
             // Stop search if there's nothing to search for.
             self.search_reset_state();
-            self.search_state.dfas = None;
+            self.search_state.set_dfas(None);
+        } else if let Some(timestamp) = timestamp {
+            // Jump to a point in time instead of searching for text.
+            self.search_state.set_dfas(None);
+            self.goto_timestamp(timestamp);
         } else {
             // Create search dfas for the new regex string.
-            self.search_state.dfas = RegexSearch::new(regex).ok();
+            self.search_state.set_dfas(RegexSearch::with_flags(regex, flags).ok());
 
             // Update search highlighting.
             self.goto_match(MAX_SEARCH_WHILE_TYPING);
@@ -932,7 +1449,11 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
         // Jump to the next match.
         let direction = self.search_state.direction;
         let clamped_origin = self.search_state.origin.grid_clamp(self.terminal, Boundary::Grid);
-        match self.terminal.search_next(dfas, clamped_origin, direction, Side::Left, limit) {
+        let regex_match =
+            self.terminal.search_next(dfas, clamped_origin, direction, Side::Left, limit);
+        self.terminal.grid_mut().collapse_history();
+
+        match regex_match {
             Some(regex_match) => {
                 let old_offset = self.terminal.grid().display_offset() as i32;
 
@@ -973,12 +1494,56 @@ impl<'a, N: Notify + 'a, T: EventListener> ActionContext<'a, N, T> {
         *self.dirty = true;
     }
 
+    /// Jump to the oldest line written at or after `timestamp`.
+    fn goto_timestamp(&mut self, timestamp: Timestamp) {
+        let grid = self.terminal.grid();
+
+        // Rewritten lines and full-screen applications can leave timestamps out of order, so pick
+        // the oldest matching line instead of relying on timestamps increasing downwards.
+        let target = (self.terminal.topmost_line().0..=self.terminal.bottommost_line().0)
+            .map(Line)
+            .map(|line| (grid.timestamp(line), line))
+            .filter(|(line_timestamp, _)| *line_timestamp >= timestamp)
+            .min()
+            .map(|(_, line)| line);
+
+        let line = match target {
+            Some(line) => line,
+            None => {
+                self.search_reset_state();
+                return;
+            },
+        };
+
+        let old_offset = self.terminal.grid().display_offset() as i32;
+
+        let point = Point::new(line, Column(0));
+        if self.terminal.mode().contains(TermMode::VI) {
+            self.terminal.vi_goto_point(point);
+        } else {
+            self.terminal.scroll_to_point(point);
+        }
+
+        self.search_state.focused_match = None;
+
+        // Store number of lines the viewport had to be moved.
+        let display_offset = self.terminal.grid().display_offset();
+        self.search_state.display_offset_delta += old_offset - display_offset as i32;
+
+        *self.dirty = true;
+    }
+
     /// Cleanup the search state.
     fn exit_search(&mut self) {
         let vi_mode = self.terminal.mode().contains(TermMode::VI);
         self.window().set_ime_allowed(!vi_mode);
 
         self.display.pending_update.dirty = true;
+
+        // Share the regex with all windows.
+        if let Some(entry) = self.search_state.entry() {
+            self.search_history.push(entry.clone());
+        }
         self.search_state.history_index = None;
 
         // Clear focused match.
@@ -1120,6 +1685,7 @@ pub struct Mouse {
     pub block_hint_launcher: bool,
     pub hint_highlight_dirty: bool,
     pub inside_text_area: bool,
+    pub double_click_match: Option<Match>,
     pub x: usize,
     pub y: usize,
 }
@@ -1137,6 +1703,7 @@ impl Default for Mouse {
             hint_highlight_dirty: Default::default(),
             block_hint_launcher: Default::default(),
             inside_text_area: Default::default(),
+            double_click_match: Default::default(),
             lines_scrolled: Default::default(),
             accumulated_scroll: Default::default(),
             x: Default::default(),
@@ -1201,6 +1768,13 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     self.ctx.display.window.has_frame.store(true, Ordering::Relaxed);
                 },
                 EventType::SearchNext => self.ctx.goto_match(None),
+                EventType::SearchCount(generation, matches) => {
+                    self.ctx.search_state.match_counter.finish(generation, matches);
+                    *self.ctx.dirty = true;
+                },
+                EventType::SearchRecount => self.ctx.search_state.match_counter.refresh(),
+                EventType::RedrawTimestamps => *self.ctx.dirty = true,
+                EventType::KeySequenceTimeout => self.abandon_key_sequence(),
                 EventType::Scroll(scroll) => self.ctx.scroll(scroll),
                 EventType::BlinkCursor => {
                     self.ctx.display.cursor_hidden ^= true;
@@ -1231,7 +1805,23 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                             self.ctx.display.window.set_title(window_config.identity.title.clone());
                         }
                     },
-                    TerminalEvent::Wakeup => *self.ctx.dirty = true,
+                    TerminalEvent::Wakeup => {
+                        // Count search matches again once new output was received.
+                        let window_id = self.ctx.display.window.id();
+                        let timer_id = TimerId::new(Topic::SearchRecount, window_id);
+                        if self.ctx.search_state.dfas.is_some()
+                            && !self.ctx.scheduler.scheduled(timer_id)
+                        {
+                            let event = Event::new(EventType::SearchRecount, window_id);
+                            self.ctx.scheduler.schedule(
+                                event,
+                                SEARCH_RECOUNT_DELAY,
+                                false,
+                                timer_id,
+                            );
+                        }
+                        *self.ctx.dirty = true;
+                    },
                     TerminalEvent::Bell => {
                         // Set window urgency.
                         if self.ctx.terminal.mode().contains(TermMode::URGENCY_HINTS) {
@@ -1249,7 +1839,7 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     },
                     TerminalEvent::ClipboardStore(clipboard_type, content) => {
                         if self.ctx.terminal.is_focused {
-                            self.ctx.clipboard.store(clipboard_type, content);
+                            self.ctx.clipboard.store_from_application(clipboard_type, content);
                         }
                     },
                     TerminalEvent::ClipboardLoad(clipboard_type, format) => {
@@ -1273,9 +1863,15 @@ impl input::Processor<EventProxy, ActionContext<'_, Notifier, EventProxy>> {
                     TerminalEvent::CursorBlinkingChange => self.ctx.update_cursor_blinking(),
                 },
                 #[cfg(unix)]
-                EventType::IpcConfig(_) => (),
-                EventType::ConfigReload(_) | EventType::CreateWindow(_) | EventType::Message(_) => {
-                },
+                EventType::IpcConfig(_)
+                | EventType::IpcExport(_)
+                | EventType::IpcBroadcastInput(_) => (),
+                EventType::ConfigReload(_)
+                | EventType::CreateWindow(_)
+                | EventType::Message(_)
+                | EventType::ExportScrollback
+                | EventType::ToggleBroadcastInput
+                | EventType::ToggleReadOnly => (),
                 #[cfg(feature = "takeover")]
                 EventType::Takeover(event) => {
                     self.ctx.takeover.update(event, &self.ctx.display);
@@ -1520,6 +2116,10 @@ impl Processor {
         let mut clipboard = unsafe { Clipboard::new(event_loop.wayland_display()) };
         #[cfg(any(not(feature = "wayland"), target_os = "macos", windows))]
         let mut clipboard = Clipboard::new();
+        clipboard.set_history_size(self.config.clipboard.history_size);
+        let mut search_history = SearchHistory::new(&self.config.search);
+        let mut macros = Macros::default();
+        let mut broadcast = BroadcastGroup::default();
 
         // Disable all device events, since we don't care about them.
         event_loop.set_device_event_filter(DeviceEventFilter::Always);
@@ -1572,6 +2172,8 @@ impl Processor {
                     // Unschedule pending events.
                     scheduler.unschedule_window(window_context.id());
 
+                    broadcast.remove(window_id);
+
                     // Shutdown if no more terminals are open.
                     if self.windows.is_empty() {
                         // Write ref tests of last window to disk.
@@ -1598,6 +2200,9 @@ impl Processor {
                             event_loop,
                             &proxy,
                             &mut clipboard,
+                            &mut search_history,
+                            &mut macros,
+                            &broadcast,
                             &mut scheduler,
                             WinitEvent::RedrawEventsCleared,
                         );
@@ -1623,6 +2228,8 @@ impl Processor {
                     // Load config and update each terminal.
                     if let Ok(config) = config::reload(&path, &self.cli_options) {
                         self.config = Rc::new(config);
+                        clipboard.set_history_size(self.config.clipboard.history_size);
+                        search_history.update_config(&self.config.search);
 
                         for window_context in self.windows.values_mut() {
                             window_context.update_config(self.config.clone());
@@ -1643,6 +2250,65 @@ impl Processor {
                         window_context.update_ipc_config(self.config.clone(), ipc_config.clone());
                     }
                 },
+                // Process IPC scrollback export.
+                #[cfg(unix)]
+                WinitEvent::UserEvent(Event {
+                    payload: EventType::IpcExport(ipc_export),
+                    window_id,
+                }) => {
+                    // Default to the focused window when no ID was specified.
+                    let window_context = match window_id {
+                        Some(window_id) => self.windows.get(&window_id),
+                        None => self.windows.values().find(|context| context.is_focused()),
+                    };
+
+                    let IpcExport { format, path, .. } = ipc_export;
+                    match window_context {
+                        Some(window_context) => window_context.export_scrollback(format, path),
+                        None => error!("Unable to export scrollback: no matching window"),
+                    }
+                },
+                // Export the scrollback history of a single window.
+                WinitEvent::UserEvent(Event {
+                    payload: EventType::ExportScrollback,
+                    window_id: Some(window_id),
+                }) => {
+                    if let Some(window_context) = self.windows.get(&window_id) {
+                        window_context.export_scrollback(None, None);
+                    }
+                },
+                // Update the group of windows broadcasting their input.
+                #[cfg(unix)]
+                WinitEvent::UserEvent(Event {
+                    payload: EventType::IpcBroadcastInput(ipc_broadcast),
+                    ..
+                }) => {
+                    for (window_id, window_context) in self.windows.iter_mut() {
+                        let enabled = ipc_broadcast.all
+                            || ipc_broadcast.window_ids.contains(&u64::from(*window_id));
+                        window_context.set_broadcast(&mut broadcast, enabled);
+                    }
+                },
+                // Toggle broadcasting for a single window.
+                WinitEvent::UserEvent(Event {
+                    payload: EventType::ToggleBroadcastInput,
+                    window_id: Some(window_id),
+                }) => {
+                    if let Some(window_context) = self.windows.get_mut(&window_id) {
+                        let enabled = !broadcast.contains(window_id);
+                        window_context.set_broadcast(&mut broadcast, enabled);
+                    }
+                },
+                // Toggle read-only mode for a single window.
+                WinitEvent::UserEvent(Event {
+                    payload: EventType::ToggleReadOnly,
+                    window_id: Some(window_id),
+                }) => {
+                    if let Some(window_context) = self.windows.get_mut(&window_id) {
+                        let read_only = !window_context.display.window.read_only();
+                        window_context.set_read_only(&mut broadcast, read_only);
+                    }
+                },
                 // Create a new terminal window.
                 WinitEvent::UserEvent(Event {
                     payload: EventType::CreateWindow(options), ..
@@ -1665,6 +2331,9 @@ impl Processor {
                             event_loop,
                             &proxy,
                             &mut clipboard,
+                            &mut search_history,
+                            &mut macros,
+                            &broadcast,
                             &mut scheduler,
                             event.clone().into(),
                         );
@@ -1679,6 +2348,9 @@ impl Processor {
                             event_loop,
                             &proxy,
                             &mut clipboard,
+                            &mut search_history,
+                            &mut macros,
+                            &broadcast,
                             &mut scheduler,
                             event,
                         );
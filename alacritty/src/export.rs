@@ -0,0 +1,593 @@
+//! Export of the terminal's scrollback history.
+
+use std::cmp::{max, min};
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write as _};
+use std::iter;
+use std::mem;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use log::{error, info};
+
+use alacritty_terminal::ansi::{Color, NamedColor};
+use alacritty_terminal::grid::{Dimensions, GridCell, Row};
+use alacritty_terminal::index::{Column, Line, Point};
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::cell::{Cell, Flags};
+use alacritty_terminal::term::color::{Rgb, COUNT};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::thread;
+
+use crate::config::export::ExportFormat;
+use crate::config::UiConfig;
+use crate::display::content::RenderableCell;
+use crate::display::Display;
+
+/// Cells which do not produce any text.
+const SPACERS: Flags = Flags::WIDE_CHAR_SPACER.union(Flags::LEADING_WIDE_CHAR_SPACER);
+
+/// SGR parameters of a cell without any attributes.
+const SGR_RESET: &str = "0";
+
+/// Maximum number of file names tried for a new export file.
+const MAX_FILE_ATTEMPTS: usize = 100;
+
+/// Number of lines converted while holding the terminal lock.
+const CHUNK_LINES: i32 = 1000;
+
+/// Write the entire grid to a file.
+///
+/// The grid is converted and written in the background, locking the terminal for one chunk of
+/// lines at a time. The format falls back to the export configuration when it's not specified.
+/// Without a path, a new file is created in the configured directory.
+pub fn export_scrollback<T: Send + 'static>(
+    terminal: &Arc<FairMutex<Term<T>>>,
+    config: &UiConfig,
+    display: &Display,
+    format: Option<ExportFormat>,
+    path: Option<PathBuf>,
+) {
+    // Use the colors at the start of the export for the entire document.
+    let mut colors = display.colors;
+    let term_colors = *terminal.lock().colors();
+    for index in 0..COUNT {
+        if let Some(color) = term_colors[index] {
+            colors[index] = color;
+        }
+    }
+
+    let format = format.unwrap_or(config.export.format);
+    let paths = config.export.paths(format);
+    let terminal = terminal.clone();
+
+    // Hints can't be sent to another thread, so only the styling options are passed along.
+    let font = config.font.clone();
+    let config_colors = config.colors.clone();
+    let bright_bold = config.draw_bold_text_with_bright_colors;
+
+    thread::spawn_named("scrollback export", move || {
+        let mut config = UiConfig::default();
+        config.font = font;
+        config.colors = config_colors;
+        config.draw_bold_text_with_bright_colors = bright_bold;
+
+        let file = match path {
+            Some(path) => File::create(&path).map(|file| (file, path)),
+            None => create_new(paths),
+        };
+
+        let result = file.and_then(|(file, path)| {
+            let mut writer = BufWriter::new(file);
+            let result = match format {
+                ExportFormat::Plain => plain(&terminal, &mut writer),
+                ExportFormat::Ansi => ansi(&terminal, &mut writer),
+                ExportFormat::Html => html(&terminal, &config, |index| colors[index], &mut writer),
+            };
+
+            match result.and_then(|_| writer.flush()) {
+                Ok(()) => Ok(path),
+                Err(err) => {
+                    // Don't leave an incomplete export behind.
+                    let _ = fs::remove_file(&path);
+                    Err(err)
+                },
+            }
+        });
+
+        match result {
+            Ok(path) => info!("Exported scrollback history to {:?}", path),
+            Err(err) => error!("Unable to export scrollback history: {}", err),
+        }
+    });
+}
+
+/// Create the first of the `paths` which does not exist yet.
+fn create_new(paths: impl Iterator<Item = PathBuf>) -> io::Result<(File, PathBuf)> {
+    for path in paths.take(MAX_FILE_ATTEMPTS) {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(io::Error::new(err.kind(), format!("{:?}: {}", path, err))),
+        }
+    }
+
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "all export file names are in use"))
+}
+
+/// Convert all lines to text in chunks, starting with the oldest line in history.
+///
+/// The terminal is only locked while `convert` appends the text of a chunk, which is written to
+/// `out` afterwards. Lines scrolled out of the history in the meantime are skipped.
+fn write_chunks<T, W, F>(
+    terminal: &FairMutex<Term<T>>,
+    out: &mut W,
+    mut convert: F,
+) -> io::Result<()>
+where
+    W: io::Write,
+    F: FnMut(&Term<T>, RangeInclusive<Line>, &mut String),
+{
+    let (mut start, mut marker) = {
+        let term = terminal.lock();
+        (term.topmost_line(), term.scroll_marker())
+    };
+
+    let mut text = String::new();
+    loop {
+        let mut term = terminal.lock();
+
+        // Follow the content scrolled up since the last chunk.
+        let delta = term.scrolled_since(marker).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "terminal screen was swapped during the export")
+        })?;
+        start = max(Line(start.0.saturating_sub(delta)), term.topmost_line());
+        marker = term.scroll_marker();
+
+        let bottommost_line = term.bottommost_line();
+        if start > bottommost_line {
+            return Ok(());
+        }
+
+        // End the chunk on a line which isn't wrapped, to keep wrapped lines together.
+        let mut end = min(start + CHUNK_LINES, bottommost_line);
+        while end < bottommost_line && is_wrapped(&term.grid()[end]) {
+            end += 1;
+        }
+
+        convert(&term, start..=end, &mut text);
+
+        // Drop history lines expanded by the conversion.
+        term.grid_mut().collapse_history();
+        drop(term);
+
+        out.write_all(text.as_bytes())?;
+        text.clear();
+
+        start = end + 1;
+    }
+}
+
+/// Write the grid as plain text.
+fn plain<T, W: io::Write>(terminal: &FairMutex<Term<T>>, out: &mut W) -> io::Result<()> {
+    let mut first = true;
+    write_chunks(terminal, out, |term, lines, text| {
+        // Chunks always end on a line which isn't wrapped.
+        if !mem::take(&mut first) {
+            text.push('\n');
+        }
+
+        let start = Point::new(*lines.start(), Column(0));
+        let end = Point::new(*lines.end(), term.last_column());
+        text.push_str(&term.bounds_to_string(start, end));
+    })
+}
+
+/// Write the grid as text with SGR escapes for all cell attributes.
+fn ansi<T, W: io::Write>(terminal: &FairMutex<Term<T>>, out: &mut W) -> io::Result<()> {
+    let mut current = String::from(SGR_RESET);
+
+    // Line breaks are only written once more text follows them.
+    let mut newlines = 0;
+
+    write_chunks(terminal, out, |term, lines, text| {
+        for row in rows(term, lines) {
+            for cell in occupied(row).iter().filter(|cell| !cell.flags.intersects(SPACERS)) {
+                text.extend(iter::repeat('\n').take(mem::take(&mut newlines)));
+
+                let sgr = sgr(cell);
+                if sgr != current {
+                    let _ = write!(text, "\x1b[{}m", sgr);
+                    current = sgr;
+                }
+
+                push_text(text, cell);
+            }
+
+            if !is_wrapped(row) {
+                if current != SGR_RESET {
+                    text.push_str("\x1b[0m");
+                    current = String::from(SGR_RESET);
+                }
+                newlines += 1;
+            }
+        }
+    })?;
+
+    if current != SGR_RESET {
+        out.write_all(b"\x1b[0m")?;
+    }
+
+    Ok(())
+}
+
+/// Write the grid as a self-contained HTML document.
+fn html<T, W, C>(
+    terminal: &FairMutex<Term<T>>,
+    config: &UiConfig,
+    color: C,
+    out: &mut W,
+) -> io::Result<()>
+where
+    W: io::Write,
+    C: Fn(usize) -> Rgb + Copy,
+{
+    let mut html = HtmlText::new(config, color);
+    out.write_all(html.header().as_bytes())?;
+
+    write_chunks(terminal, out, |term, lines, text| {
+        for row in rows(term, lines) {
+            for cell in occupied(row).iter().filter(|cell| !cell.flags.intersects(SPACERS)) {
+                html.push_cell(text, cell);
+            }
+
+            html.end_row(text, is_wrapped(row));
+        }
+    })?;
+
+    out.write_all(html.footer().as_bytes())
+}
+
+/// Builder for a document with text styled using HTML spans inside a `pre` element.
+struct HtmlText<'a, C> {
+    config: &'a UiConfig,
+    color: C,
+    foreground: Rgb,
+
+    /// Style of the currently open span.
+    style: String,
+
+    /// Line breaks which are only written once more text follows them.
+    newlines: usize,
+}
+
+impl<'a, C> HtmlText<'a, C>
+where
+    C: Fn(usize) -> Rgb + Copy,
+{
+    fn new(config: &'a UiConfig, color: C) -> Self {
+        let foreground = color(NamedColor::Foreground as usize);
+        Self { config, color, foreground, style: String::new(), newlines: 0 }
+    }
+
+    /// Start of the document, up to the opening tag of the `pre` element.
+    fn header(&self) -> String {
+        let background = (self.color)(NamedColor::Background as usize);
+        let mut family = String::new();
+        push_escaped(&mut family, &self.config.font.normal().family);
+
+        let mut text = String::new();
+        let _ = write!(
+            text,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Alacritty</title>\n\
+             </head>\n<body style=\"margin: 0; background-color: {1};\">\n<pre style=\"margin: \
+             0; padding: 8px; color: {0}; background-color: {1}; font-family: '{2}', monospace; \
+             white-space: pre-wrap;\">",
+            self.foreground, background, family,
+        );
+
+        text
+    }
+
+    /// Append a cell's text, opening a new span when its style changes.
+    fn push_cell(&mut self, text: &mut String, cell: &Cell) {
+        text.extend(iter::repeat('\n').take(mem::take(&mut self.newlines)));
+
+        let style = style(self.config, self.color, self.foreground, cell);
+        if style != self.style {
+            self.close_span(text);
+            if !style.is_empty() {
+                let _ = write!(text, "<span style=\"{}\">", style);
+            }
+            self.style = style;
+        }
+
+        let mut cell_text = String::new();
+        push_text(&mut cell_text, cell);
+        push_escaped(text, &cell_text);
+    }
+
+    /// Close the span at the end of a row, breaking the line unless it's `wrapped`.
+    fn end_row(&mut self, text: &mut String, wrapped: bool) {
+        self.close_span(text);
+
+        if !wrapped {
+            self.newlines += 1;
+        }
+    }
+
+    /// Close the currently open span.
+    fn close_span(&mut self, text: &mut String) {
+        if !self.style.is_empty() {
+            text.push_str("</span>");
+            self.style.clear();
+        }
+    }
+
+    /// End of the document, dropping trailing line breaks.
+    fn footer(&mut self) -> String {
+        let mut text = String::new();
+        self.close_span(&mut text);
+        text.push_str("</pre>\n</body>\n</html>\n");
+        text
+    }
+}
+
+/// Iterate over the rows of `lines`.
+fn rows<T>(term: &Term<T>, lines: RangeInclusive<Line>) -> impl Iterator<Item = &Row<Cell>> {
+    (lines.start().0..=lines.end().0).map(move |line| &term.grid()[Line(line)])
+}
+
+/// Cells of a row up to the last cell with any visible content.
+fn occupied(row: &Row<Cell>) -> &[Cell] {
+    let cells = &row[..];
+
+    if is_wrapped(row) {
+        return cells;
+    }
+
+    let length = cells.iter().rposition(|cell| !cell.is_empty()).map_or(0, |index| index + 1);
+    &cells[..length]
+}
+
+/// Check if a row continues on the next line.
+fn is_wrapped(row: &Row<Cell>) -> bool {
+    row.last().map_or(false, |cell| cell.flags.contains(Flags::WRAPLINE))
+}
+
+/// Append a cell's characters to `text`.
+fn push_text(text: &mut String, cell: &Cell) {
+    // Tabs are stored with their padding, so they can be replaced with a single space.
+    text.push(if cell.c == '\t' { ' ' } else { cell.c });
+
+    for c in cell.zerowidth().into_iter().flatten() {
+        text.push(*c);
+    }
+}
+
+/// Append `text` with HTML special characters escaped.
+fn push_escaped(buffer: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => buffer.push_str("&amp;"),
+            '<' => buffer.push_str("&lt;"),
+            '>' => buffer.push_str("&gt;"),
+            '"' => buffer.push_str("&quot;"),
+            '\'' => buffer.push_str("&#39;"),
+            c => buffer.push(c),
+        }
+    }
+}
+
+/// SGR parameters reproducing a cell's attributes.
+fn sgr(cell: &Cell) -> String {
+    let mut sgr = String::from(SGR_RESET);
+
+    let attributes = [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "4:2"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::DOTTED_UNDERLINE, "4:4"),
+        (Flags::DASHED_UNDERLINE, "4:5"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ];
+    for (flag, parameter) in attributes {
+        if cell.flags.contains(flag) {
+            sgr.push(';');
+            sgr.push_str(parameter);
+        }
+    }
+
+    if let Some(fg) = color_sgr(cell.fg, 30) {
+        let _ = write!(sgr, ";{}", fg);
+    }
+    if let Some(bg) = color_sgr(cell.bg, 40) {
+        let _ = write!(sgr, ";{}", bg);
+    }
+
+    let underline = cell.underline_color().map(|color| match color {
+        Color::Named(named) => Color::Indexed(named as u8),
+        color => color,
+    });
+    if let Some(underline) = underline.and_then(|color| color_sgr(color, 50)) {
+        let _ = write!(sgr, ";{}", underline);
+    }
+
+    sgr
+}
+
+/// SGR parameters for a color, using `base` as the parameter for the first named color.
+///
+/// Colors without an SGR representation, like the default foreground, return `None`.
+fn color_sgr(color: Color, base: u8) -> Option<String> {
+    match color {
+        Color::Named(named) if (named as usize) < 8 => Some((base + named as u8).to_string()),
+        Color::Named(named) if (named as usize) < 16 => {
+            Some((base + 60 + named as u8 - 8).to_string())
+        },
+        Color::Named(_) => None,
+        Color::Indexed(index) => Some(format!("{};5;{}", base + 8, index)),
+        Color::Spec(Rgb { r, g, b }) => Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+    }
+}
+
+/// Inline CSS reproducing a cell's attributes.
+fn style<C>(config: &UiConfig, color: C, foreground: Rgb, cell: &Cell) -> String
+where
+    C: Fn(usize) -> Rgb + Copy,
+{
+    let mut fg = RenderableCell::compute_fg_rgb(config, color, cell.fg, cell.flags);
+    let mut bg = RenderableCell::compute_bg_rgb(color, cell.bg);
+    let mut has_bg = cell.bg != Color::Named(NamedColor::Background);
+
+    if cell.flags.contains(Flags::INVERSE) {
+        mem::swap(&mut fg, &mut bg);
+        has_bg = true;
+    }
+
+    if cell.flags.contains(Flags::HIDDEN) {
+        fg = bg;
+    }
+
+    let mut style = String::new();
+    if fg != foreground {
+        let _ = write!(style, "color: {};", fg);
+    }
+    if has_bg {
+        let _ = write!(style, "background-color: {};", bg);
+    }
+    if cell.flags.contains(Flags::BOLD) {
+        style.push_str("font-weight: bold;");
+    }
+    if cell.flags.contains(Flags::ITALIC) {
+        style.push_str("font-style: italic;");
+    }
+
+    let underline = cell.flags.intersects(Flags::ALL_UNDERLINES);
+    let strikeout = cell.flags.contains(Flags::STRIKEOUT);
+    match (underline, strikeout) {
+        (true, true) => style.push_str("text-decoration-line: underline line-through;"),
+        (true, false) => style.push_str("text-decoration-line: underline;"),
+        (false, true) => style.push_str("text-decoration-line: line-through;"),
+        (false, false) => (),
+    }
+
+    if underline {
+        let decoration = match cell.flags & Flags::ALL_UNDERLINES {
+            Flags::DOUBLE_UNDERLINE => "double",
+            Flags::UNDERCURL => "wavy",
+            Flags::DOTTED_UNDERLINE => "dotted",
+            Flags::DASHED_UNDERLINE => "dashed",
+            _ => "solid",
+        };
+        let _ = write!(style, "text-decoration-style: {};", decoration);
+
+        if let Some(underline) = cell.underline_color() {
+            let underline = RenderableCell::compute_fg_rgb(config, color, underline, cell.flags);
+            let _ = write!(style, "text-decoration-color: {};", underline);
+        }
+    }
+
+    style
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::process;
+
+    use alacritty_terminal::term::test::mock_term;
+
+    #[test]
+    fn sgr_attributes() {
+        let mut cell = Cell::default();
+        assert_eq!(sgr(&cell), "0");
+
+        cell.flags = Flags::BOLD | Flags::UNDERCURL;
+        cell.fg = Color::Named(NamedColor::Red);
+        cell.bg = Color::Named(NamedColor::BrightBlue);
+        assert_eq!(sgr(&cell), "0;1;4:3;31;104");
+
+        cell.flags = Flags::empty();
+        cell.fg = Color::Indexed(202);
+        cell.bg = Color::Spec(Rgb { r: 1, g: 2, b: 3 });
+        assert_eq!(sgr(&cell), "0;38;5;202;48;2;1;2;3");
+    }
+
+    #[test]
+    fn html_spans() {
+        let config = UiConfig::default();
+        let color = |index| {
+            if index == NamedColor::Red as usize {
+                Rgb { r: 255, g: 0, b: 0 }
+            } else {
+                Rgb::default()
+            }
+        };
+        let mut html = HtmlText::new(&config, color);
+        let mut text = String::new();
+
+        let mut cell = Cell { c: '<', ..Cell::default() };
+        html.push_cell(&mut text, &cell);
+        cell.fg = Color::Named(NamedColor::Red);
+        cell.c = 'a';
+        html.push_cell(&mut text, &cell);
+        html.push_cell(&mut text, &cell);
+        html.end_row(&mut text, false);
+        assert_eq!(text, "&lt;<span style=\"color: #ff0000;\">aa</span>");
+
+        // Line breaks are only written before more text.
+        html.push_cell(&mut text, &cell);
+        html.end_row(&mut text, false);
+        html.end_row(&mut text, false);
+        text.push_str(&html.footer());
+        assert!(text.ends_with(
+            "</span>\n<span style=\"color: #ff0000;\">a</span></pre>\n</body>\n</html>\n"
+        ));
+    }
+
+    #[test]
+    fn chunked_export() {
+        let content = (0..2500).map(|i| format!("{:04}", i)).collect::<Vec<_>>().join("\r\n");
+        let term = FairMutex::new(mock_term(&content));
+
+        let mut text = Vec::new();
+        plain(&term, &mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), content.replace('\r', ""));
+
+        let mut text = Vec::new();
+        ansi(&term, &mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), content.replace('\r', ""));
+    }
+
+    #[test]
+    fn unique_file_names() {
+        let directory = std::env::temp_dir().join(format!("alacritty-export-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let paths = || ["a.txt", "b.txt"].iter().map(|name| directory.join(name));
+
+        let (mut file, path) = create_new(paths()).unwrap();
+        assert_eq!(path, directory.join("a.txt"));
+        file.write_all(b"1").unwrap();
+        assert_eq!(create_new(paths()).unwrap().1, directory.join("b.txt"));
+        assert!(create_new(paths()).is_err());
+        assert_eq!(fs::read_to_string(directory.join("a.txt")).unwrap(), "1");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn html_escaping() {
+        let mut text = String::new();
+        push_escaped(&mut text, "<a href=\"x\">&</a>");
+        assert_eq!(text, "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+    }
+}
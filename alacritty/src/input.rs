@@ -17,7 +17,7 @@ use std::time::{Duration, Instant};
 use winit::dpi::PhysicalPosition;
 use winit::event::{
     ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
-    Touch as TouchEvent, TouchPhase,
+    Touch as TouchEvent, TouchPhase, VirtualKeyCode,
 };
 use winit::event_loop::EventLoopWindowTarget;
 #[cfg(target_os = "macos")]
@@ -28,14 +28,19 @@ use alacritty_terminal::ansi::{ClearMode, Handler};
 use alacritty_terminal::event::EventListener;
 use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::{Boundary, Column, Direction, Point, Side};
-use alacritty_terminal::selection::SelectionType;
-use alacritty_terminal::term::search::Match;
+use alacritty_terminal::selection::{Selection, SelectionType};
+use alacritty_terminal::term::search::{Match, SearchFlags};
 use alacritty_terminal::term::{ClipboardType, Term, TermMode};
-use alacritty_terminal::vi_mode::ViMotion;
+use alacritty_terminal::vi_mode::{InlineSearch, TextObject, ViMotion};
 
 use crate::clipboard::Clipboard;
-use crate::config::{Action, BindingMode, Key, MouseAction, SearchAction, UiConfig, ViAction};
-use crate::display::hint::HintMatch;
+use crate::config::{
+    Action, BindingMode, KeyPress, ModsWrapper, MouseAction, MouseBinding, SearchAction, UiConfig,
+    ViAction,
+};
+use crate::display::filter::FilterView;
+use crate::display::hint::{self, HintMatch};
+use crate::display::key_sequence::{KeySequence, PendingKey};
 use crate::display::window::Window;
 use crate::display::{Display, SizeInfo};
 use crate::event::{
@@ -71,8 +76,108 @@ pub struct Processor<T: EventListener, A: ActionContext<T>> {
     _phantom: PhantomData<T>,
 }
 
+/// State of partially entered vi mode commands.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ViInput {
+    /// Number of times the next motion is repeated.
+    pub count: Option<usize>,
+
+    /// Operator waiting for a motion, with its count.
+    pub operator: Option<(ViOperator, usize)>,
+
+    /// Action waiting for a character argument.
+    pub pending: Option<ViPending>,
+
+    /// Last inline search, repeated by `;` and `,`.
+    pub inline_search: Option<InlineSearch>,
+
+    /// Register used by the next yank or paste.
+    pub register: Option<char>,
+}
+
+impl ViInput {
+    /// Cancel the partially entered command.
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.operator = None;
+        self.pending = None;
+        self.register = None;
+    }
+
+    /// Check if the next character is used by a partially entered command.
+    fn captures(&self, key: Option<VirtualKeyCode>) -> bool {
+        self.pending.is_some()
+            || self.operator.is_some()
+            || (self.count.is_some() && key == Some(VirtualKeyCode::Key0))
+    }
+}
+
+/// Vi mode operator waiting for a motion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViOperator {
+    /// Copy the text covered by the motion.
+    Yank,
+}
+
+/// Vi mode action waiting for a character argument.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViPending {
+    /// Set the mark named by the next character.
+    SetMark,
+    /// Jump to the mark named by the next character.
+    GotoMark,
+    /// Jump to the line of the mark named by the next character.
+    GotoMarkLine,
+    /// Search for the next character within the current line.
+    InlineSearch { direction: Direction, stop_short: bool },
+    /// Complete a `g` prefixed command.
+    GPrefix,
+    /// Apply the operator to the text object named by the next character.
+    TextObject { inner: bool },
+    /// Select the register named by the next character.
+    Register,
+}
+
+/// Text covered by a vi mode motion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ViMotionKind {
+    /// Text up to, but not including, the target.
+    Exclusive,
+    /// Text up to and including the target.
+    Inclusive,
+    /// All lines between the origin and the target.
+    Linewise,
+}
+
+impl From<ViMotion> for ViMotionKind {
+    fn from(motion: ViMotion) -> Self {
+        match motion {
+            ViMotion::Up
+            | ViMotion::Down
+            | ViMotion::High
+            | ViMotion::Middle
+            | ViMotion::Low
+            | ViMotion::Top
+            | ViMotion::Bottom => Self::Linewise,
+            ViMotion::SemanticRightEnd
+            | ViMotion::WordRightEnd
+            | ViMotion::Last
+            | ViMotion::Bracket => Self::Inclusive,
+            _ => Self::Exclusive,
+        }
+    }
+}
+
 pub trait ActionContext<T: EventListener> {
     fn write_to_pty<B: Into<Cow<'static, [u8]>>>(&self, _data: B) {}
+    fn write_input<B: Into<Cow<'static, [u8]>>>(&mut self, data: B) {
+        if !self.read_only() {
+            self.send_input(data);
+        }
+    }
+    fn send_input<B: Into<Cow<'static, [u8]>>>(&mut self, data: B) {
+        self.write_to_pty(data);
+    }
     fn mark_dirty(&mut self) {}
     fn size_info(&self) -> SizeInfo;
     fn copy_selection(&mut self, _ty: ClipboardType) {}
@@ -80,16 +185,26 @@ pub trait ActionContext<T: EventListener> {
     fn toggle_selection(&mut self, _ty: SelectionType, _point: Point, _side: Side) {}
     fn update_selection(&mut self, _point: Point, _side: Side) {}
     fn clear_selection(&mut self) {}
+    fn expand_selection_semantic(&mut self) {}
+    fn shrink_selection(&mut self) {}
+    fn export_scrollback(&mut self) {}
+    fn toggle_timestamps(&mut self) {}
+    fn toggle_timestamp_format(&mut self) {}
+    fn show_marks(&mut self) {}
     fn selection_is_empty(&self) -> bool;
     fn mouse_mut(&mut self) -> &mut Mouse;
     fn mouse(&self) -> &Mouse;
     fn touch_purpose(&mut self) -> &mut TouchPurpose;
+    fn vi_input(&mut self) -> &mut ViInput;
     fn received_count(&mut self) -> &mut usize;
     fn suppress_chars(&mut self) -> &mut bool;
     fn modifiers(&mut self) -> &mut ModifiersState;
     fn scroll(&mut self, _scroll: Scroll) {}
     fn window(&mut self) -> &mut Window;
     fn display(&mut self) -> &mut Display;
+    fn key_sequence(&mut self) -> &mut KeySequence;
+    fn schedule_key_sequence_timeout(&mut self) {}
+    fn cancel_key_sequence_timeout(&mut self) {}
     fn terminal(&self) -> &Term<T>;
     fn terminal_mut(&mut self) -> &mut Term<T>;
     fn spawn_new_instance(&mut self) {}
@@ -110,16 +225,43 @@ pub trait ActionContext<T: EventListener> {
     fn search_pop_word(&mut self) {}
     fn search_history_previous(&mut self) {}
     fn search_history_next(&mut self) {}
+    fn toggle_search_flag(&mut self, _flag: SearchFlags) {}
+    fn start_filter(&mut self) {}
+    fn confirm_filter(&mut self) {}
+    fn cancel_filter(&mut self) {}
+    fn change_filter_context(&mut self, _delta: isize) {}
+    fn filter_view(&mut self) -> Option<&mut FilterView> {
+        None
+    }
+    fn search_word(&mut self, _direction: Direction) {}
     fn search_next(&mut self, origin: Point, direction: Direction, side: Side) -> Option<Match>;
     fn advance_search_origin(&mut self, _direction: Direction) {}
     fn search_direction(&self) -> Direction;
     fn search_active(&self) -> bool;
     fn on_typing_start(&mut self) {}
     fn toggle_vi_mode(&mut self) {}
+    fn enter_binding_mode(&mut self, _mode: String) {}
+    fn start_macro_recording(&mut self) {}
+    fn toggle_broadcast_input(&mut self) {}
+    fn read_only(&self) -> bool {
+        false
+    }
+    fn toggle_read_only(&mut self) {}
+    fn stop_macro_recording(&mut self, _name: Option<&str>) {}
+    fn replay_macro(&mut self, _name: Option<&str>) {}
+    fn leave_binding_mode(&mut self) {}
+    fn binding_mode(&self) -> Option<&str> {
+        None
+    }
     fn hint_input(&mut self, _character: char) {}
     fn trigger_hint(&mut self, _hint: &HintMatch) {}
     fn expand_selection(&mut self) {}
     fn paste(&mut self, _text: &str) {}
+    fn paste_pending(&self) -> bool {
+        false
+    }
+    fn confirm_paste(&mut self, _single_line: bool) {}
+    fn cancel_paste(&mut self) {}
     fn spawn_daemon<I, S>(&self, _program: &str, _args: I)
     where
         I: IntoIterator<Item = S> + Debug + Copy,
@@ -151,15 +293,17 @@ impl<T: EventListener> Execute<T> for Action {
     #[inline]
     fn execute<A: ActionContext<T>>(&self, ctx: &mut A) {
         match self {
+            Action::Esc(_) if ctx.read_only() => (),
             Action::Esc(s) => {
                 ctx.on_typing_start();
                 ctx.clear_selection();
                 ctx.scroll(Scroll::Bottom);
-                ctx.write_to_pty(s.clone().into_bytes())
+                ctx.write_input(s.clone().into_bytes())
             },
             Action::Command(program) => ctx.spawn_daemon(program.program(), program.args()),
             Action::Hint(hint) => {
-                ctx.display().hint_state.start(hint.clone());
+                let scrollback_lines = ctx.config().hints.scrollback_lines;
+                ctx.display().hint_state.start(hint.clone(), scrollback_lines);
                 ctx.mark_dirty();
             },
             Action::ToggleViMode => {
@@ -168,7 +312,8 @@ impl<T: EventListener> Execute<T> for Action {
             },
             Action::ViMotion(motion) => {
                 ctx.on_typing_start();
-                ctx.terminal_mut().vi_motion(*motion);
+                let count = ctx.vi_input().count.take();
+                vi_motion(ctx.terminal_mut(), *motion, count);
                 ctx.mark_dirty();
             },
             Action::Vi(ViAction::ToggleNormalSelection) => {
@@ -203,7 +348,7 @@ impl<T: EventListener> Execute<T> for Action {
                 };
 
                 if let Some(regex_match) = ctx.search_next(origin, direction, Side::Left) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.start());
+                    ctx.terminal_mut().vi_jump(*regex_match.start());
                     ctx.mark_dirty();
                 }
             },
@@ -219,7 +364,7 @@ impl<T: EventListener> Execute<T> for Action {
                 };
 
                 if let Some(regex_match) = ctx.search_next(origin, direction, Side::Left) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.start());
+                    ctx.terminal_mut().vi_jump(*regex_match.start());
                     ctx.mark_dirty();
                 }
             },
@@ -228,7 +373,7 @@ impl<T: EventListener> Execute<T> for Action {
                 let origin = terminal.vi_mode_cursor.point.sub(terminal, Boundary::None, 1);
 
                 if let Some(regex_match) = ctx.search_next(origin, Direction::Left, Side::Left) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.start());
+                    ctx.terminal_mut().vi_jump(*regex_match.start());
                     ctx.mark_dirty();
                 }
             },
@@ -237,10 +382,102 @@ impl<T: EventListener> Execute<T> for Action {
                 let origin = terminal.vi_mode_cursor.point.add(terminal, Boundary::None, 1);
 
                 if let Some(regex_match) = ctx.search_next(origin, Direction::Right, Side::Right) {
-                    ctx.terminal_mut().vi_goto_point(*regex_match.end());
+                    ctx.terminal_mut().vi_jump(*regex_match.end());
                     ctx.mark_dirty();
                 }
             },
+            Action::Vi(ViAction::SearchWordForward) => {
+                ctx.on_typing_start();
+                ctx.search_word(Direction::Right);
+            },
+            Action::Vi(ViAction::SearchWordBackward) => {
+                ctx.on_typing_start();
+                ctx.search_word(Direction::Left);
+            },
+            Action::Vi(ViAction::SetMark) => ctx.vi_input().pending = Some(ViPending::SetMark),
+            Action::Vi(ViAction::GotoMark) => ctx.vi_input().pending = Some(ViPending::GotoMark),
+            Action::Vi(ViAction::GotoMarkLine) => {
+                ctx.vi_input().pending = Some(ViPending::GotoMarkLine);
+            },
+            Action::Vi(ViAction::Yank) => {
+                if ctx.selection_is_empty() {
+                    let count = ctx.vi_input().count.take().unwrap_or(1);
+                    ctx.vi_input().operator = Some((ViOperator::Yank, count));
+                } else {
+                    match ctx.vi_input().register.take() {
+                        Some(register) => {
+                            let text = ctx.terminal().selection_to_string().unwrap_or_default();
+                            ctx.clipboard_mut().store_register(register, text);
+                        },
+                        None => ctx.copy_selection(ClipboardType::Clipboard),
+                    }
+                    ctx.clear_selection();
+                }
+            },
+            Action::Vi(ViAction::SelectRegister) => {
+                ctx.vi_input().pending = Some(ViPending::Register);
+            },
+            Action::Vi(ViAction::Paste) => {
+                let text = match ctx.vi_input().register.take() {
+                    Some(register) => ctx.clipboard_mut().register(register).map(String::from),
+                    None => Some(ctx.clipboard_mut().load(ClipboardType::Clipboard)),
+                };
+                if let Some(text) = text {
+                    ctx.paste(&text);
+                }
+            },
+            Action::Vi(ViAction::GPrefix) => ctx.vi_input().pending = Some(ViPending::GPrefix),
+            Action::Vi(ViAction::InlineSearchForward) => {
+                let pending =
+                    ViPending::InlineSearch { direction: Direction::Right, stop_short: false };
+                ctx.vi_input().pending = Some(pending);
+            },
+            Action::Vi(ViAction::InlineSearchBackward) => {
+                let pending =
+                    ViPending::InlineSearch { direction: Direction::Left, stop_short: false };
+                ctx.vi_input().pending = Some(pending);
+            },
+            Action::Vi(ViAction::InlineSearchForwardShort) => {
+                let pending =
+                    ViPending::InlineSearch { direction: Direction::Right, stop_short: true };
+                ctx.vi_input().pending = Some(pending);
+            },
+            Action::Vi(ViAction::InlineSearchBackwardShort) => {
+                let pending =
+                    ViPending::InlineSearch { direction: Direction::Left, stop_short: true };
+                ctx.vi_input().pending = Some(pending);
+            },
+            Action::Vi(ViAction::InlineSearchNext) => {
+                let vi_input = ctx.vi_input();
+                let count = vi_input.count.take().unwrap_or(1);
+                if let Some(search) = vi_input.inline_search {
+                    let term = ctx.terminal_mut();
+                    if let Some(point) = inline_search_target(term, search, count, true) {
+                        term.vi_goto_point(point);
+                        ctx.mark_dirty();
+                    }
+                }
+            },
+            Action::Vi(ViAction::InlineSearchPrevious) => {
+                let vi_input = ctx.vi_input();
+                let count = vi_input.count.take().unwrap_or(1);
+                if let Some(search) = vi_input.inline_search {
+                    let term = ctx.terminal_mut();
+                    if let Some(point) = inline_search_target(term, search.reversed(), count, true)
+                    {
+                        term.vi_goto_point(point);
+                        ctx.mark_dirty();
+                    }
+                }
+            },
+            Action::Vi(ViAction::JumpOlder) => {
+                ctx.terminal_mut().vi_jump_older();
+                ctx.mark_dirty();
+            },
+            Action::Vi(ViAction::JumpNewer) => {
+                ctx.terminal_mut().vi_jump_newer();
+                ctx.mark_dirty();
+            },
             Action::Vi(ViAction::CenterAroundViCursor) => {
                 let term = ctx.terminal();
                 let display_offset = term.grid().display_offset() as i32;
@@ -267,6 +504,16 @@ impl<T: EventListener> Execute<T> for Action {
             Action::Search(SearchAction::SearchDeleteWord) => ctx.search_pop_word(),
             Action::Search(SearchAction::SearchHistoryPrevious) => ctx.search_history_previous(),
             Action::Search(SearchAction::SearchHistoryNext) => ctx.search_history_next(),
+            Action::Search(SearchAction::SearchToggleSmartCase) => {
+                ctx.toggle_search_flag(SearchFlags::SMART_CASE);
+            },
+            Action::Search(SearchAction::SearchToggleLiteral) => {
+                ctx.toggle_search_flag(SearchFlags::LITERAL);
+            },
+            Action::Search(SearchAction::SearchToggleWholeWord) => {
+                ctx.toggle_search_flag(SearchFlags::WHOLE_WORD);
+            },
+            Action::Search(SearchAction::SearchFilter) => ctx.start_filter(),
             Action::Mouse(MouseAction::ExpandSelection) => ctx.expand_selection(),
             Action::SearchForward => ctx.start_search(Direction::Right),
             Action::SearchBackward => ctx.start_search(Direction::Left),
@@ -274,6 +521,8 @@ impl<T: EventListener> Execute<T> for Action {
             #[cfg(not(any(target_os = "macos", windows)))]
             Action::CopySelection => ctx.copy_selection(ClipboardType::Selection),
             Action::ClearSelection => ctx.clear_selection(),
+            Action::ExpandSelectionSemantic => ctx.expand_selection_semantic(),
+            Action::ShrinkSelection => ctx.shrink_selection(),
             Action::Paste => {
                 let text = ctx.clipboard_mut().load(ClipboardType::Clipboard);
                 ctx.paste(&text);
@@ -353,9 +602,33 @@ impl<T: EventListener> Execute<T> for Action {
                 ctx.mark_dirty();
             },
             Action::ClearHistory => ctx.terminal_mut().clear_screen(ClearMode::Saved),
+            Action::ExportScrollback => ctx.export_scrollback(),
+            Action::ToggleTimestamps => ctx.toggle_timestamps(),
+            Action::ToggleTimestampFormat => ctx.toggle_timestamp_format(),
+            Action::ClipboardHistory => {
+                let entries = ctx.clipboard_mut().entries();
+                ctx.display().clipboard_picker.start(entries);
+                ctx.mark_dirty();
+            },
             Action::ClearLogNotice => ctx.pop_message(),
             Action::SpawnNewInstance => ctx.spawn_new_instance(),
             Action::CreateNewWindow => ctx.create_new_window(),
+            Action::EnterMode(mode) => ctx.enter_binding_mode(mode.clone()),
+            Action::List(actions) => {
+                for action in actions {
+                    action.execute(ctx);
+
+                    if action.changes_focus_or_mode() {
+                        break;
+                    }
+                }
+            },
+            Action::LeaveMode => ctx.leave_binding_mode(),
+            Action::StartMacroRecording => ctx.start_macro_recording(),
+            Action::ToggleBroadcastInput => ctx.toggle_broadcast_input(),
+            Action::ToggleReadOnly => ctx.toggle_read_only(),
+            Action::StopMacroRecording(name) => ctx.stop_macro_recording(name.as_deref()),
+            Action::ReplayMacro(name) => ctx.replay_macro(name.as_deref()),
             Action::ReceiveChar | Action::None => (),
         }
     }
@@ -415,7 +688,12 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
 
         if (lmb_pressed || rmb_pressed) && (self.ctx.modifiers().shift() || !self.ctx.mouse_mode())
         {
-            self.ctx.update_selection(point, cell_side);
+            match self.ctx.mouse().double_click_match.clone() {
+                Some(regex_match) if lmb_pressed => {
+                    self.drag_double_click_match(regex_match, point, cell_side);
+                },
+                _ => self.ctx.update_selection(point, cell_side),
+            }
         } else if cell_changed
             && self.ctx.terminal().mode().intersects(TermMode::MOUSE_MOTION | TermMode::MOUSE_DRAG)
         {
@@ -431,6 +709,23 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
         }
     }
 
+    /// Select a double click regex match, extending the selection to the mouse outside of it.
+    fn drag_double_click_match(&mut self, regex_match: Match, point: Point, side: Side) {
+        let (start, end) = (*regex_match.start(), *regex_match.end());
+        if regex_match.contains(&point) {
+            self.ctx.start_selection(SelectionType::Simple, start, Side::Left);
+            self.ctx.update_selection(end, Side::Right);
+        } else if point < start {
+            self.ctx.start_selection(SelectionType::Simple, end, Side::Right);
+            self.ctx.update_selection(point, side);
+        } else {
+            self.ctx.start_selection(SelectionType::Simple, start, Side::Left);
+            self.ctx.update_selection(point, side);
+        }
+
+        self.ctx.mouse_mut().double_click_match = Some(regex_match);
+    }
+
     /// Check which side of a cell an X coordinate lies on.
     fn cell_side(&self, x: usize) -> Side {
         let size_info = self.ctx.size_info();
@@ -439,9 +734,9 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             x.saturating_sub(size_info.padding_x() as usize) % size_info.cell_width() as usize;
         let half_cell_width = (size_info.cell_width() / 2.0) as usize;
 
-        let additional_padding =
-            (size_info.width() - size_info.padding_x() * 2.) % size_info.cell_width();
-        let end_of_grid = size_info.width() - size_info.padding_x() - additional_padding;
+        let grid_width = size_info.width() - size_info.padding_x() - size_info.padding_right();
+        let additional_padding = grid_width % size_info.cell_width();
+        let end_of_grid = size_info.width() - size_info.padding_right() - additional_padding;
 
         if cell_x > half_cell_width
             // Edge case when mouse leaves the window.
@@ -454,6 +749,11 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 
     fn mouse_report(&mut self, button: u8, state: ElementState) {
+        // Mouse reports are input for the application too.
+        if self.ctx.read_only() {
+            return;
+        }
+
         let display_offset = self.ctx.terminal().grid().display_offset();
         let point = self.ctx.mouse().point(&self.ctx.size_info(), display_offset);
 
@@ -584,6 +884,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             ClickState::Click => {
                 // Don't launch URLs if this click cleared the selection.
                 self.ctx.mouse_mut().block_hint_launcher = !self.ctx.selection_is_empty();
+                self.ctx.mouse_mut().double_click_match = None;
 
                 self.ctx.clear_selection();
 
@@ -596,10 +897,25 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             },
             ClickState::DoubleClick => {
                 self.ctx.mouse_mut().block_hint_launcher = true;
-                self.ctx.start_selection(SelectionType::Semantic, point, side);
+
+                // Select the first double click regex match at the clicked point.
+                let term = self.ctx.terminal();
+                let regex_match =
+                    self.ctx.config().mouse.double_click.regex.iter().find_map(|regex| {
+                        regex.with_compiled(|regex| hint::regex_match_at(term, point, regex, false))
+                    });
+
+                match regex_match {
+                    Some(regex_match) => self.drag_double_click_match(regex_match, point, side),
+                    None => {
+                        self.ctx.mouse_mut().double_click_match = None;
+                        self.ctx.start_selection(SelectionType::Semantic, point, side);
+                    },
+                }
             },
             ClickState::TripleClick => {
                 self.ctx.mouse_mut().block_hint_launcher = true;
+                self.ctx.mouse_mut().double_click_match = None;
                 self.ctx.start_selection(SelectionType::Lines, point, side);
             },
             ClickState::None => (),
@@ -703,6 +1019,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             .mode()
             .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
             && !self.ctx.modifiers().shift()
+            && !self.ctx.read_only()
         {
             let multiplier = f64::from(self.ctx.config().terminal_config.scrolling.multiplier);
 
@@ -747,7 +1064,7 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 
     pub fn on_focus_change(&mut self, is_focused: bool) {
-        if self.ctx.terminal().mode().contains(TermMode::FOCUS_IN_OUT) {
+        if self.ctx.terminal().mode().contains(TermMode::FOCUS_IN_OUT) && !self.ctx.read_only() {
             let chr = if is_focused { "I" } else { "O" };
 
             let msg = format!("\x1b[{}", chr);
@@ -921,12 +1238,80 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
+        // All key bindings are disabled while a paste waits for confirmation.
+        if self.ctx.paste_pending() {
+            *self.ctx.suppress_chars() = false;
+            return;
+        }
+
         // All key bindings are disabled while a hint is being selected.
         if self.ctx.display().hint_state.active() {
+            // Scroll the list of labels above the viewport with the arrow keys.
+            if input.state == ElementState::Pressed {
+                let hint_state = &mut self.ctx.display().hint_state;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Up) => hint_state.scroll_list(1),
+                    Some(VirtualKeyCode::Down) => hint_state.scroll_list(-1),
+                    _ => (),
+                }
+                self.ctx.mark_dirty();
+            }
+            *self.ctx.suppress_chars() = false;
+            return;
+        }
+
+        // Navigate the clipboard history picker with the arrow keys.
+        if self.ctx.display().clipboard_picker.active() {
+            if input.state == ElementState::Pressed {
+                let picker = &mut self.ctx.display().clipboard_picker;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Up) => picker.move_selection(-1),
+                    Some(VirtualKeyCode::Down) => picker.move_selection(1),
+                    _ => (),
+                }
+                self.ctx.mark_dirty();
+            }
+            *self.ctx.suppress_chars() = false;
+            return;
+        }
+
+        // Navigate the filter view with the arrow and page keys.
+        if let Some(filter) = self.ctx.filter_view() {
+            if input.state == ElementState::Pressed {
+                let page = filter.page_lines() as isize;
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Up) => filter.move_selection(-1),
+                    Some(VirtualKeyCode::Down) => filter.move_selection(1),
+                    Some(VirtualKeyCode::PageUp) => filter.move_selection(-page),
+                    Some(VirtualKeyCode::PageDown) => filter.move_selection(page),
+                    Some(VirtualKeyCode::Home) => filter.move_selection(isize::MIN),
+                    Some(VirtualKeyCode::End) => filter.move_selection(isize::MAX),
+                    _ => (),
+                }
+                self.ctx.mark_dirty();
+            }
             *self.ctx.suppress_chars() = false;
             return;
         }
 
+        // Pass the next character to partially entered vi mode commands.
+        let vi_mode = self.ctx.terminal().mode().contains(TermMode::VI);
+        if vi_mode
+            && !self.ctx.search_active()
+            && self.ctx.vi_input().captures(input.virtual_keycode)
+        {
+            if input.state == ElementState::Pressed {
+                *self.ctx.suppress_chars() = false;
+
+                // Cancel the command on keys which don't produce a character.
+                let non_character = VirtualKeyCode::Escape..=VirtualKeyCode::Down;
+                if input.virtual_keycode.map_or(false, |key| non_character.contains(&key)) {
+                    self.ctx.vi_input().reset();
+                }
+            }
+            return;
+        }
+
         // Reset search delay when the user is still typing.
         if self.ctx.search_active() {
             let timer_id = TimerId::new(Topic::DelayedSearch, self.ctx.window().id());
@@ -940,6 +1325,14 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             ElementState::Pressed => {
                 *self.ctx.received_count() = 0;
                 self.process_key_bindings(input);
+
+                // Reset the vi mode count and register once a binding has used them.
+                let binding_triggered = *self.ctx.suppress_chars();
+                let vi_input = self.ctx.vi_input();
+                if binding_triggered && vi_input.pending.is_none() && vi_input.operator.is_none() {
+                    vi_input.count = None;
+                    vi_input.register = None;
+                }
             },
             ElementState::Released => *self.ctx.suppress_chars() = false,
         }
@@ -973,22 +1366,54 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             return;
         }
 
+        // Hold back the characters of keys in a pending key sequence.
+        if self.ctx.key_sequence().active() {
+            self.ctx.key_sequence().push_char(c);
+            return;
+        }
+
+        // Answer the paste confirmation prompt.
+        if self.ctx.paste_pending() && !suppress_chars {
+            self.paste_prompt_input(c);
+            return;
+        }
+
         // Handle hint selection over anything else.
         if self.ctx.display().hint_state.active() && !suppress_chars {
             self.ctx.hint_input(c);
             return;
         }
 
-        // Pass keys to search and ignore them during `suppress_chars`.
+        // Pass keys to the clipboard history picker.
+        if self.ctx.display().clipboard_picker.active() && !suppress_chars {
+            self.clipboard_picker_input(c);
+            return;
+        }
+
+        // Pass keys to the filter view.
+        if self.ctx.filter_view().is_some() && !suppress_chars {
+            self.filter_view_input(c);
+            return;
+        }
+
+        // Pass keys to search and vi mode, and ignore them during `suppress_chars`.
         let search_active = self.ctx.search_active();
-        if suppress_chars || search_active || self.ctx.terminal().mode().contains(TermMode::VI) {
+        let vi_mode = self.ctx.terminal().mode().contains(TermMode::VI);
+        if suppress_chars || search_active || vi_mode {
             if search_active && !suppress_chars {
                 self.ctx.search_input(c);
+            } else if vi_mode && !suppress_chars {
+                self.vi_char_input(c);
             }
 
             return;
         }
 
+        // Keys aren't sent to the PTY in read-only mode or without a binding in a user mode.
+        if self.ctx.binding_mode().is_some() || self.ctx.read_only() {
+            return;
+        }
+
         self.ctx.on_typing_start();
 
         if self.ctx.terminal().grid().display_offset() != 0 {
@@ -1016,63 +1441,504 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
             bytes.insert(0, b'\x1b');
         }
 
-        self.ctx.write_to_pty(bytes);
+        self.ctx.write_input(bytes);
 
         *self.ctx.received_count() += 1;
     }
 
+    /// Handle input of the paste confirmation prompt.
+    fn paste_prompt_input(&mut self, c: char) {
+        match c {
+            '\r' | 'y' | 'Y' => self.ctx.confirm_paste(false),
+            's' | 'S' => self.ctx.confirm_paste(true),
+            '\x1b' | 'n' | 'N' => self.ctx.cancel_paste(),
+            _ => (),
+        }
+    }
+
+    /// Handle input of the clipboard history picker.
+    fn clipboard_picker_input(&mut self, c: char) {
+        let picker = &mut self.ctx.display().clipboard_picker;
+        let paste = match c {
+            'j' => {
+                picker.move_selection(1);
+                false
+            },
+            'k' => {
+                picker.move_selection(-1);
+                false
+            },
+            '1'..='9' => picker.select_history(c as usize - '1' as usize),
+            '\r' => true,
+            '\x1b' => {
+                picker.stop();
+                false
+            },
+            _ => false,
+        };
+
+        if paste {
+            if let Some(text) = picker.stop() {
+                self.ctx.paste(&text);
+            }
+        }
+
+        self.ctx.mark_dirty();
+    }
+
+    /// Handle input of the filter view.
+    fn filter_view_input(&mut self, c: char) {
+        let delta = match c {
+            'j' => 1,
+            'k' => -1,
+            'g' => isize::MIN,
+            'G' => isize::MAX,
+            '+' => return self.ctx.change_filter_context(1),
+            '-' => return self.ctx.change_filter_context(-1),
+            '\r' => return self.ctx.confirm_filter(),
+            '\x1b' => return self.ctx.cancel_filter(),
+            _ => return,
+        };
+
+        if let Some(filter) = self.ctx.filter_view() {
+            filter.move_selection(delta);
+        }
+        self.ctx.mark_dirty();
+    }
+
+    /// Handle characters of partially entered vi mode commands.
+    fn vi_char_input(&mut self, c: char) {
+        if let Some(pending) = self.ctx.vi_input().pending.take() {
+            self.vi_pending_input(pending, c);
+            return;
+        }
+
+        let vi_input = self.ctx.vi_input();
+        match c.to_digit(10) {
+            Some(digit) if digit != 0 || vi_input.count.is_some() => {
+                let count = vi_input.count.unwrap_or(0);
+                vi_input.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+            },
+            _ if vi_input.operator.is_some() => self.vi_operator_input(c),
+            _ => vi_input.reset(),
+        }
+    }
+
+    /// Complete a pending vi mode action using the character `c`.
+    fn vi_pending_input(&mut self, pending: ViPending, c: char) {
+        match pending {
+            ViPending::SetMark => {
+                let term = self.ctx.terminal_mut();
+                let point = term.vi_mode_cursor.point;
+                if term.vi_marks.set(c, point) {
+                    self.ctx.show_marks();
+                }
+            },
+            ViPending::GotoMark => self.ctx.terminal_mut().vi_goto_mark(c, false),
+            ViPending::GotoMarkLine => self.ctx.terminal_mut().vi_goto_mark(c, true),
+            ViPending::InlineSearch { direction, stop_short } => {
+                let search = InlineSearch { character: c, direction, stop_short };
+                self.ctx.vi_input().inline_search = Some(search);
+                self.vi_inline_search(search, false);
+            },
+            ViPending::GPrefix if c == 'g' => self.vi_char_motion(ViMotion::Top),
+            ViPending::TextObject { inner } => match TextObject::from_char(c) {
+                Some(object) => self.vi_text_object(object, inner),
+                None => self.ctx.vi_input().reset(),
+            },
+            ViPending::GPrefix => self.ctx.vi_input().reset(),
+            ViPending::Register if c.is_ascii_alphabetic() => {
+                self.ctx.vi_input().register = Some(c);
+            },
+            ViPending::Register => self.ctx.vi_input().reset(),
+        }
+
+        self.ctx.mark_dirty();
+    }
+
+    /// Pass the character `c` to the pending vi mode operator.
+    fn vi_operator_input(&mut self, c: char) {
+        let motion = match c {
+            'h' => ViMotion::Left,
+            'j' => ViMotion::Down,
+            'k' => ViMotion::Up,
+            'l' => ViMotion::Right,
+            'w' => ViMotion::SemanticRight,
+            'b' => ViMotion::SemanticLeft,
+            'e' => ViMotion::SemanticRightEnd,
+            'W' => ViMotion::WordRight,
+            'B' => ViMotion::WordLeft,
+            'E' => ViMotion::WordRightEnd,
+            '0' => ViMotion::First,
+            '$' => ViMotion::Last,
+            '^' => ViMotion::FirstOccupied,
+            '{' => ViMotion::ParagraphUp,
+            '}' => ViMotion::ParagraphDown,
+            '%' => ViMotion::Bracket,
+            'H' => ViMotion::High,
+            'M' => ViMotion::Middle,
+            'L' => ViMotion::Low,
+            'G' => ViMotion::Bottom,
+            'y' => {
+                // Repeat the operator's count for the number of lines, like `3yy`.
+                let vi_input = self.ctx.vi_input();
+                let count = vi_input.count.take().unwrap_or(1);
+                let operator = vi_input.operator.take();
+
+                let term = self.ctx.terminal();
+                let start = term.vi_mode_cursor.point;
+                let lines = operator.map_or(1, |(_, op_count)| op_count).saturating_mul(count);
+                let lines = min(lines - 1, term.total_lines()) as i32;
+                let end_line = min(start.line + lines, term.bottommost_line());
+                let end = Point::new(end_line, start.column);
+
+                self.vi_yank(start, end, ViMotionKind::Linewise);
+                return;
+            },
+            ';' | ',' => {
+                let search = match self.ctx.vi_input().inline_search {
+                    Some(search) if c == ';' => search,
+                    Some(search) => search.reversed(),
+                    None => return self.ctx.vi_input().reset(),
+                };
+                self.vi_inline_search(search, true);
+                return;
+            },
+            'f' | 'F' | 't' | 'T' => {
+                let direction = if c.is_lowercase() { Direction::Right } else { Direction::Left };
+                let stop_short = c.eq_ignore_ascii_case(&'t');
+                let pending = ViPending::InlineSearch { direction, stop_short };
+                self.ctx.vi_input().pending = Some(pending);
+                return;
+            },
+            'i' | 'a' => {
+                self.ctx.vi_input().pending = Some(ViPending::TextObject { inner: c == 'i' });
+                return;
+            },
+            'g' => {
+                self.ctx.vi_input().pending = Some(ViPending::GPrefix);
+                return;
+            },
+            _ => return self.ctx.vi_input().reset(),
+        };
+
+        self.vi_char_motion(motion);
+    }
+
+    /// Move the vi cursor, or apply the pending operator to the text covered by the motion.
+    fn vi_char_motion(&mut self, motion: ViMotion) {
+        let (count, operator) = self.vi_take_count();
+        let origin = self.ctx.terminal().vi_mode_cursor.point;
+
+        vi_motion(self.ctx.terminal_mut(), motion, count);
+
+        if operator.is_some() {
+            let target = self.ctx.terminal().vi_mode_cursor.point;
+            self.vi_yank(origin, target, motion.into());
+        }
+    }
+
+    /// Move the vi cursor with an inline search, or apply the pending operator to the covered
+    /// text.
+    fn vi_inline_search(&mut self, search: InlineSearch, repeat: bool) {
+        let (count, operator) = self.vi_take_count();
+        let term = self.ctx.terminal_mut();
+        let origin = term.vi_mode_cursor.point;
+
+        let target = match inline_search_target(term, search, count.unwrap_or(1), repeat) {
+            Some(target) => target,
+            None => return,
+        };
+
+        if operator.is_some() {
+            // Backward searches exclude the character below the cursor.
+            let kind = match search.direction {
+                Direction::Right => ViMotionKind::Inclusive,
+                Direction::Left => ViMotionKind::Exclusive,
+            };
+            self.vi_yank(origin, target, kind);
+        } else {
+            term.vi_goto_point(target);
+        }
+    }
+
+    /// Apply the pending operator to a text object.
+    fn vi_text_object(&mut self, object: TextObject, inner: bool) {
+        let (_, operator) = self.vi_take_count();
+        let point = self.ctx.terminal().vi_mode_cursor.point;
+
+        if let (Some(_), Some(range)) = (operator, object.range(self.ctx.terminal(), point, inner))
+        {
+            let kind =
+                if object.is_linewise() { ViMotionKind::Linewise } else { ViMotionKind::Inclusive };
+            self.vi_yank(*range.start(), *range.end(), kind);
+        }
+    }
+
+    /// Take the count and operator of the current vi mode command.
+    ///
+    /// The counts of the operator and the motion are multiplied, like vi does for `2y3w`.
+    fn vi_take_count(&mut self) -> (Option<usize>, Option<ViOperator>) {
+        let vi_input = self.ctx.vi_input();
+        let count = vi_input.count.take();
+        match vi_input.operator.take() {
+            Some((operator, op_count)) => {
+                let count = count.unwrap_or(1).saturating_mul(op_count);
+                (Some(count), Some(operator))
+            },
+            None => (count, None),
+        }
+    }
+
+    /// Copy the text between two points to the clipboard.
+    ///
+    /// The vi cursor is moved to the start of the copied text.
+    fn vi_yank(&mut self, origin: Point, target: Point, kind: ViMotionKind) {
+        let (start, end) = (min(origin, target), max(origin, target));
+
+        let term = self.ctx.terminal_mut();
+        let cursor = match kind {
+            ViMotionKind::Linewise => Point::new(start.line, origin.column),
+            _ => start,
+        };
+        term.vi_goto_point(cursor);
+
+        let selection = match kind {
+            ViMotionKind::Exclusive if start == end => return,
+            ViMotionKind::Exclusive => {
+                let mut selection = Selection::new(SelectionType::Simple, start, Side::Left);
+                selection.update(end, Side::Left);
+                selection
+            },
+            ViMotionKind::Inclusive => {
+                let mut selection = Selection::new(SelectionType::Simple, start, Side::Left);
+                selection.update(end, Side::Right);
+                selection
+            },
+            ViMotionKind::Linewise => {
+                let mut selection = Selection::new(SelectionType::Lines, start, Side::Left);
+                selection.update(end, Side::Right);
+                selection
+            },
+        };
+
+        // Copy without changing the selection visible to the user.
+        let previous = term.selection.replace(selection);
+        let text = term.selection_to_string().unwrap_or_default();
+        term.selection = previous;
+
+        match self.ctx.vi_input().register.take() {
+            Some(register) => self.ctx.clipboard_mut().store_register(register, text),
+            None if !text.is_empty() => {
+                self.ctx.clipboard_mut().store(ClipboardType::Clipboard, text)
+            },
+            None => (),
+        }
+    }
+
     /// Attempt to find a binding and execute its action.
     ///
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
+    ///
+    /// Keys which could continue a key sequence binding are held back until the sequence is
+    /// complete, or processed like regular keys once it is abandoned. Longer sequences take
+    /// precedence, so a key starting a sequence only triggers its own binding once the next key
+    /// doesn't continue the sequence or the sequence times out.
     fn process_key_bindings(&mut self, input: KeyboardInput) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
         let mods = *self.ctx.modifiers();
-        let mut suppress_chars = None;
+        let keys = self.ctx.key_sequence().keys().to_vec();
 
-        for i in 0..self.ctx.config().key_bindings().len() {
-            let binding = &self.ctx.config().key_bindings()[i];
+        // Modifiers of the next key don't interrupt the sequence.
+        if !keys.is_empty() && input.virtual_keycode.map_or(false, is_modifier_key) {
+            *self.ctx.suppress_chars() = false;
+            return;
+        }
+
+        let (bindings, continued) = self.matching_key_bindings(&keys, &input, mods);
+
+        // Wait for the next key of the sequence.
+        if continued {
+            self.ctx.key_sequence().push(input, mods);
+            self.ctx.schedule_key_sequence_timeout();
+            *self.ctx.suppress_chars() = true;
+            self.ctx.mark_dirty();
+            return;
+        }
+
+        if !keys.is_empty() {
+            // Handle the held back keys before the key which didn't continue their sequence.
+            if bindings.is_empty() {
+                self.abandon_key_sequence();
+                self.process_key_bindings(input);
+                return;
+            }
+
+            self.stop_key_sequence();
+        }
+
+        self.execute_key_bindings(&bindings);
+    }
+
+    /// Find the key bindings triggered by `input`, after the pending keys of a sequence.
+    ///
+    /// Returns the indices of all bindings which are completed by the key and whether any binding
+    /// requires further keys.
+    fn matching_key_bindings(
+        &mut self,
+        keys: &[PendingKey],
+        input: &KeyboardInput,
+        mods: ModifiersState,
+    ) -> (Vec<usize>, bool) {
+        let user_mode = self.ctx.binding_mode();
+        let search_active = self.ctx.search_active();
+        let mode = BindingMode::new(self.ctx.terminal().mode(), search_active, user_mode.is_some());
+        let leader = self.ctx.config().key_sequence.leader;
+        let key_bindings = self.ctx.config().key_bindings();
+
+        let mut completed = Vec::new();
+        let mut continued = false;
+        for (i, binding) in key_bindings.iter().enumerate() {
+            if !mode.contains(binding.mode)
+                || mode.intersects(binding.notmode)
+                || !binding.is_available_in(user_mode)
+            {
+                continue;
+            }
 
-            let key = match (binding.trigger, input.virtual_keycode) {
-                (Key::Scancode(_), _) => Key::Scancode(input.scancode),
-                (_, Some(key)) => Key::Keycode(key),
-                _ => continue,
+            // Bindings using the leader key are disabled without one.
+            let leader = match (binding.leader, leader) {
+                (true, None) => continue,
+                (true, leader) => leader,
+                (false, _) => None,
             };
 
-            if binding.is_triggered_by(mode, mods, &key) {
-                // Pass through the key if any of the bindings has the `ReceiveChar` action.
-                *suppress_chars.get_or_insert(true) &= binding.action != Action::ReceiveChar;
+            let mut sequence = leader.iter().chain(&binding.sequence);
+            let sequence_len = usize::from(leader.is_some()) + binding.sequence.len();
+            if sequence_len < keys.len()
+                || !keys.iter().all(|key| {
+                    sequence.next().map_or(false, |press| press.matches(&key.input, key.mods))
+                })
+            {
+                continue;
+            }
 
-                // Binding was triggered; run the action.
-                binding.action.clone().execute(&mut self.ctx);
+            match sequence.next() {
+                Some(press) => continued |= press.matches(input, mods),
+                None => {
+                    let trigger =
+                        KeyPress { key: binding.trigger, mods: ModsWrapper(binding.mods) };
+                    if trigger.matches(input, mods) {
+                        completed.push(i);
+                    }
+                },
             }
         }
 
+        // Bindings of the active user-defined mode take precedence over all other bindings.
+        if completed.iter().any(|&i| key_bindings[i].user_mode.is_some()) {
+            completed.retain(|&i| key_bindings[i].user_mode.is_some());
+        }
+
+        (completed, continued)
+    }
+
+    /// Execute the actions of the key bindings with the indices `bindings`.
+    fn execute_key_bindings(&mut self, bindings: &[usize]) {
+        let mut suppress_chars = None;
+
+        for &i in bindings {
+            let binding = &self.ctx.config().key_bindings()[i];
+
+            // Pass through the key if any of the bindings has the `ReceiveChar` action.
+            *suppress_chars.get_or_insert(true) &=
+                !binding.action.actions().contains(&Action::ReceiveChar);
+
+            // Binding was triggered; run the action.
+            binding.action.clone().execute(&mut self.ctx);
+        }
+
         // Don't suppress char if no bindings were triggered.
         *self.ctx.suppress_chars() = suppress_chars.unwrap_or(false);
     }
 
+    /// Give up on the pending key sequence.
+    ///
+    /// If the pending keys complete a shorter sequence, its binding is triggered. Otherwise all
+    /// keys are processed like regular key presses, without starting new sequences.
+    pub fn abandon_key_sequence(&mut self) {
+        let keys = self.stop_key_sequence();
+        let (last, prefix) = match keys.split_last() {
+            Some(split) => split,
+            None => return,
+        };
+
+        let mods = *self.ctx.modifiers();
+
+        let (bindings, _) = self.matching_key_bindings(prefix, &last.input, last.mods);
+        if bindings.is_empty() {
+            for key in &keys {
+                let (bindings, _) = self.matching_key_bindings(&[], &key.input, key.mods);
+                self.replay_key(key, &bindings);
+            }
+        } else {
+            self.replay_key(last, &bindings);
+        }
+
+        *self.ctx.modifiers() = mods;
+        *self.ctx.suppress_chars() = false;
+    }
+
+    /// Process a held back key press, triggering the key bindings with the indices `bindings`.
+    fn replay_key(&mut self, key: &PendingKey, bindings: &[usize]) {
+        *self.ctx.modifiers() = key.mods;
+        self.execute_key_bindings(bindings);
+
+        for c in key.chars.chars() {
+            self.received_char(c);
+        }
+    }
+
+    /// Clear the pending key sequence, returning its keys.
+    fn stop_key_sequence(&mut self) -> Vec<PendingKey> {
+        self.ctx.cancel_key_sequence_timeout();
+        self.ctx.mark_dirty();
+        self.ctx.key_sequence().take()
+    }
+
     /// Attempt to find a binding and execute its action.
     ///
     /// The provided mode, mods, and key must match what is allowed by a binding
     /// for its action to be executed.
     fn process_mouse_bindings(&mut self, button: MouseButton) {
-        let mode = BindingMode::new(self.ctx.terminal().mode(), self.ctx.search_active());
-        let mouse_mode = self.ctx.mouse_mode();
         let mods = *self.ctx.modifiers();
+        let user_mode = self.ctx.binding_mode();
+        let search_active = self.ctx.search_active();
+        let mode = BindingMode::new(self.ctx.terminal().mode(), search_active, user_mode.is_some());
+        let mouse_mode = self.ctx.mouse_mode();
 
-        for i in 0..self.ctx.config().mouse_bindings().len() {
-            let mut binding = self.ctx.config().mouse_bindings()[i].clone();
+        let mut bindings: Vec<MouseBinding> = Vec::new();
+        for binding in self.ctx.config().mouse_bindings() {
+            let mut binding = binding.clone();
 
             // Require shift for all modifiers when mouse mode is active.
             if mouse_mode {
                 binding.mods |= ModifiersState::SHIFT;
             }
 
-            if binding.is_triggered_by(mode, mods, &button) {
-                binding.action.execute(&mut self.ctx);
+            if binding.is_triggered_by(mode, mods, &button) && binding.is_available_in(user_mode) {
+                bindings.push(binding);
             }
         }
+
+        // Bindings of the active user-defined mode take precedence over all other bindings.
+        if bindings.iter().any(|binding| binding.user_mode.is_some()) {
+            bindings.retain(|binding| binding.user_mode.is_some());
+        }
+
+        for binding in bindings {
+            binding.action.execute(&mut self.ctx);
+        }
     }
 
     /// Check mouse icon state in relation to the message bar.
@@ -1156,16 +2022,73 @@ impl<T: EventListener, A: ActionContext<T>> Processor<T, A> {
     }
 }
 
+/// Apply a vi motion `count` times.
+///
+/// With a count, motions to the top or bottom move to the line with that number instead.
+fn vi_motion<T: EventListener>(term: &mut Term<T>, motion: ViMotion, count: Option<usize>) {
+    match (motion, count) {
+        (ViMotion::Top | ViMotion::Bottom, Some(count)) => {
+            let offset = i32::try_from(count.saturating_sub(1)).unwrap_or(i32::MAX);
+            let line = term.topmost_line() + offset.min(term.total_lines() as i32);
+            term.vi_goto_line(line);
+        },
+        _ => {
+            for _ in 0..count.unwrap_or(1) {
+                let point = term.vi_mode_cursor.point;
+                term.vi_motion(motion);
+
+                // Stop once the motion can't go any further.
+                if term.vi_mode_cursor.point == point {
+                    break;
+                }
+            }
+        },
+    }
+}
+
+/// Find the target of an inline search, repeated `count` times.
+fn inline_search_target<T>(
+    term: &Term<T>,
+    search: InlineSearch,
+    count: usize,
+    repeat: bool,
+) -> Option<Point> {
+    let mut point = term.vi_mode_cursor.point;
+    for i in 0..count {
+        point = search.find(term, point, repeat || i > 0)?;
+    }
+    Some(point)
+}
+
+/// Check if a key only changes the modifiers of the following keys.
+fn is_modifier_key(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::cell::RefCell;
+
     use winit::event::{DeviceId, Event as WinitEvent, VirtualKeyCode, WindowEvent};
     use winit::window::WindowId;
 
+    use alacritty_terminal::ansi::Mode;
     use alacritty_terminal::event::Event as TerminalEvent;
 
     use crate::config::Binding;
+    use crate::input::ActionContext as _;
     use crate::message_bar::MessageBuffer;
 
     const KEY: VirtualKeyCode = VirtualKeyCode::Key0;
@@ -1182,10 +2105,19 @@ mod tests {
         pub received_count: usize,
         pub suppress_chars: bool,
         pub modifiers: ModifiersState,
+        pub key_sequence: KeySequence,
+        pub key_sequence_timeout: bool,
+        pub written: RefCell<Vec<u8>>,
+        pub binding_mode: Option<String>,
+        pub read_only: bool,
         config: &'a UiConfig,
     }
 
     impl<'a, T: EventListener> super::ActionContext<T> for ActionContext<'a, T> {
+        fn write_to_pty<B: Into<Cow<'static, [u8]>>>(&self, data: B) {
+            self.written.borrow_mut().extend_from_slice(&data.into());
+        }
+
         fn search_next(
             &mut self,
             _origin: Point,
@@ -1242,6 +2174,10 @@ mod tests {
             unimplemented!();
         }
 
+        fn vi_input(&mut self) -> &mut ViInput {
+            unimplemented!();
+        }
+
         fn received_count(&mut self) -> &mut usize {
             &mut self.received_count
         }
@@ -1262,6 +2198,34 @@ mod tests {
             unimplemented!();
         }
 
+        fn key_sequence(&mut self) -> &mut KeySequence {
+            &mut self.key_sequence
+        }
+
+        fn schedule_key_sequence_timeout(&mut self) {
+            self.key_sequence_timeout = true;
+        }
+
+        fn cancel_key_sequence_timeout(&mut self) {
+            self.key_sequence_timeout = false;
+        }
+
+        fn leave_binding_mode(&mut self) {
+            self.binding_mode = None;
+        }
+
+        fn binding_mode(&self) -> Option<&str> {
+            self.binding_mode.as_deref()
+        }
+
+        fn read_only(&self) -> bool {
+            self.read_only
+        }
+
+        fn paste(&mut self, text: &str) {
+            self.write_input(text.as_bytes().to_vec());
+        }
+
         fn pop_message(&mut self) {
             self.message_buffer.pop();
         }
@@ -1327,6 +2291,11 @@ mod tests {
                     received_count: 0,
                     suppress_chars: false,
                     modifiers: Default::default(),
+                    key_sequence: Default::default(),
+                    key_sequence_timeout: false,
+                    written: Default::default(),
+                    binding_mode: None,
+                    read_only: false,
                     message_buffer: &mut message_buffer,
                     config: &cfg,
                 };
@@ -1468,7 +2437,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_shiftmod_require_shift,
-        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::SHIFT,
@@ -1476,7 +2445,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_shift,
-        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::SHIFT, action: Action::from("\x1b[1;2D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1484,7 +2453,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_controlmod,
-        binding: Binding { trigger: KEY, mods: ModifiersState::CTRL, action: Action::from("\x1b[1;5D"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::CTRL, action: Action::from("\x1b[1;5D"), mode: BindingMode::empty(), notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::CTRL,
@@ -1492,7 +2461,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_not_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1b[D"), mode: BindingMode::empty(), notmode: BindingMode::APP_CURSOR },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1b[D"), mode: BindingMode::empty(), notmode: BindingMode::APP_CURSOR, sequence: Vec::new(), leader: false, user_mode: None },
         triggers: true,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1500,7 +2469,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_appcursormode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: true,
         mode: BindingMode::APP_CURSOR,
         mods: ModifiersState::empty(),
@@ -1508,7 +2477,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_nomode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::empty(),
@@ -1516,7 +2485,7 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_appcursormode_appkeypadmode_nomod_require_appcursor,
-        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::empty(), action: Action::from("\x1bOD"), mode: BindingMode::APP_CURSOR, notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: true,
         mode: BindingMode::APP_CURSOR | BindingMode::APP_KEYPAD,
         mods: ModifiersState::empty(),
@@ -1524,9 +2493,149 @@ mod tests {
 
     test_process_binding! {
         name: process_binding_fail_with_extra_mods,
-        binding: Binding { trigger: KEY, mods: ModifiersState::LOGO, action: Action::from("arst"), mode: BindingMode::empty(), notmode: BindingMode::empty() },
+        binding: Binding { trigger: KEY, mods: ModifiersState::LOGO, action: Action::from("arst"), mode: BindingMode::empty(), notmode: BindingMode::empty(), sequence: Vec::new(), leader: false, user_mode: None },
         triggers: false,
         mode: BindingMode::empty(),
         mods: ModifiersState::ALT | ModifiersState::LOGO,
     }
+
+    /// Bindings for `G`, the sequence `G G` and `H`.
+    const SEQUENCE_BINDINGS: &str = r#"
+        key_bindings:
+          - { key: G, chars: "g" }
+          - { key: G, sequence: [{ key: G }], chars: "gg" }
+          - { key: H, chars: "h" }
+    "#;
+
+    /// Run `f` with a processor using the key bindings of the `config` YAML.
+    fn with_processor<F>(config: &str, f: F)
+    where
+        F: FnOnce(&mut Processor<MockEventProxy, ActionContext<'_, MockEventProxy>>),
+    {
+        let mut clipboard = Clipboard::new_nop();
+        let cfg: UiConfig = serde_yaml::from_str(config).unwrap();
+        let size = SizeInfo::new(21.0, 51.0, 3.0, 3.0, 0., 0., false);
+        let mut terminal = Term::new(&cfg.terminal_config, &size, MockEventProxy);
+        let mut mouse = Mouse::default();
+        let mut message_buffer = MessageBuffer::default();
+
+        let context = ActionContext {
+            terminal: &mut terminal,
+            mouse: &mut mouse,
+            size_info: &size,
+            clipboard: &mut clipboard,
+            received_count: 0,
+            suppress_chars: false,
+            modifiers: Default::default(),
+            key_sequence: Default::default(),
+            key_sequence_timeout: false,
+            written: Default::default(),
+            binding_mode: None,
+            read_only: false,
+            message_buffer: &mut message_buffer,
+            config: &cfg,
+        };
+
+        f(&mut Processor::new(context));
+    }
+
+    #[allow(deprecated)]
+    fn key_press(key: VirtualKeyCode) -> KeyboardInput {
+        KeyboardInput {
+            scancode: 0,
+            state: ElementState::Pressed,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    #[test]
+    fn key_sequence_continued() {
+        with_processor(SEQUENCE_BINDINGS, |processor| {
+            // The first key is held back, since it could start a sequence.
+            processor.process_key_bindings(key_press(VirtualKeyCode::G));
+            assert!(processor.ctx.key_sequence.active());
+            assert!(processor.ctx.key_sequence_timeout);
+            assert!(processor.ctx.written.borrow().is_empty());
+
+            processor.process_key_bindings(key_press(VirtualKeyCode::G));
+            assert!(!processor.ctx.key_sequence.active());
+            assert!(!processor.ctx.key_sequence_timeout);
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"gg");
+        });
+    }
+
+    #[test]
+    fn key_sequence_abandoned() {
+        with_processor(SEQUENCE_BINDINGS, |processor| {
+            processor.process_key_bindings(key_press(VirtualKeyCode::G));
+            assert!(processor.ctx.key_sequence.active());
+
+            // Keys not continuing the sequence fall through, after the held back key.
+            processor.process_key_bindings(key_press(VirtualKeyCode::H));
+            assert!(!processor.ctx.key_sequence.active());
+            assert!(!processor.ctx.key_sequence_timeout);
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"gh");
+        });
+    }
+
+    #[test]
+    fn key_sequence_timeout() {
+        with_processor(SEQUENCE_BINDINGS, |processor| {
+            processor.process_key_bindings(key_press(VirtualKeyCode::G));
+            assert!(processor.ctx.key_sequence_timeout);
+
+            // The timeout triggers the binding of the keys entered so far.
+            processor.abandon_key_sequence();
+            assert!(!processor.ctx.key_sequence.active());
+            assert!(!processor.ctx.key_sequence_timeout);
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"g");
+
+            // New keys start over.
+            processor.process_key_bindings(key_press(VirtualKeyCode::H));
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"gh");
+        });
+    }
+
+    #[test]
+    fn user_mode_blocks_pty_bindings() {
+        with_processor(SEQUENCE_BINDINGS, |processor| {
+            processor.ctx.binding_mode = Some(String::from("Window"));
+
+            // Bindings writing to the PTY are ignored.
+            processor.process_key_bindings(key_press(VirtualKeyCode::H));
+            processor.process_key_bindings(key_press(VirtualKeyCode::Up));
+            assert!(processor.ctx.written.borrow().is_empty());
+
+            // Escape leaves the mode by default.
+            processor.process_key_bindings(key_press(VirtualKeyCode::Escape));
+            assert_eq!(processor.ctx.binding_mode, None);
+
+            processor.process_key_bindings(key_press(VirtualKeyCode::H));
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"h");
+        });
+    }
+
+    #[test]
+    fn read_only_blocks_output() {
+        with_processor(SEQUENCE_BINDINGS, |processor| {
+            processor.ctx.terminal.set_mode(Mode::ReportCellMouseMotion);
+            processor.ctx.terminal.set_mode(Mode::ReportFocusInOut);
+
+            let send_input = |processor: &mut Processor<_, ActionContext<'_, _>>| {
+                processor.process_key_bindings(key_press(VirtualKeyCode::H));
+                processor.ctx.paste("ls");
+                processor.mouse_report(0, ElementState::Pressed);
+                processor.on_focus_change(true);
+            };
+
+            processor.ctx.read_only = true;
+            send_input(processor);
+            assert!(processor.ctx.written.borrow().is_empty());
+
+            processor.ctx.read_only = false;
+            send_input(processor);
+            assert_eq!(processor.ctx.written.borrow().as_slice(), b"hls\x1b[M !!\x1b[I");
+        });
+    }
 }
@@ -73,6 +73,18 @@ pub fn spawn_ipc_socket(options: &Options, event_proxy: EventLoopProxy<Event>) -
                     let event = Event::new(EventType::IpcConfig(ipc_config), window_id);
                     let _ = event_proxy.send_event(event);
                 },
+                SocketMessage::ExportScrollback(ipc_export) => {
+                    let window_id = ipc_export
+                        .window_id
+                        .and_then(|id| u64::try_from(id).ok())
+                        .map(WindowId::from);
+                    let event = Event::new(EventType::IpcExport(ipc_export), window_id);
+                    let _ = event_proxy.send_event(event);
+                },
+                SocketMessage::BroadcastInput(ipc_broadcast) => {
+                    let event = Event::new(EventType::IpcBroadcastInput(ipc_broadcast), None);
+                    let _ = event_proxy.send_event(event);
+                },
                 #[cfg(feature = "takeover")]
                 SocketMessage::Takeover(takeover) => {
                     // TODO: Add validation of the arguments to the TakeoverEvent::try_from.
@@ -0,0 +1,86 @@
+//! Keystroke macros shared by all windows.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+
+/// Name of the directory storing named macros.
+const MACRO_DIRECTORY: &str = "macros";
+
+/// Recorded keystroke macros.
+#[derive(Default, Debug)]
+pub struct Macros {
+    /// Input of the most recently recorded macro.
+    last: Vec<u8>,
+}
+
+impl Macros {
+    /// Input of the most recently recorded macro.
+    pub fn last(&self) -> &[u8] {
+        &self.last
+    }
+
+    /// Replace the most recently recorded macro.
+    pub fn set_last(&mut self, input: Vec<u8>) {
+        self.last = input;
+    }
+
+    /// Load the input of a named macro from its file.
+    pub fn load(name: &str) -> Option<Vec<u8>> {
+        let path = macro_path(name)?;
+        match fs::read(&path) {
+            Ok(input) => Some(input),
+            Err(err) => {
+                warn!("Unable to read macro from {:?}: {}", path, err);
+                None
+            },
+        }
+    }
+
+    /// Save the input of a macro to the file for `name`.
+    pub fn save(name: &str, input: &[u8]) {
+        let path = match macro_path(name) {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(err) = write(&path, input) {
+            warn!("Unable to save macro to {:?}: {}", path, err);
+        }
+    }
+}
+
+/// Write a file, creating its parent directories.
+fn write(path: &Path, input: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, input)
+}
+
+/// Location of the file storing the macro `name`.
+fn macro_path(name: &str) -> Option<PathBuf> {
+    // Prevent macro names from escaping the macro directory.
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        warn!("Invalid macro name {:?}", name);
+        return None;
+    }
+
+    let directory = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    Some(directory.join("alacritty").join(MACRO_DIRECTORY).join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macro_names() {
+        assert!(macro_path("../config").is_none());
+        assert!(macro_path("dir/name").is_none());
+        assert!(macro_path("").is_none());
+    }
+}
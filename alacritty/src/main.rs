@@ -12,7 +12,7 @@
 #[cfg(not(any(feature = "x11", feature = "wayland", target_os = "macos", windows)))]
 compile_error!(r#"at least one of the "x11"/"wayland" features must be enabled"#);
 
-#[cfg(target_os = "macos")]
+#[cfg(unix)]
 use std::env;
 use std::error::Error;
 use std::fmt::Write as _;
@@ -29,23 +29,28 @@ use winit::platform::x11::EventLoopWindowTargetExtX11;
 
 use alacritty_terminal::tty;
 
+mod broadcast;
 mod cli;
 mod clipboard;
 mod config;
 mod daemon;
 mod display;
 mod event;
+mod export;
 mod input;
 #[cfg(unix)]
 mod ipc;
 mod logging;
 #[cfg(target_os = "macos")]
 mod macos;
+mod macros;
+mod match_counter;
 mod message_bar;
 #[cfg(windows)]
 mod panic;
 mod renderer;
 mod scheduler;
+mod search_history;
 mod string;
 mod window_context;
 #[cfg(feature = "takeover")]
@@ -58,7 +63,7 @@ mod gl {
 
 use crate::cli::Options;
 #[cfg(unix)]
-use crate::cli::{MessageOptions, Subcommands};
+use crate::cli::{IpcExport, MessageOptions, SocketMessage, Subcommands};
 use crate::config::{monitor, UiConfig};
 use crate::event::{Event, Processor};
 #[cfg(target_os = "macos")]
@@ -91,7 +96,14 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 /// `msg` subcommand entrypoint.
 #[cfg(unix)]
-fn msg(options: MessageOptions) -> Result<(), Box<dyn Error>> {
+fn msg(mut options: MessageOptions) -> Result<(), Box<dyn Error>> {
+    // Resolve relative paths, since the receiving process has a different working directory.
+    if let SocketMessage::ExportScrollback(IpcExport { path: Some(path), .. }) =
+        &mut options.message
+    {
+        *path = env::current_dir()?.join(&*path);
+    }
+
     ipc::send_message(options.socket, options.message).map_err(|err| err.into())
 }
 
@@ -0,0 +1,274 @@
+//! Counting of search matches across the entire scrollback history.
+
+use std::cmp::{max, min, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+use alacritty_terminal::grid::Dimensions;
+use alacritty_terminal::index::{Column, Direction, Line, Point};
+use alacritty_terminal::sync::FairMutex;
+use alacritty_terminal::term::cell::Flags;
+use alacritty_terminal::term::search::{Match, RegexIter, RegexSearch};
+use alacritty_terminal::term::Term;
+use alacritty_terminal::thread;
+
+use crate::event::{EventProxy, EventType};
+
+/// Number of lines searched while holding the terminal lock.
+const CHUNK_LINES: i32 = 1000;
+
+/// Background counter for all matches of the search regex.
+#[derive(Default)]
+pub struct MatchCounter {
+    /// Generation of the most recent count, used to abort outdated counts.
+    generation: Arc<AtomicUsize>,
+
+    /// Generation of the count running in the background.
+    running: Option<usize>,
+
+    /// Whether the matches need to be counted again.
+    dirty: bool,
+
+    /// All matches found by the last completed count.
+    matches: Option<Vec<Match>>,
+}
+
+impl MatchCounter {
+    /// Discard all matches, since the regex has changed.
+    ///
+    /// A running count is aborted, the next count starts once it has stopped.
+    pub fn reset(&mut self) {
+        self.generation.fetch_add(1, AtomicOrdering::Relaxed);
+        self.matches = None;
+        self.dirty = true;
+    }
+
+    /// Count the matches again, since the terminal content has changed.
+    pub fn refresh(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Start counting in the background, if the matches are outdated.
+    pub fn update(
+        &mut self,
+        terminal: &Arc<FairMutex<Term<EventProxy>>>,
+        regex: &RegexSearch,
+        event_proxy: EventProxy,
+    ) {
+        if !self.dirty || self.running.is_some() {
+            return;
+        }
+
+        let generation = self.generation.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+        self.running = Some(generation);
+        self.dirty = false;
+
+        let current_generation = self.generation.clone();
+        let terminal = terminal.clone();
+        let regex = regex.clone();
+
+        thread::spawn_named("search match counter", move || {
+            let outdated = || current_generation.load(AtomicOrdering::Relaxed) != generation;
+            let matches = count(&terminal, &regex, outdated);
+            event_proxy.send_event(EventType::SearchCount(generation, matches));
+        });
+    }
+
+    /// Store the result of a count, which is `None` if it was aborted.
+    pub fn finish(&mut self, generation: usize, matches: Option<Vec<Match>>) {
+        if self.running == Some(generation) {
+            self.running = None;
+        }
+
+        if generation == self.generation.load(AtomicOrdering::Relaxed) {
+            self.matches = matches;
+        }
+    }
+
+    /// Total number of matches, if they have been counted.
+    pub fn total(&self) -> Option<usize> {
+        self.matches.as_ref().map(Vec::len)
+    }
+
+    /// Index of the match containing `point`, starting at one.
+    pub fn position(&self, point: Point) -> Option<usize> {
+        let matches = self.matches.as_ref()?;
+        let index = matches
+            .binary_search_by(|regex_match| {
+                if *regex_match.end() < point {
+                    Ordering::Less
+                } else if *regex_match.start() > point {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .ok()?;
+        Some(index + 1)
+    }
+
+    /// Text describing the position of `point` within all matches, like `3/17`.
+    pub fn label(&self, point: Option<Point>) -> Option<String> {
+        let total = self.total()?;
+        match point.and_then(|point| self.position(point)) {
+            Some(position) => Some(format!("{}/{}", position, total)),
+            None => Some(format!("?/{}", total)),
+        }
+    }
+}
+
+/// Find all matches in the terminal, releasing its lock between chunks of lines.
+///
+/// Matches found in earlier chunks are moved with the content scrolled up while the lock was
+/// released, so they stay consistent with the terminal once the count is complete. The count
+/// starts over when the active grid is swapped between chunks.
+///
+/// Returns `None` if the count was aborted because `outdated` returned `true`.
+fn count<T, F>(
+    terminal: &FairMutex<Term<T>>,
+    regex: &RegexSearch,
+    outdated: F,
+) -> Option<Vec<Match>>
+where
+    F: Fn() -> bool,
+{
+    let mut matches = Vec::new();
+    let (mut start, mut marker) = {
+        let term = terminal.lock();
+        (Point::new(term.topmost_line(), Column(0)), term.scroll_marker())
+    };
+
+    loop {
+        if outdated() {
+            return None;
+        }
+        let mut term = terminal.lock();
+
+        // Move everything found so far with the content scrolled since the last chunk.
+        let topmost_line = term.topmost_line();
+        match term.scrolled_since(marker) {
+            Some(0) => (),
+            Some(delta) => {
+                matches = matches
+                    .into_iter()
+                    .filter_map(|regex_match| shift_match(regex_match, delta, topmost_line))
+                    .collect();
+                start.line = max(Line(start.line.0.saturating_sub(delta)), topmost_line);
+            },
+            None => {
+                matches.clear();
+                start = Point::new(topmost_line, Column(0));
+            },
+        }
+        marker = term.scroll_marker();
+
+        let bottommost_line = term.bottommost_line();
+        if start.line > bottommost_line {
+            break;
+        }
+
+        // End the chunk on a line which isn't wrapped, to avoid splitting matches.
+        let mut end_line = min(start.line + CHUNK_LINES, bottommost_line);
+        while end_line < bottommost_line && is_wrapped(&term, end_line) {
+            end_line += 1;
+        }
+        let end = Point::new(end_line, term.last_column());
+
+        matches.extend(RegexIter::new(start, end, Direction::Right, &term, regex));
+        term.grid_mut().collapse_history();
+
+        start = Point::new(end_line + 1, Column(0));
+    }
+
+    Some(matches)
+}
+
+/// Move a match up by `delta` lines, dropping it once it has left the history.
+fn shift_match(regex_match: Match, delta: i32, topmost_line: Line) -> Option<Match> {
+    let (mut start, mut end) = regex_match.into_inner();
+    end.line = Line(end.line.0.checked_sub(delta)?);
+    start.line = Line(start.line.0.saturating_sub(delta));
+
+    if end.line < topmost_line {
+        return None;
+    }
+    if start.line < topmost_line {
+        start = Point::new(topmost_line, Column(0));
+    }
+
+    Some(start..=end)
+}
+
+/// Check if a line continues on the next line.
+fn is_wrapped<T>(term: &Term<T>, line: Line) -> bool {
+    term.grid()[line][term.last_column()].flags.contains(Flags::WRAPLINE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use alacritty_terminal::term::test::mock_term;
+
+    #[test]
+    fn count_matches() {
+        let term = FairMutex::new(mock_term("foo bar\r\nfoo\r\nbaz foo"));
+        let regex = RegexSearch::new("foo").unwrap();
+
+        let matches = count(&term, &regex, || false).unwrap();
+        assert_eq!(matches.len(), 3);
+        assert_eq!(count(&term, &regex, || true), None);
+
+        let mut counter = MatchCounter::default();
+        counter.reset();
+        let generation = counter.generation.load(AtomicOrdering::Relaxed);
+        counter.finish(generation, Some(matches));
+
+        assert_eq!(counter.total(), Some(3));
+        assert_eq!(counter.position(Point::new(Line(1), Column(2))), Some(2));
+        assert_eq!(counter.label(Some(Point::new(Line(2), Column(4)))), Some(String::from("3/3")));
+        assert_eq!(counter.label(Some(Point::new(Line(0), Column(3)))), Some(String::from("?/3")));
+    }
+
+    #[test]
+    fn count_restarts_on_grid_swap() {
+        let content = vec!["foo"; 1500].join("\r\n");
+        let term = FairMutex::new(mock_term(&content));
+        let regex = RegexSearch::new("foo").unwrap();
+
+        // Enter the alternate screen after the first chunk has been counted.
+        let chunks = Cell::new(0);
+        let matches = count(&term, &regex, || {
+            chunks.set(chunks.get() + 1);
+            if chunks.get() == 2 {
+                let mut term = term.lock();
+                term.swap_alt();
+                term.grid_mut()[Line(3)][Column(0)].c = 'f';
+                term.grid_mut()[Line(3)][Column(1)].c = 'o';
+                term.grid_mut()[Line(3)][Column(2)].c = 'o';
+            }
+            false
+        })
+        .unwrap();
+
+        let point = |line, column| Point::new(Line(line), Column(column));
+        assert_eq!(matches, [point(3, 0)..=point(3, 2)]);
+    }
+
+    #[test]
+    fn shift_matches() {
+        let point = |line, column| Point::new(Line(line), Column(column));
+        let topmost_line = Line(-5);
+
+        let shifted = shift_match(point(0, 1)..=point(0, 3), 2, topmost_line);
+        assert_eq!(shifted, Some(point(-2, 1)..=point(-2, 3)));
+
+        let shifted = shift_match(point(-4, 1)..=point(-3, 2), 2, topmost_line);
+        assert_eq!(shifted, Some(point(-5, 0)..=point(-5, 2)));
+
+        assert_eq!(shift_match(point(-4, 1)..=point(-4, 2), 2, topmost_line), None);
+        assert_eq!(shift_match(point(-4, 1)..=point(-4, 2), i32::MAX, topmost_line), None);
+    }
+}
@@ -182,6 +182,12 @@ impl MessageBuffer {
         self.messages.push_back(message);
     }
 
+    /// Show a message before all queued messages.
+    #[inline]
+    pub fn push_front(&mut self, message: Message) {
+        self.messages.push_front(message);
+    }
+
     /// Check whether the message is already queued in the message bar.
     #[inline]
     pub fn is_queued(&self, message: &Message) -> bool {
@@ -237,7 +237,22 @@ impl Renderer {
             gl::Viewport(
                 size.padding_x() as i32,
                 size.padding_y() as i32,
-                size.width() as i32 - 2 * size.padding_x() as i32,
+                (size.width() - size.padding_x() - size.padding_right()) as i32,
+                size.height() as i32 - 2 * size.padding_y() as i32,
+            );
+        }
+    }
+
+    /// Move the viewport over the gutter left of the terminal grid.
+    ///
+    /// The projection is not changed, so the gutter's columns can be addressed starting at column
+    /// zero. The regular viewport has to be restored with [`Self::set_viewport`] afterwards.
+    pub fn set_gutter_viewport(&self, size: &SizeInfo) {
+        unsafe {
+            gl::Viewport(
+                size.padding_right() as i32,
+                size.padding_y() as i32,
+                (size.width() - size.padding_x() - size.padding_right()) as i32,
                 size.height() as i32 - 2 * size.padding_y() as i32,
             );
         }
@@ -26,9 +26,12 @@ impl TimerId {
 pub enum Topic {
     SelectionScrolling,
     DelayedSearch,
+    SearchRecount,
     BlinkCursor,
     BlinkTimeout,
     Frame,
+    Timestamps,
+    KeySequence,
 }
 
 /// Event scheduled to be emitted at a specific time.
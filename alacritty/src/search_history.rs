@@ -0,0 +1,250 @@
+//! Search history shared by all windows.
+
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use log::warn;
+
+use alacritty_terminal::term::search::SearchFlags;
+
+use crate::config::search::SearchConfig;
+use crate::event::SearchEntry;
+
+/// Name of the file storing the search history.
+const HISTORY_FILE: &str = "search_history";
+
+/// Separator between the flags and the regex of a history file entry.
+const FLAGS_SEPARATOR: char = '\t';
+
+/// Regexes of past searches, most recent first.
+#[derive(Default, Debug)]
+pub struct SearchHistory {
+    /// Unique history entries.
+    entries: VecDeque<SearchEntry>,
+
+    /// Maximum number of entries.
+    size: usize,
+
+    /// File new entries are appended to, `None` when the history isn't persistent.
+    path: Option<PathBuf>,
+
+    /// Number of entries in the history file, as far as this instance knows.
+    file_entries: usize,
+}
+
+impl SearchHistory {
+    /// Create the search history, restoring it from the history file if it is persistent.
+    pub fn new(config: &SearchConfig) -> Self {
+        let mut history = Self::default();
+        history.update_config(config);
+        history
+    }
+
+    /// Apply changes to the search configuration.
+    pub fn update_config(&mut self, config: &SearchConfig) {
+        self.size = config.history_size;
+        self.entries.truncate(self.size);
+
+        let path = if config.persistent_history { history_path() } else { None };
+        if path != self.path {
+            self.path = path;
+            self.load();
+        }
+    }
+
+    /// All entries, most recent first.
+    pub fn entries(&self) -> impl Iterator<Item = &SearchEntry> {
+        self.entries.iter()
+    }
+
+    /// Add a regex to the history, replacing identical older entries.
+    pub fn push(&mut self, entry: SearchEntry) {
+        if entry.regex.is_empty() || self.size == 0 {
+            return;
+        }
+
+        if let Some(path) = &self.path {
+            match append(path, &entry) {
+                Ok(()) => self.file_entries += 1,
+                Err(err) => warn!("Unable to save search history to {:?}: {}", path, err),
+            }
+        }
+
+        self.insert(entry);
+
+        // Remove duplicates from the file once it has grown well beyond the history size.
+        if self.file_entries > 2 * self.size {
+            self.load();
+        }
+    }
+
+    /// Add an entry without writing it to the history file.
+    fn insert(&mut self, entry: SearchEntry) {
+        self.entries.retain(|old| old.regex != entry.regex);
+        self.entries.push_front(entry);
+        self.entries.truncate(self.size);
+    }
+
+    /// Merge the entries of the history file, which might have been written by other instances.
+    ///
+    /// The file is rewritten without duplicates, to prevent it from growing indefinitely.
+    fn load(&mut self) {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => return,
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return,
+            Err(err) => {
+                warn!("Unable to read search history from {:?}: {}", path, err);
+                return;
+            },
+        };
+
+        // Entries of this instance are more recent than the file's.
+        let current: Vec<SearchEntry> = self.entries.drain(..).collect();
+        for line in content.lines() {
+            self.insert(parse_entry(line));
+        }
+        for entry in current.into_iter().rev() {
+            self.insert(entry);
+        }
+
+        let mut compacted = String::new();
+        for entry in self.entries.iter().rev() {
+            compacted.push_str(&format_entry(entry));
+            compacted.push('\n');
+        }
+
+        self.file_entries = content.lines().count();
+        if compacted != content {
+            match replace(&path, &compacted) {
+                Ok(()) => self.file_entries = self.entries.len(),
+                Err(err) => warn!("Unable to save search history to {:?}: {}", path, err),
+            }
+        }
+    }
+}
+
+/// Format a history file entry as its flags, followed by the regex.
+fn format_entry(entry: &SearchEntry) -> String {
+    format!("{}{}{}", entry.flags.bits(), FLAGS_SEPARATOR, entry.regex)
+}
+
+/// Parse a history file entry.
+///
+/// Lines without valid flags are used as regex with the default flags.
+fn parse_entry(line: &str) -> SearchEntry {
+    let flags = line.split_once(FLAGS_SEPARATOR).and_then(|(flags, regex)| {
+        let flags = SearchFlags::from_bits(flags.parse().ok()?)?;
+        Some((flags, regex))
+    });
+
+    match flags {
+        Some((flags, regex)) => SearchEntry { regex: regex.into(), flags },
+        None => SearchEntry { regex: line.into(), ..Default::default() },
+    }
+}
+
+/// Location of the search history file.
+fn history_path() -> Option<PathBuf> {
+    let directory = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    Some(directory.join("alacritty").join(HISTORY_FILE))
+}
+
+/// Append an entry to the history file.
+fn append(path: &Path, entry: &SearchEntry) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let mut file = open_options().create(true).append(true).open(path)?;
+    writeln!(file, "{}", format_entry(entry))
+}
+
+/// Replace the content of the history file.
+///
+/// The content is written to a temporary file first, so other instances never read a
+/// partially written history.
+fn replace(path: &Path, content: &str) -> io::Result<()> {
+    let tmp_path = path.with_file_name(format!(".{}.{}", HISTORY_FILE, process::id()));
+
+    let result = open_options()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&tmp_path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .and_then(|_| fs::rename(&tmp_path, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+
+    result
+}
+
+/// Options for opening the history file, which is only accessible by the user.
+fn open_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regexes(history: &SearchHistory) -> Vec<&str> {
+        history.entries().map(|entry| entry.regex.as_str()).collect()
+    }
+
+    #[test]
+    fn persistent_history() {
+        let directory = std::env::temp_dir().join(format!("alacritty-{}", std::process::id()));
+        let path = directory.join(HISTORY_FILE);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, "foo\nbar\n3\tfoo\nbaz\n").unwrap();
+
+        let mut history = SearchHistory { size: 3, ..Default::default() };
+        history.push(SearchEntry { regex: "error".into(), ..Default::default() });
+        history.path = Some(path.clone());
+        history.load();
+
+        // Entries of the running instance win over the file's, and duplicates are removed.
+        assert_eq!(regexes(&history), ["error", "baz", "foo"]);
+        assert_eq!(history.entries().nth(2).unwrap().flags, SearchFlags::from_bits_truncate(3));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3\tfoo\n1\tbaz\n1\terror\n");
+
+        history.push(SearchEntry { regex: "foo".into(), flags: SearchFlags::WHOLE_WORD });
+        history.push(SearchEntry { regex: String::new(), ..Default::default() });
+        assert_eq!(regexes(&history), ["foo", "error", "baz"]);
+        assert_eq!(history.entries().next().unwrap().flags, SearchFlags::WHOLE_WORD);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3\tfoo\n1\tbaz\n1\terror\n4\tfoo\n");
+
+        // The file is compacted once it grows beyond twice the history size.
+        for regex in ["a", "b", "a"] {
+            history.push(SearchEntry { regex: regex.into(), ..Default::default() });
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4\tfoo\n1\tb\n1\ta\n");
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
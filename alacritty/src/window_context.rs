@@ -6,6 +6,7 @@ use std::io::Write;
 use std::mem;
 #[cfg(not(windows))]
 use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -30,22 +31,29 @@ use alacritty_terminal::event::Event as TerminalEvent;
 use alacritty_terminal::event_loop::{EventLoop as PtyEventLoop, Msg, Notifier};
 use alacritty_terminal::grid::{Dimensions, Scroll};
 use alacritty_terminal::index::Direction;
+use alacritty_terminal::selection::Selection;
 use alacritty_terminal::sync::FairMutex;
 use alacritty_terminal::term::test::TermSize;
 use alacritty_terminal::term::{Term, TermMode};
 use alacritty_terminal::tty;
 
+use crate::broadcast::BroadcastGroup;
 #[cfg(unix)]
 use crate::cli::IpcConfig;
 use crate::cli::WindowOptions;
 use crate::clipboard::Clipboard;
+use crate::config::export::ExportFormat;
 use crate::config::UiConfig;
 use crate::display::window::Window;
 use crate::display::Display;
 use crate::event::{ActionContext, Event, EventProxy, EventType, Mouse, SearchState, TouchPurpose};
+use crate::export;
+use crate::input::ViInput;
 use crate::logging::LOG_TARGET_IPC_CONFIG;
+use crate::macros::Macros;
 use crate::message_bar::MessageBuffer;
 use crate::scheduler::Scheduler;
+use crate::search_history::SearchHistory;
 use crate::{input, renderer};
 #[cfg(feature = "takeover")]
 use crate::takeover::Takeover;
@@ -65,6 +73,10 @@ pub struct WindowContext {
     font_size: Size,
     mouse: Mouse,
     touch: TouchPurpose,
+    vi_input: ViInput,
+    selection_expansions: Vec<(Option<Selection>, Selection)>,
+    macro_recording: Option<Vec<u8>>,
+    pending_paste: Option<String>,
     dirty: bool,
     occluded: bool,
     preserve_title: bool,
@@ -181,7 +193,8 @@ impl WindowContext {
         // Create a display.
         //
         // The display manages a window and can draw the terminal.
-        let display = Display::new(window, context, &config)?;
+        let mut display = Display::new(window, context, &config)?;
+        display.window.set_read_only(options.read_only);
 
         info!(
             "PTY dimensions: {:?} x {:?}",
@@ -261,6 +274,10 @@ impl WindowContext {
             modifiers: Default::default(),
             mouse: Default::default(),
             touch: Default::default(),
+            vi_input: Default::default(),
+            selection_expansions: Default::default(),
+            macro_recording: Default::default(),
+            pending_paste: Default::default(),
             dirty: Default::default(),
             occluded: Default::default(),
             #[cfg(feature = "takeover")]
@@ -293,6 +310,10 @@ impl WindowContext {
                 }
             }
 
+            if let Err(err) = config.terminal_config.scrolling.validate_history() {
+                error!(target: LOG_TARGET_IPC_CONFIG, "Invalid scrolling history: {}", err);
+            }
+
             self.config = Rc::new(config);
         }
 
@@ -327,6 +348,12 @@ impl WindowContext {
             self.display.pending_update.dirty = true;
         }
 
+        // Reset the timestamp gutter when its configuration was changed.
+        if old_config.timestamps != self.config.timestamps {
+            self.display.set_timestamps(self.config.timestamps.gutter());
+            self.dirty = true;
+        }
+
         // Update title on config reload according to the following table.
         //
         // │cli │ dynamic_title │ current_title == old_config ││ set_title │
@@ -400,12 +427,44 @@ impl WindowContext {
         self.update_config(config);
     }
 
+    /// Write the scrollback history to a file in the background.
+    pub fn export_scrollback(&self, format: Option<ExportFormat>, path: Option<PathBuf>) {
+        export::export_scrollback(&self.terminal, &self.config, &self.display, format, path);
+    }
+
+    /// Add or remove the window from the group of windows broadcasting their input.
+    pub fn set_broadcast(&mut self, group: &mut BroadcastGroup, broadcast: bool) {
+        if broadcast {
+            group.insert(self.id(), &self.notifier, self.display.window.read_only());
+        } else {
+            group.remove(self.id());
+        }
+
+        self.display.window.set_broadcast(broadcast);
+    }
+
+    /// Stop or resume forwarding input to the PTY.
+    pub fn set_read_only(&mut self, group: &mut BroadcastGroup, read_only: bool) {
+        group.set_read_only(self.id(), read_only);
+        self.display.window.set_read_only(read_only);
+    }
+
+    /// Check if the window has keyboard focus.
+    #[cfg(unix)]
+    pub fn is_focused(&self) -> bool {
+        self.terminal.lock().is_focused
+    }
+
     /// Process events for this terminal window.
+    #[allow(clippy::too_many_arguments)]
     pub fn handle_event(
         &mut self,
         event_loop: &EventLoopWindowTarget<Event>,
         event_proxy: &EventLoopProxy<Event>,
         clipboard: &mut Clipboard,
+        search_history: &mut SearchHistory,
+        macros: &mut Macros,
+        broadcast: &BroadcastGroup,
         scheduler: &mut Scheduler,
         event: WinitEvent<'_, Event>,
     ) {
@@ -451,6 +510,10 @@ impl WindowContext {
             display: &mut self.display,
             mouse: &mut self.mouse,
             touch: &mut self.touch,
+            vi_input: &mut self.vi_input,
+            selection_expansions: &mut self.selection_expansions,
+            macro_recording: &mut self.macro_recording,
+            pending_paste: &mut self.pending_paste,
             dirty: &mut self.dirty,
             occluded: &mut self.occluded,
             terminal: &mut terminal,
@@ -465,6 +528,9 @@ impl WindowContext {
             event_proxy,
             event_loop,
             clipboard,
+            search_history,
+            macros,
+            broadcast,
             scheduler,
         };
         let mut processor = input::Processor::new(context);
@@ -480,13 +546,25 @@ impl WindowContext {
                 &mut self.display,
                 &mut self.notifier,
                 &self.message_buffer,
-                &self.search_state,
+                &mut self.search_state,
                 old_is_searching,
                 &self.config,
             );
             self.dirty = true;
         }
 
+        // Filter the lines of the new grid once the active grid has been swapped.
+        if let Some(filter) = &mut self.search_state.filter {
+            if filter.update(&terminal) {
+                terminal.grid_mut().collapse_history();
+                self.dirty = true;
+            }
+        }
+
+        // Count the search matches in the background.
+        let event_proxy = EventProxy::new(event_proxy.clone(), self.id());
+        self.search_state.update_match_count(&self.terminal, event_proxy);
+
         if self.dirty || self.mouse.hint_highlight_dirty {
             self.dirty |= self.display.update_highlighted_hints(
                 &terminal,
@@ -570,7 +648,7 @@ impl WindowContext {
         display: &mut Display,
         notifier: &mut Notifier,
         message_buffer: &MessageBuffer,
-        search_state: &SearchState,
+        search_state: &mut SearchState,
         old_is_searching: bool,
         config: &UiConfig,
     ) {
@@ -591,6 +669,11 @@ impl WindowContext {
             config,
         );
 
+        // Filter the lines of the resized terminal again.
+        if let Some(filter) = &mut search_state.filter {
+            filter.resize(terminal, &config.terminal_config, display.size_info);
+        }
+
         let new_is_searching = search_state.history_index.is_some();
         if !old_is_searching && new_is_searching {
             // Scroll on search start to make sure origin is visible with minimal viewport motion.
@@ -10,7 +10,9 @@ mod scrolling;
 
 use crate::ansi::{CursorShape, CursorStyle};
 
-pub use crate::config::scrolling::{Scrolling, MAX_SCROLLBACK_LINES};
+pub use crate::config::scrolling::{
+    HistoryBackend, Scrolling, MAX_COMPRESSED_SCROLLBACK_LINES, MAX_SCROLLBACK_LINES,
+};
 
 /// Logging target for config error messages.
 pub const LOG_TARGET_CONFIG: &str = "alacritty_config_derive";
@@ -6,31 +6,82 @@ use alacritty_config_derive::{ConfigDeserialize, SerdeReplace};
 /// Maximum scrollback amount configurable.
 pub const MAX_SCROLLBACK_LINES: u32 = 100_000;
 
+/// Maximum scrollback amount configurable with history compression.
+pub const MAX_COMPRESSED_SCROLLBACK_LINES: u32 = 10_000_000;
+
 /// Struct for scrolling related settings.
 #[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Scrolling {
     pub multiplier: u8,
 
+    /// Number of history lines kept uncompressed.
+    pub compress_after: Option<u32>,
+
+    /// Storage used for history lines.
+    pub history_backend: HistoryBackend,
+
     history: ScrollingHistory,
 }
 
 impl Default for Scrolling {
     fn default() -> Self {
-        Self { multiplier: 3, history: Default::default() }
+        Self {
+            multiplier: 3,
+            compress_after: None,
+            history_backend: Default::default(),
+            history: Default::default(),
+        }
     }
 }
 
 impl Scrolling {
+    /// Maximum number of lines in the scrollback history.
+    ///
+    /// When using [`HistoryBackend::File`], this is the number of history lines kept in memory.
     pub fn history(self) -> u32 {
         self.history.0
     }
 
+    /// Check that the history size does not exceed the limit for uncompressed history.
+    ///
+    /// Without history compression, the history is limited to [`MAX_SCROLLBACK_LINES`]. Exceeding
+    /// this limit resets the history to its default size.
+    pub fn validate_history(&mut self) -> Result<(), String> {
+        let lines = self.history.0;
+        if self.compress_after.is_some() || lines <= MAX_SCROLLBACK_LINES {
+            return Ok(());
+        }
+
+        self.history = ScrollingHistory::default();
+
+        Err(format!(
+            "exceeded maximum scrolling history without `compress_after` \
+             ({lines}/{MAX_SCROLLBACK_LINES})"
+        ))
+    }
+
     // Update the history size, used in ref tests.
     pub fn set_history(&mut self, history: u32) {
         self.history = ScrollingHistory(history);
     }
 }
 
+/// Storage for the scrollback history.
+#[derive(ConfigDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryBackend {
+    /// Keep all history lines in memory.
+    Memory,
+
+    /// Move old history lines into a temporary file, without limiting the history size.
+    File,
+}
+
+impl Default for HistoryBackend {
+    fn default() -> Self {
+        HistoryBackend::Memory
+    }
+}
+
 #[derive(SerdeReplace, Copy, Clone, Debug, PartialEq, Eq)]
 struct ScrollingHistory(u32);
 
@@ -47,9 +98,9 @@ impl<'de> Deserialize<'de> for ScrollingHistory {
     {
         let lines = u32::deserialize(deserializer)?;
 
-        if lines > MAX_SCROLLBACK_LINES {
+        if lines > MAX_COMPRESSED_SCROLLBACK_LINES {
             Err(SerdeError::custom(format!(
-                "exceeded maximum scrolling history ({lines}/{MAX_SCROLLBACK_LINES})"
+                "exceeded maximum scrolling history ({lines}/{MAX_COMPRESSED_SCROLLBACK_LINES})"
             )))
         } else {
             Ok(Self(lines))
@@ -0,0 +1,550 @@
+//! Compact representation of rows in the scrollback history.
+
+use std::cell::Cell;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Range;
+
+use once_cell::unsync::OnceCell;
+use serde::{Deserialize, Serialize};
+
+use crate::grid::spill::{SpilledRows, PAGE_LINES};
+use crate::grid::{GridCell, Row, Timestamp};
+use crate::index::Column;
+use crate::term::cell::Flags;
+
+/// A single row in compressed form.
+///
+/// Instead of storing every cell individually, the characters of all cells are kept in a single
+/// UTF-8 buffer, while their attributes are run-length encoded. Trailing cells which are equal to
+/// the default cell are not stored at all.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CompressedRow<T> {
+    /// Primary character of every stored cell.
+    text: Box<str>,
+
+    /// Attributes of consecutive cells, stored as a template cell and the length of the run.
+    runs: Box<[(T, u32)]>,
+
+    /// Time of the last write to the row.
+    #[serde(default)]
+    timestamp: Timestamp,
+}
+
+impl<T> Default for CompressedRow<T> {
+    fn default() -> Self {
+        Self { text: Box::default(), runs: Box::default(), timestamp: 0 }
+    }
+}
+
+impl<T: GridCell + Clone + Default + PartialEq> CompressedRow<T> {
+    /// Compress an expanded row.
+    pub fn new(row: &Row<T>) -> Self {
+        let default = T::default();
+        let len = row.into_iter().rposition(|cell| cell != &default).map_or(0, |i| i + 1);
+
+        let mut text = String::with_capacity(len);
+        let mut runs: Vec<(T, u32)> = Vec::new();
+
+        for cell in &row[..Column(len)] {
+            let c = cell.character();
+            text.push(c);
+
+            // Compare attributes by temporarily using the cell's character in the template.
+            if let Some((template, count)) = runs.last_mut() {
+                let template_c = template.character();
+                template.set_character(c);
+                let same_attributes = template == cell;
+                template.set_character(template_c);
+
+                if same_attributes {
+                    *count += 1;
+                    continue;
+                }
+            }
+
+            runs.push((cell.clone(), 1));
+        }
+
+        Self {
+            text: text.into_boxed_str(),
+            runs: runs.into_boxed_slice(),
+            timestamp: row.timestamp,
+        }
+    }
+
+    /// Expand the row to the specified number of columns.
+    ///
+    /// Cells beyond the new width are dropped, while missing cells are filled with the default
+    /// cell.
+    pub fn decompress(&self, columns: usize) -> Row<T> {
+        let mut cells = Vec::with_capacity(columns);
+
+        let mut chars = self.text.chars();
+        'runs: for (template, count) in self.runs.iter() {
+            for _ in 0..*count {
+                if cells.len() == columns {
+                    break 'runs;
+                }
+
+                let mut cell = template.clone();
+                cell.set_character(chars.next().unwrap_or(' '));
+                cells.push(cell);
+            }
+        }
+
+        // Remove fullwidth characters which lost their spacer due to truncation.
+        let occ = cells.len();
+        if occ == columns && occ < self.text.chars().count() {
+            let last = &mut cells[occ - 1];
+            if last.flags().contains(Flags::WIDE_CHAR) {
+                last.flags_mut().remove(Flags::WIDE_CHAR);
+                last.set_character(' ');
+            }
+        }
+
+        cells.resize_with(max(columns, 1), T::default);
+
+        let mut row = Row::from_vec(cells, occ);
+        row.timestamp = self.timestamp;
+        row
+    }
+}
+
+/// A compressed row which is expanded on demand.
+#[derive(Clone, Debug)]
+pub struct CompressedLine<T> {
+    row: CompressedRow<T>,
+
+    /// Expanded version of the row, populated when the row is accessed.
+    expanded: OnceCell<Box<Row<T>>>,
+
+    /// Expanded row was modified and the compressed row is outdated.
+    dirty: bool,
+}
+
+impl<T> Default for CompressedLine<T> {
+    fn default() -> Self {
+        CompressedRow::default().into()
+    }
+}
+
+impl<T> From<CompressedRow<T>> for CompressedLine<T> {
+    fn from(row: CompressedRow<T>) -> Self {
+        Self { row, expanded: OnceCell::new(), dirty: false }
+    }
+}
+
+impl<T: GridCell + Clone + Default + PartialEq> CompressedLine<T> {
+    pub fn new(row: &Row<T>) -> Self {
+        CompressedRow::new(row).into()
+    }
+
+    /// Compressed version of the row.
+    ///
+    /// This does not include modifications made to the expanded row until it is collapsed.
+    #[inline]
+    pub fn compressed(&self) -> &CompressedRow<T> {
+        &self.row
+    }
+
+    /// Time of the last write to the row, without expanding it.
+    #[inline]
+    pub fn timestamp(&self) -> Timestamp {
+        match self.expanded.get() {
+            Some(expanded) => expanded.timestamp,
+            None => self.row.timestamp,
+        }
+    }
+
+    /// Get the expanded row.
+    #[inline]
+    pub fn get(&self, columns: usize) -> &Row<T> {
+        self.expanded.get_or_init(|| Box::new(self.row.decompress(columns)))
+    }
+
+    /// Get the expanded row for modification.
+    #[inline]
+    pub fn get_mut(&mut self, columns: usize) -> &mut Row<T> {
+        self.dirty = true;
+        self.get(columns);
+        self.expanded.get_mut().unwrap()
+    }
+
+    /// Drop the expanded row, compressing any changes made to it.
+    ///
+    /// Returns `true` if the compressed row was modified.
+    pub fn collapse(&mut self) -> bool {
+        let expanded = self.expanded.take();
+        match expanded {
+            Some(expanded) if self.dirty => {
+                self.row = CompressedRow::new(&expanded);
+                self.dirty = false;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Consume the line, returning the expanded row.
+    fn into_row(mut self, columns: usize) -> Row<T> {
+        match self.expanded.take() {
+            Some(expanded) => *expanded,
+            None => self.row.decompress(columns),
+        }
+    }
+}
+
+/// Compressed history lines.
+///
+/// Lines are indexed from the newest to the oldest line, with index `0` being the line directly
+/// above the uncompressed history.
+///
+/// When a history file is used, the oldest lines are moved out of memory and into the file. These
+/// lines are indexed after all lines kept in memory.
+#[derive(Clone, Debug)]
+pub struct CompressedRows<T> {
+    /// Compressed rows, ordered from the oldest to the newest line.
+    rows: VecDeque<CompressedLine<T>>,
+
+    /// Lines older than `rows`, which are stored in the history file.
+    spilled: Option<SpilledRows<T>>,
+
+    /// Number of columns rows are expanded to.
+    columns: usize,
+
+    /// Range of rows in `rows` which might currently be expanded.
+    expanded: Cell<Option<(usize, usize)>>,
+}
+
+impl<T> Default for CompressedRows<T> {
+    fn default() -> Self {
+        Self { rows: VecDeque::new(), spilled: None, columns: 1, expanded: Cell::new(None) }
+    }
+}
+
+impl<T> CompressedRows<T> {
+    #[inline]
+    pub fn new(columns: usize) -> Self {
+        Self { columns, ..Default::default() }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rows.len() + self.spilled.as_ref().map_or(0, SpilledRows::len)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.spilled.as_ref().map_or(true, SpilledRows::is_empty)
+    }
+
+    /// Check if lines are moved into the history file.
+    #[inline]
+    pub fn is_spilling(&self) -> bool {
+        self.spilled.is_some()
+    }
+
+    /// Start moving lines into a new history file.
+    pub fn enable_spill(&mut self) -> io::Result<()> {
+        if self.spilled.is_none() {
+            self.spilled = Some(SpilledRows::new()?);
+        }
+
+        Ok(())
+    }
+
+    /// Convert a line index to the index in the underlying buffer.
+    #[inline]
+    fn buffer_index(&self, index: usize) -> usize {
+        debug_assert!(index < self.rows.len());
+
+        self.rows.len() - index - 1
+    }
+
+    /// Remove the oldest rows.
+    #[inline]
+    pub fn remove_oldest(&mut self, mut count: usize) {
+        if let Some(spilled) = &mut self.spilled {
+            let removed = min(count, spilled.len());
+            spilled.remove_oldest(removed);
+            count -= removed;
+        }
+
+        let count = min(count, self.rows.len());
+        self.rows.drain(..count);
+        self.shift_expanded(count);
+    }
+
+    /// Keep track of rows which have been expanded.
+    #[inline]
+    fn track_expanded(&self, index: usize) {
+        let expanded = match self.expanded.get() {
+            Some((start, end)) => (min(start, index), max(end, index)),
+            None => (index, index),
+        };
+        self.expanded.set(Some(expanded));
+    }
+
+    /// Move the tracked expansion range after rows were removed at the start of the buffer.
+    #[inline]
+    fn shift_expanded(&mut self, removed: usize) {
+        let expanded = self.expanded.get().and_then(|(start, end)| {
+            end.checked_sub(removed).map(|end| (start.saturating_sub(removed), end))
+        });
+        self.expanded.set(expanded);
+    }
+}
+
+impl<T: GridCell + Clone + Default + PartialEq> CompressedRows<T> {
+    /// Get a row, expanding it if necessary.
+    #[inline]
+    pub fn get(&self, index: usize) -> &Row<T> {
+        if let Some(index) = self.spilled_index(index) {
+            return self.spilled.as_ref().unwrap().get(index, self.columns);
+        }
+
+        let index = self.buffer_index(index);
+        let line = &self.rows[index];
+
+        if line.expanded.get().is_none() {
+            self.track_expanded(index);
+        }
+
+        line.get(self.columns)
+    }
+
+    /// Get a mutable row, expanding it if necessary.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut Row<T> {
+        if let Some(index) = self.spilled_index(index) {
+            return self.spilled.as_mut().unwrap().get_mut(index, self.columns);
+        }
+
+        let index = self.buffer_index(index);
+
+        if self.rows[index].expanded.get().is_none() {
+            self.track_expanded(index);
+        }
+
+        self.rows[index].get_mut(self.columns)
+    }
+
+    /// Time of the last write to a row, without expanding it.
+    #[inline]
+    pub fn timestamp(&self, index: usize) -> Timestamp {
+        match self.spilled_index(index) {
+            Some(index) => self.spilled.as_ref().unwrap().timestamp(index),
+            None => self.rows[self.buffer_index(index)].timestamp(),
+        }
+    }
+
+    /// Convert a line index to an index in the history file.
+    #[inline]
+    fn spilled_index(&self, index: usize) -> Option<usize> {
+        index.checked_sub(self.rows.len())
+    }
+
+    /// Add a new row directly above the uncompressed history.
+    #[inline]
+    pub fn push_newest(&mut self, row: &Row<T>) {
+        self.rows.push_back(CompressedLine::new(row));
+    }
+
+    /// Add a new row at the top of the history.
+    #[inline]
+    pub fn push_oldest(&mut self, row: &Row<T>) {
+        if let Some(spilled) = self.spilled.as_mut().filter(|spilled| !spilled.is_empty()) {
+            spilled.push_oldest(row);
+            return;
+        }
+
+        self.rows.push_front(CompressedLine::new(row));
+
+        if let Some((start, end)) = self.expanded.get() {
+            self.expanded.set(Some((start + 1, end + 1)));
+        }
+    }
+
+    /// Remove the newest row, returning it in its expanded form.
+    #[inline]
+    pub fn pop_newest(&mut self) -> Option<Row<T>> {
+        if self.rows.is_empty() {
+            self.unspill_page();
+        }
+
+        let line = self.rows.pop_back()?;
+        Some(line.into_row(self.columns))
+    }
+
+    /// Move the oldest lines kept in memory into the history file.
+    ///
+    /// Lines are only moved in full pages, so up to `memory_lines + PAGE_LINES - 1` lines are kept
+    /// in memory.
+    pub fn spill(&mut self, memory_lines: usize) {
+        let spilled = match &mut self.spilled {
+            Some(spilled) => spilled,
+            None => return,
+        };
+
+        let mut removed = 0;
+        while self.rows.len() >= memory_lines + PAGE_LINES {
+            spilled.push_newest(self.rows.drain(..PAGE_LINES).map(|mut line| {
+                line.collapse();
+                line
+            }));
+            removed += PAGE_LINES;
+        }
+
+        self.shift_expanded(removed);
+    }
+
+    /// Stop using the history file, moving all of its lines back into memory.
+    pub fn disable_spill(&mut self) {
+        while self.spilled.as_ref().map_or(false, |spilled| !spilled.is_empty()) {
+            self.unspill_page();
+        }
+
+        self.spilled = None;
+    }
+
+    /// Move the newest page of the history file back into memory.
+    fn unspill_page(&mut self) {
+        let lines = match self.spilled.as_mut().and_then(SpilledRows::pop_newest) {
+            Some(lines) => lines,
+            None => return,
+        };
+
+        let count = lines.len();
+        for line in lines.into_iter().rev() {
+            self.rows.push_front(line);
+        }
+
+        // Track all new lines, since they might have been loaded while in the file.
+        let expanded = match self.expanded.get() {
+            Some((_, end)) => (0, end + count),
+            None => (0, count.saturating_sub(1)),
+        };
+        self.expanded.set(Some(expanded));
+    }
+
+    /// Drop the expanded version of all rows outside of `visible`.
+    ///
+    /// The `visible` range is specified in line indices, starting at the newest line.
+    pub fn collapse(&mut self, visible: Range<usize>) {
+        if let Some(spilled) = &mut self.spilled {
+            let rows = self.rows.len();
+            spilled.collapse(visible.start.saturating_sub(rows)..visible.end.saturating_sub(rows));
+        }
+
+        let (start, end) = match self.expanded.take() {
+            Some(expanded) => expanded,
+            None => return,
+        };
+
+        let len = self.rows.len();
+        let visible = len.saturating_sub(visible.end)..len.saturating_sub(visible.start);
+
+        let mut remaining = None;
+        for index in start..=min(end, len.saturating_sub(1)) {
+            if visible.contains(&index) {
+                if self.rows[index].expanded.get().is_some() {
+                    remaining = Some(remaining.map_or((index, index), |(start, _)| (start, index)));
+                }
+            } else {
+                self.rows[index].collapse();
+            }
+        }
+
+        self.expanded.set(remaining);
+    }
+
+    /// Update the number of columns rows are expanded to.
+    pub fn set_columns(&mut self, columns: usize) {
+        if self.columns == columns {
+            return;
+        }
+
+        self.collapse(0..0);
+        self.columns = columns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::term::cell::Cell;
+
+    fn row(text: &str, columns: usize) -> Row<Cell> {
+        let mut row = Row::<Cell>::new(columns);
+        for (i, c) in text.chars().enumerate() {
+            row[Column(i)].c = c;
+        }
+        row
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut row = row("hello world", 20);
+        row[Column(6)].flags.insert(Flags::BOLD);
+        row[Column(7)].flags.insert(Flags::BOLD);
+        row[Column(8)].push_zerowidth('\u{0301}');
+        row.timestamp = 42;
+
+        let compressed = CompressedRow::new(&row);
+        assert_eq!(&*compressed.text, "hello world");
+        assert_eq!(compressed.runs.len(), 4);
+
+        assert_eq!(compressed.decompress(20), row);
+    }
+
+    #[test]
+    fn empty_row() {
+        let row = Row::<Cell>::new(5);
+
+        let compressed = CompressedRow::new(&row);
+        assert!(compressed.text.is_empty());
+        assert!(compressed.runs.is_empty());
+
+        assert_eq!(compressed.decompress(5), row);
+    }
+
+    #[test]
+    fn decompress_resized() {
+        let compressed = CompressedRow::new(&row("abcdef", 6));
+
+        assert_eq!(compressed.decompress(3), row("abc", 3));
+        assert_eq!(compressed.decompress(8), row("abcdef", 8));
+    }
+
+    #[test]
+    fn decompress_truncated_wide_char() {
+        let mut row = row("a文 ", 4);
+        row[Column(1)].flags.insert(Flags::WIDE_CHAR);
+        row[Column(2)].flags.insert(Flags::WIDE_CHAR_SPACER);
+
+        let expanded = CompressedRow::new(&row).decompress(2);
+        assert_eq!(expanded[Column(1)].c, ' ');
+        assert!(!expanded[Column(1)].flags.contains(Flags::WIDE_CHAR));
+    }
+
+    #[test]
+    fn collapse_outside_viewport() {
+        let mut rows = CompressedRows::new(5);
+        for c in ['a', 'b', 'c', 'd'] {
+            rows.push_newest(&row(&c.to_string(), 5));
+        }
+
+        assert_eq!(rows.get(0)[Column(0)].c, 'd');
+        assert_eq!(rows.get(3)[Column(0)].c, 'a');
+        rows.get_mut(2)[Column(0)].c = 'x';
+
+        rows.collapse(0..1);
+
+        assert!(rows.rows[3].expanded.get().is_some());
+        assert!(rows.rows.iter().take(3).all(|line| line.expanded.get().is_none()));
+        assert_eq!(rows.get(2)[Column(0)].c, 'x');
+    }
+}
@@ -1,24 +1,28 @@
 //! A specialized 2D grid implementation optimized for use in a terminal.
 
 use std::cmp::{max, min};
+use std::io;
 use std::ops::{Bound, Deref, Index, IndexMut, Range, RangeBounds};
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use crate::ansi::{CharsetIndex, StandardCharset};
 use crate::index::{Column, Line, Point};
 use crate::term::cell::{Flags, ResetDiscriminant};
 
+mod compressed;
 pub mod resize;
 mod row;
+mod spill;
 mod storage;
 #[cfg(test)]
 mod tests;
 
-pub use self::row::Row;
+pub use self::row::{timestamp_now, Row, Timestamp};
 use self::storage::Storage;
 
-pub trait GridCell: Sized {
+pub trait GridCell: Sized + Serialize + DeserializeOwned {
     /// Check if the cell contains any content.
     fn is_empty(&self) -> bool;
 
@@ -27,6 +31,12 @@ pub trait GridCell: Sized {
 
     fn flags(&self) -> &Flags;
     fn flags_mut(&mut self) -> &mut Flags;
+
+    /// Primary character stored in the cell.
+    fn character(&self) -> char;
+
+    /// Replace the primary character stored in the cell.
+    fn set_character(&mut self, c: char);
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -133,6 +143,22 @@ pub struct Grid<T> {
 
     /// Maximum number of lines in history.
     max_scroll_limit: usize,
+
+    /// Number of history lines kept uncompressed.
+    ///
+    /// Compression is disabled when this is `None`.
+    #[serde(skip)]
+    compress_after: Option<usize>,
+
+    /// Number of history lines kept in memory when using a history file.
+    ///
+    /// Lines are never moved into a file when this is `None`.
+    #[serde(skip)]
+    spill_after: Option<usize>,
+
+    /// Total number of lines scrolled into the history.
+    #[serde(skip)]
+    scrolled_lines: u64,
 }
 
 impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
@@ -143,11 +169,47 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             display_offset: 0,
             saved_cursor: Cursor::default(),
             cursor: Cursor::default(),
+            compress_after: None,
+            spill_after: None,
+            scrolled_lines: 0,
             lines,
             columns,
         }
     }
 
+    /// Update the number of history lines which are kept uncompressed.
+    pub fn update_compression(&mut self, compress_after: Option<usize>) {
+        self.compress_after = compress_after;
+
+        match self.uncompressed_history() {
+            Some(_) => self.compress_history(),
+            None => self.raw.decompress(),
+        }
+    }
+
+    /// Move history lines exceeding `memory_lines` into a temporary file.
+    ///
+    /// Lines in the file are always compressed and are read back whenever they are accessed. The
+    /// file is removed once the grid is dropped.
+    pub fn enable_spill(&mut self, memory_lines: usize) -> io::Result<()> {
+        self.raw.enable_spill()?;
+        self.spill_after = Some(memory_lines);
+        self.compress_history();
+
+        Ok(())
+    }
+
+    /// Move all history lines from the temporary file back into memory.
+    pub fn disable_spill(&mut self) {
+        if !self.raw.is_spilling() {
+            return;
+        }
+
+        self.raw.disable_spill();
+        self.spill_after = None;
+        self.update_compression(self.compress_after);
+    }
+
     /// Update the size of the scrollback history.
     pub fn update_history(&mut self, history_size: usize) {
         let current_history_size = self.history_size();
@@ -168,12 +230,61 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
             Scroll::Top => self.history_size(),
             Scroll::Bottom => 0,
         };
+
+        self.collapse_history();
     }
 
     fn increase_scroll_limit(&mut self, count: usize) {
-        let count = min(count, self.max_scroll_limit - self.history_size());
-        if count != 0 {
-            self.raw.initialize(count, self.columns);
+        let mut new_lines = min(count, self.max_scroll_limit - self.history_size());
+
+        // Drop the oldest compressed lines when history is full, since rotation would otherwise
+        // discard the oldest uncompressed lines instead.
+        new_lines += self.raw.remove_compressed(count - new_lines);
+
+        if new_lines != 0 {
+            self.raw.initialize(new_lines, self.columns);
+        }
+    }
+
+    /// Number of history lines which are kept uncompressed.
+    fn uncompressed_history(&self) -> Option<usize> {
+        match (self.compress_after, self.spill_after) {
+            (Some(compress_after), Some(spill_after)) => Some(min(compress_after, spill_after)),
+            (compress_after, spill_after) => compress_after.or(spill_after),
+        }
+    }
+
+    /// Compress history lines beyond the compression threshold.
+    ///
+    /// When a history file is used, compressed lines beyond the memory limit are moved into it.
+    fn compress_history(&mut self) {
+        if let Some(uncompressed_history) = self.uncompressed_history() {
+            self.raw.compress(self.lines + uncompressed_history);
+        }
+
+        if let Some(spill_after) = self.spill_after {
+            self.raw.spill(self.lines + spill_after);
+        }
+
+        self.collapse_history();
+    }
+
+    /// Time of the last write to a line.
+    ///
+    /// Unlike indexing the grid, this does not expand compressed history lines.
+    #[inline]
+    pub fn timestamp(&self, line: Line) -> Timestamp {
+        self.raw.timestamp(line)
+    }
+
+    /// Drop expanded compressed lines outside of the viewport.
+    ///
+    /// Indexing the grid immutably expands compressed history lines, so this should be called
+    /// after reading large parts of the history.
+    pub fn collapse_history(&mut self) {
+        if self.raw.compressed_len() != 0 {
+            let visible = self.display_offset..self.display_offset + self.lines;
+            self.raw.collapse_compressed(visible);
         }
     }
 
@@ -268,6 +379,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
         // Create scrollback for the new lines.
         self.increase_scroll_limit(positions);
+        self.scrolled_lines += positions as u64;
 
         // Swap the lines fixed at the top to their target positions after rotation.
         //
@@ -294,6 +406,8 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
         for i in (region.end.0..(screen_lines as i32)).rev().map(Line::from) {
             self.raw.swap(i, i - positions);
         }
+
+        self.compress_history();
     }
 
     pub fn clear_viewport<D>(&mut self)
@@ -346,7 +460,7 @@ impl<T> Grid<T> {
     /// Reset a visible region within the grid.
     pub fn reset_region<D, R: RangeBounds<Line>>(&mut self, bounds: R)
     where
-        T: ResetDiscriminant<D> + GridCell + Clone + Default,
+        T: ResetDiscriminant<D> + GridCell + Clone + Default + PartialEq,
         D: PartialEq,
     {
         let start = match bounds.start_bound() {
@@ -382,7 +496,7 @@ impl<T> Grid<T> {
     #[inline]
     pub fn initialize_all(&mut self)
     where
-        T: GridCell + Clone + Default,
+        T: GridCell + Clone + Default + PartialEq,
     {
         // Remove all cached lines to clear them of any content.
         self.truncate();
@@ -393,7 +507,11 @@ impl<T> Grid<T> {
 
     /// This is used only for truncating before saving ref-tests.
     #[inline]
-    pub fn truncate(&mut self) {
+    pub fn truncate(&mut self)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        self.raw.decompress();
         self.raw.truncate();
     }
 
@@ -418,13 +536,25 @@ impl<T> Grid<T> {
         GridIterator { grid: self, point: start, end }
     }
 
+    /// Total number of lines which have been scrolled into the history.
+    ///
+    /// Content moves up by one line for every scrolled line, which allows tracking its position
+    /// while the terminal is unlocked.
+    #[inline]
+    pub fn scrolled_lines(&self) -> u64 {
+        self.scrolled_lines
+    }
+
     #[inline]
     pub fn display_offset(&self) -> usize {
         self.display_offset
     }
 
     #[inline]
-    pub fn cursor_cell(&mut self) -> &mut T {
+    pub fn cursor_cell(&mut self) -> &mut T
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
         let point = self.cursor.point;
         &mut self[point.line][point.column]
     }
@@ -440,7 +570,7 @@ impl<T: PartialEq> PartialEq for Grid<T> {
     }
 }
 
-impl<T> Index<Line> for Grid<T> {
+impl<T: GridCell + Clone + Default + PartialEq> Index<Line> for Grid<T> {
     type Output = Row<T>;
 
     #[inline]
@@ -449,14 +579,14 @@ impl<T> Index<Line> for Grid<T> {
     }
 }
 
-impl<T> IndexMut<Line> for Grid<T> {
+impl<T: GridCell + Clone + Default + PartialEq> IndexMut<Line> for Grid<T> {
     #[inline]
     fn index_mut(&mut self, index: Line) -> &mut Row<T> {
         &mut self.raw[index]
     }
 }
 
-impl<T> Index<Point> for Grid<T> {
+impl<T: GridCell + Clone + Default + PartialEq> Index<Point> for Grid<T> {
     type Output = T;
 
     #[inline]
@@ -465,7 +595,7 @@ impl<T> Index<Point> for Grid<T> {
     }
 }
 
-impl<T> IndexMut<Point> for Grid<T> {
+impl<T: GridCell + Clone + Default + PartialEq> IndexMut<Point> for Grid<T> {
     #[inline]
     fn index_mut(&mut self, point: Point) -> &mut T {
         &mut self[point.line][point.column]
@@ -567,7 +697,7 @@ pub struct GridIterator<'a, T> {
     end: Point,
 }
 
-impl<'a, T> GridIterator<'a, T> {
+impl<'a, T: GridCell + Clone + Default + PartialEq> GridIterator<'a, T> {
     /// Current iteratior position.
     pub fn point(&self) -> Point {
         self.point
@@ -579,7 +709,7 @@ impl<'a, T> GridIterator<'a, T> {
     }
 }
 
-impl<'a, T> Iterator for GridIterator<'a, T> {
+impl<'a, T: GridCell + Clone + Default + PartialEq> Iterator for GridIterator<'a, T> {
     type Item = Indexed<&'a T>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -605,7 +735,7 @@ pub trait BidirectionalIterator: Iterator {
     fn prev(&mut self) -> Option<Self::Item>;
 }
 
-impl<'a, T> BidirectionalIterator for GridIterator<'a, T> {
+impl<'a, T: GridCell + Clone + Default + PartialEq> BidirectionalIterator for GridIterator<'a, T> {
     fn prev(&mut self) -> Option<Self::Item> {
         let topmost_line = self.grid.topmost_line();
         let last_column = self.grid.last_column();
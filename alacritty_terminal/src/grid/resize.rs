@@ -163,6 +163,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
             // Add removed cells to previous row and reflow content.
             last_row.append(&mut cells);
+            last_row.timestamp = max(last_row.timestamp, row.timestamp);
 
             let cursor_buffer_line = self.lines - self.cursor.point.line.0 as usize - 1;
 
@@ -268,6 +269,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
                 row.append_front(buffered);
             }
 
+            let timestamp = row.timestamp;
             loop {
                 // Remove all cells which require reflowing.
                 let mut wrapped = match row.shrink(columns) {
@@ -354,6 +356,7 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
                         wrapped.resize_with(columns, T::default);
                     }
                     row = Row::from_vec(wrapped, occ);
+                    row.timestamp = timestamp;
 
                     if i < self.display_offset {
                         // Since we added a new line, rotate up the viewport.
@@ -365,9 +368,15 @@ impl<T: GridCell + Default + PartialEq + Clone> Grid<T> {
 
         // Reverse iterator and use it as the new grid storage.
         let mut reversed: Vec<Row<T>> = new_raw.drain(..).rev().collect();
-        reversed.truncate(self.max_scroll_limit + self.lines);
+        let max_lines = self.max_scroll_limit.saturating_add(self.lines);
+        reversed.truncate(max_lines);
         self.raw.replace_inner(reversed);
 
+        // Remove compressed lines which no longer fit into the scrollback history.
+        let overflow = self.raw.len().saturating_sub(max_lines);
+        self.raw.remove_compressed(overflow);
+        self.compress_history();
+
         // Reflow the primary cursor, or clamp it if reflow is disabled.
         if !reflow {
             self.cursor.point.column = min(self.cursor.point.column, Column(columns - 1));
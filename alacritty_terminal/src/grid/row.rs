@@ -2,6 +2,7 @@
 
 use std::cmp::{max, min};
 use std::ops::{Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{ptr, slice};
 
 use serde::{Deserialize, Serialize};
@@ -20,6 +21,21 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// Time of the last write to the row.
+    #[serde(default)]
+    pub timestamp: Timestamp,
+}
+
+/// Time in milliseconds since the UNIX epoch.
+///
+/// A timestamp of `0` is used for rows which have not been written to.
+pub type Timestamp = u64;
+
+/// Current time as a row timestamp.
+pub fn timestamp_now() -> Timestamp {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    elapsed.as_millis() as Timestamp
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
@@ -50,7 +66,7 @@ impl<T: Clone + Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row { inner, occ: 0, timestamp: 0 }
     }
 
     /// Increase the number of columns in the row.
@@ -109,6 +125,7 @@ impl<T: Clone + Default> Row<T> {
         }
 
         self.occ = 0;
+        self.timestamp = 0;
     }
 }
 
@@ -116,7 +133,7 @@ impl<T: Clone + Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row { inner: vec, occ, timestamp: 0 }
     }
 
     #[inline]
@@ -0,0 +1,559 @@
+//! Scrollback history stored in a temporary file.
+
+use std::cell::Cell;
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::{env, process};
+
+use log::error;
+use once_cell::unsync::OnceCell;
+use parking_lot::Mutex;
+
+use crate::grid::compressed::{CompressedLine, CompressedRow};
+use crate::grid::{GridCell, Row, Timestamp};
+
+/// Number of lines stored in a single page of the history file.
+pub const PAGE_LINES: usize = 256;
+
+/// Counter for creating unique history file names.
+static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Temporary file which is removed once it is dropped.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl SpillFile {
+    /// Create a new file in the runtime directory.
+    fn new() -> io::Result<Self> {
+        let id = FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let file_name = format!("Alacritty-{}-{}.history", process::id(), id);
+        let path = dirs::runtime_dir().unwrap_or_else(env::temp_dir).join(file_name);
+
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(&path)?;
+
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    /// Append data to the end of the file, returning its offset.
+    fn append(&self, data: &[u8]) -> io::Result<u64> {
+        let mut file = self.file.lock();
+        let offset = file.seek(SeekFrom::End(0))?;
+        file.write_all(data)?;
+        Ok(offset)
+    }
+
+    /// Overwrite data starting at `offset`.
+    fn write_at(&self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let mut file = self.file.lock();
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(data)
+    }
+
+    /// Read `len` bytes starting at `offset`.
+    fn read(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut file = self.file.lock();
+        file.seek(SeekFrom::Start(offset))?;
+
+        let mut data = vec![0; len];
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+
+    /// Current size of the file.
+    fn len(&self) -> io::Result<u64> {
+        Ok(self.file.lock().metadata()?.len())
+    }
+
+    /// Remove all data after `len`.
+    fn truncate(&self, len: u64) -> io::Result<()> {
+        self.file.lock().set_len(len)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Region of the history file storing a page.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Slot {
+    offset: u64,
+
+    /// Number of bytes used by the page.
+    len: usize,
+
+    /// Number of bytes available for reuse once the page is removed.
+    capacity: usize,
+}
+
+impl Slot {
+    #[inline]
+    fn end(&self) -> u64 {
+        self.offset + self.capacity as u64
+    }
+}
+
+/// A block of lines in the history file.
+#[derive(Clone, Debug)]
+struct Page<T> {
+    /// Location of the page in the history file.
+    ///
+    /// This is `None` for pages which have not been written to the file yet.
+    location: Option<Slot>,
+
+    /// Lines of the page, populated when any of them is accessed.
+    lines: OnceCell<Box<[CompressedLine<T>]>>,
+}
+
+/// History lines stored in a file.
+///
+/// Lines are written to the file in pages of [`PAGE_LINES`] lines and read back one page at a
+/// time. Just like [`super::compressed::CompressedRows`], lines are indexed from the newest to
+/// the oldest line.
+#[derive(Clone, Debug)]
+pub struct SpilledRows<T> {
+    /// File storing the pages, shared with all clones of this buffer.
+    file: Arc<SpillFile>,
+
+    /// Pages ordered from the oldest to the newest line.
+    pages: VecDeque<Page<T>>,
+
+    /// Number of lines removed from the oldest page.
+    skip: usize,
+
+    /// Range of pages which might currently be loaded.
+    loaded: Cell<Option<(usize, usize)>>,
+
+    /// Regions of the file which are no longer used by any page.
+    free: Vec<Slot>,
+}
+
+impl<T> SpilledRows<T> {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            file: Arc::new(SpillFile::new()?),
+            pages: VecDeque::new(),
+            skip: 0,
+            loaded: Cell::new(None),
+            free: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pages.len() * PAGE_LINES - self.skip
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Convert a line index to the page index and the line's offset inside that page.
+    #[inline]
+    fn page_index(&self, index: usize) -> (usize, usize) {
+        debug_assert!(index < self.len());
+
+        let position = self.len() - index - 1 + self.skip;
+        (position / PAGE_LINES, position % PAGE_LINES)
+    }
+
+    /// Remove the oldest lines.
+    pub fn remove_oldest(&mut self, count: usize) {
+        self.skip += min(count, self.len());
+
+        let removed = self.skip / PAGE_LINES;
+        for page in self.pages.drain(..removed) {
+            if let Some(slot) = page.location {
+                self.free.push(slot);
+            }
+        }
+        self.skip %= PAGE_LINES;
+        self.shift_loaded(removed);
+
+        if removed > 0 {
+            self.reclaim();
+        }
+    }
+
+    /// Write data to a free region of the file, or append it if no region is large enough.
+    ///
+    /// Regions are only reused while no clone shares the file, since clones might still read them.
+    fn write_data(&mut self, data: &[u8]) -> io::Result<Slot> {
+        let len = data.len();
+        if Arc::strong_count(&self.file) == 1 {
+            let position = self.free.iter().position(|slot| slot.capacity >= len);
+            if let Some(slot) = position.map(|position| self.free.swap_remove(position)) {
+                let slot = Slot { len, ..slot };
+                if let Err(err) = self.file.write_at(slot.offset, data) {
+                    self.free.push(slot);
+                    return Err(err);
+                }
+                return Ok(slot);
+            }
+        }
+
+        let offset = self.file.append(data)?;
+        Ok(Slot { offset, len, capacity: len })
+    }
+
+    /// Release a region of the file for reuse by other pages.
+    #[inline]
+    fn release(&mut self, slot: Option<Slot>) {
+        if let Some(slot) = slot {
+            self.free.push(slot);
+            self.reclaim();
+        }
+    }
+
+    /// Shrink the file by removing free regions at its end.
+    fn reclaim(&mut self) {
+        if Arc::strong_count(&self.file) != 1 {
+            return;
+        }
+
+        // Remove all data once all lines are gone.
+        if self.pages.is_empty() {
+            self.free.clear();
+            if let Err(err) = self.file.truncate(0) {
+                error!("Unable to truncate scrollback history file: {}", err);
+            }
+            return;
+        }
+
+        let result = self.file.len().and_then(|file_len| {
+            let mut len = file_len;
+            while let Some(position) = self.free.iter().position(|slot| slot.end() == len) {
+                len = self.free.swap_remove(position).offset;
+            }
+
+            if len == file_len {
+                Ok(())
+            } else {
+                self.file.truncate(len)
+            }
+        });
+
+        if let Err(err) = result {
+            error!("Unable to truncate scrollback history file: {}", err);
+        }
+    }
+
+    /// Keep track of pages which have been loaded.
+    #[inline]
+    fn track_loaded(&self, index: usize) {
+        let loaded = match self.loaded.get() {
+            Some((start, end)) => (min(start, index), max(end, index)),
+            None => (index, index),
+        };
+        self.loaded.set(Some(loaded));
+    }
+
+    /// Move the tracked page range after pages were removed at the start of the buffer.
+    #[inline]
+    fn shift_loaded(&mut self, removed: usize) {
+        let loaded = self.loaded.get().and_then(|(start, end)| {
+            end.checked_sub(removed).map(|end| (start.saturating_sub(removed), end))
+        });
+        self.loaded.set(loaded);
+    }
+}
+
+impl<T: GridCell + Clone + Default + PartialEq> SpilledRows<T> {
+    /// Get a row, reading it from the file if necessary.
+    #[inline]
+    pub fn get(&self, index: usize, columns: usize) -> &Row<T> {
+        let (page, line) = self.page_index(index);
+        self.load(page)[line].get(columns)
+    }
+
+    /// Time of the last write to a row, reading it from the file if necessary.
+    #[inline]
+    pub fn timestamp(&self, index: usize) -> Timestamp {
+        let (page, line) = self.page_index(index);
+        self.load(page)[line].timestamp()
+    }
+
+    /// Get a mutable row, reading it from the file if necessary.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize, columns: usize) -> &mut Row<T> {
+        let (page, line) = self.page_index(index);
+        self.load(page);
+
+        let lines = self.pages[page].lines.get_mut().unwrap();
+        lines[line].get_mut(columns)
+    }
+
+    /// Append lines to the file, ordered from the oldest to the newest line.
+    pub fn push_newest(&mut self, lines: impl Iterator<Item = CompressedLine<T>>) {
+        let lines: Box<[CompressedLine<T>]> = lines.collect();
+        debug_assert_eq!(lines.len(), PAGE_LINES);
+
+        let location = self.write(&lines);
+        let loaded = OnceCell::new();
+        if location.is_none() {
+            // Keep the lines in memory if they could not be written to disk.
+            let _ = loaded.set(lines);
+            self.track_loaded(self.pages.len());
+        }
+
+        self.pages.push_back(Page { location, lines: loaded });
+    }
+
+    /// Add a new row at the top of the history.
+    pub fn push_oldest(&mut self, row: &Row<T>) {
+        if self.skip == 0 {
+            let lines = (0..PAGE_LINES).map(|_| CompressedLine::default()).collect();
+            self.pages.push_front(Page { location: None, lines: OnceCell::with_value(lines) });
+            self.skip = PAGE_LINES;
+
+            let loaded = self.loaded.get().map_or((0, 0), |(_, end)| (0, end + 1));
+            self.loaded.set(Some(loaded));
+        }
+
+        self.skip -= 1;
+        self.load(0);
+
+        let page = &mut self.pages[0];
+        page.lines.get_mut().unwrap()[self.skip] = CompressedLine::new(row);
+        let location = page.location.take();
+        self.release(location);
+    }
+
+    /// Remove the newest page, returning its lines ordered from the oldest to the newest line.
+    pub fn pop_newest(&mut self) -> Option<Vec<CompressedLine<T>>> {
+        let index = self.pages.len().checked_sub(1)?;
+        self.load(index);
+
+        let page = self.pages.pop_back()?;
+        self.release(page.location);
+        let mut lines = page.lines.into_inner().unwrap().into_vec();
+
+        if self.pages.is_empty() {
+            lines.drain(..self.skip);
+            self.skip = 0;
+        }
+
+        if let Some((start, end)) = self.loaded.get() {
+            let loaded = (start < index).then(|| (start, min(end, index - 1)));
+            self.loaded.set(loaded);
+        }
+
+        Some(lines)
+    }
+
+    /// Unload all pages outside of `visible`, writing modified pages back to the file.
+    ///
+    /// The `visible` range is specified in line indices, starting at the newest line.
+    pub fn collapse(&mut self, visible: Range<usize>) {
+        let (start, end) = match self.loaded.take() {
+            Some(loaded) => loaded,
+            None => return,
+        };
+
+        let len = self.len();
+        let visible = if visible.start < visible.end && visible.start < len {
+            let first = self.page_index(min(visible.end, len) - 1).0;
+            let last = self.page_index(visible.start).0;
+            first..last + 1
+        } else {
+            0..0
+        };
+
+        let mut remaining = None;
+        for index in start..=min(end, self.pages.len().saturating_sub(1)) {
+            if visible.contains(&index) {
+                if self.pages[index].lines.get().is_some() {
+                    remaining = Some(remaining.map_or((index, index), |(start, _)| (start, index)));
+                }
+            } else {
+                self.unload(index);
+
+                // Keep pages which could not be written to the file in memory.
+                if self.pages[index].lines.get().is_some() {
+                    remaining = Some(remaining.map_or((index, index), |(start, _)| (start, index)));
+                }
+            }
+        }
+
+        self.loaded.set(remaining);
+    }
+
+    /// Get all lines of a page, reading them from the file if necessary.
+    fn load(&self, index: usize) -> &[CompressedLine<T>] {
+        let page = &self.pages[index];
+        page.lines.get_or_init(|| {
+            self.track_loaded(index);
+
+            let (offset, len) = page.location.map_or((0, 0), |slot| (slot.offset, slot.len));
+            let lines = self.file.read(offset, len).and_then(|data| {
+                serde_json::from_slice::<Vec<CompressedRow<T>>>(&data).map_err(io::Error::from)
+            });
+
+            match lines {
+                Ok(lines) => lines.into_iter().map(CompressedLine::from).collect(),
+                Err(err) => {
+                    error!("Unable to read scrollback history file: {}", err);
+                    (0..PAGE_LINES).map(|_| CompressedLine::default()).collect()
+                },
+            }
+        })
+    }
+
+    /// Drop the lines of a page, writing them to the file if they were modified.
+    fn unload(&mut self, index: usize) {
+        let page = &mut self.pages[index];
+        let lines = match page.lines.get_mut() {
+            Some(lines) => lines,
+            None => return,
+        };
+
+        let mut dirty = page.location.is_none();
+        for line in lines.iter_mut() {
+            dirty |= line.collapse();
+        }
+
+        if dirty {
+            let lines = page.lines.take().unwrap();
+            let location = page.location.take();
+            self.release(location);
+
+            match self.write(&lines) {
+                Some(location) => self.pages[index].location = Some(location),
+                None => {
+                    let _ = self.pages[index].lines.set(lines);
+                },
+            }
+        } else {
+            page.lines.take();
+        }
+    }
+
+    /// Write lines to a free region of the file, returning their location.
+    fn write(&mut self, lines: &[CompressedLine<T>]) -> Option<Slot> {
+        let rows: Vec<_> = lines.iter().map(CompressedLine::compressed).collect();
+        let data = serde_json::to_vec(&rows).map_err(io::Error::from);
+
+        match data.and_then(|data| self.write_data(&data)) {
+            Ok(slot) => Some(slot),
+            Err(err) => {
+                error!("Unable to write scrollback history file: {}", err);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::index::Column;
+    use crate::term::cell::Cell;
+
+    fn page(first: usize) -> impl Iterator<Item = CompressedLine<Cell>> {
+        (first..first + PAGE_LINES).map(|i| {
+            let mut row = Row::<Cell>::new(5);
+            row[Column(0)].c = char::from_digit((i % 10) as u32, 10).unwrap();
+            CompressedLine::new(&row)
+        })
+    }
+
+    #[test]
+    fn spill_and_load() {
+        let mut rows = SpilledRows::new().unwrap();
+        rows.push_newest(page(0));
+        rows.push_newest(page(PAGE_LINES));
+
+        assert_eq!(rows.len(), 2 * PAGE_LINES);
+        assert!(rows.pages.iter().all(|page| page.lines.get().is_none()));
+
+        assert_eq!(rows.get(0, 5)[Column(0)].c, char::from_digit(1, 10).unwrap());
+        assert_eq!(rows.get(2 * PAGE_LINES - 1, 5)[Column(0)].c, '0');
+
+        rows.collapse(0..1);
+        assert!(rows.pages[0].lines.get().is_none());
+        assert!(rows.pages[1].lines.get().is_some());
+    }
+
+    #[test]
+    fn modified_page() {
+        let mut rows = SpilledRows::new().unwrap();
+        rows.push_newest(page(0));
+
+        rows.get_mut(3, 5)[Column(0)].c = 'x';
+        rows.collapse(0..0);
+
+        assert!(rows.pages[0].lines.get().is_none());
+        assert_eq!(rows.get(3, 5)[Column(0)].c, 'x');
+    }
+
+    #[test]
+    fn remove_and_push_oldest() {
+        let mut rows = SpilledRows::new().unwrap();
+        rows.push_newest(page(0));
+        rows.push_newest(page(PAGE_LINES));
+
+        rows.remove_oldest(PAGE_LINES + 1);
+        assert_eq!(rows.len(), PAGE_LINES - 1);
+        assert_eq!(rows.pages.len(), 1);
+
+        rows.push_oldest(&Row::new(5));
+        rows.push_oldest(&Row::new(5));
+        assert_eq!(rows.len(), PAGE_LINES + 1);
+        assert_eq!(rows.get(PAGE_LINES, 5)[Column(0)].c, ' ');
+
+        let lines = rows.pop_newest().unwrap();
+        assert_eq!(lines.len(), PAGE_LINES);
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn reuse_file_space() {
+        let mut rows = SpilledRows::new().unwrap();
+        rows.push_newest(page(0));
+        rows.push_newest(page(PAGE_LINES));
+        rows.push_newest(page(2 * PAGE_LINES));
+        let file_len = rows.file.len().unwrap();
+
+        // Modified pages are written back to their previous region.
+        rows.get_mut(3 * PAGE_LINES - 1, 5)[Column(0)].c = 'x';
+        rows.collapse(0..0);
+        assert_eq!(rows.file.len().unwrap(), file_len);
+        assert_eq!(rows.get(3 * PAGE_LINES - 1, 5)[Column(0)].c, 'x');
+
+        // Removed pages at the end of the file shrink it.
+        rows.remove_oldest(PAGE_LINES);
+        rows.pop_newest().unwrap();
+        assert!(rows.file.len().unwrap() < file_len);
+        assert_eq!(rows.free.len(), 1);
+
+        rows.pop_newest().unwrap();
+        assert_eq!(rows.file.len().unwrap(), 0);
+    }
+
+    #[test]
+    fn file_removed_on_drop() {
+        let rows = SpilledRows::<Cell>::new().unwrap();
+        let path = rows.file.path.clone();
+        assert!(path.exists());
+
+        drop(rows);
+        assert!(!path.exists());
+    }
+}
@@ -1,11 +1,13 @@
-use std::cmp::{max, PartialEq};
+use std::cmp::{max, min, PartialEq};
+use std::io;
 use std::mem;
 use std::mem::MaybeUninit;
-use std::ops::{Index, IndexMut};
+use std::ops::{Index, IndexMut, Range};
 
 use serde::{Deserialize, Serialize};
 
-use super::Row;
+use super::compressed::CompressedRows;
+use super::{GridCell, Row, Timestamp};
 use crate::index::Line;
 
 /// Maximum number of buffered lines outside of the grid for performance optimization.
@@ -24,6 +26,11 @@ const MAX_CACHE_SIZE: usize = 1_000;
 /// implementation is provided. Anything from [`Vec`] that should be exposed must be done so
 /// manually.
 ///
+/// History lines can optionally be moved out of the ring buffer into a compressed representation,
+/// see [`Storage::compress`]. Compressed lines are always the oldest lines in the storage and are
+/// expanded again whenever they are indexed. The oldest compressed lines can further be moved into
+/// a temporary file, see [`Storage::spill`].
+///
 /// [`slice::rotate_left`]: https://doc.rust-lang.org/std/primitive.slice.html#method.rotate_left
 /// [`Deref`]: std::ops::Deref
 /// [`zero`]: #structfield.zero
@@ -41,13 +48,17 @@ pub struct Storage<T> {
     /// Number of visible lines.
     visible_lines: usize,
 
-    /// Total number of lines currently active in the terminal (scrollback + visible)
+    /// Total number of uncompressed lines currently active in the terminal (scrollback + visible)
     ///
     /// Shrinking this length allows reducing the number of lines in the scrollback buffer without
     /// having to truncate the raw `inner` buffer.
     /// As long as `len` is bigger than `inner`, it is also possible to grow the scrollback buffer
     /// without any additional insertions.
     len: usize,
+
+    /// History lines above `inner` which have been compressed.
+    #[serde(skip)]
+    compressed: CompressedRows<T>,
 }
 
 impl<T: PartialEq> PartialEq for Storage<T> {
@@ -55,6 +66,8 @@ impl<T: PartialEq> PartialEq for Storage<T> {
         // Both storage buffers need to be truncated and zeroed.
         assert_eq!(self.zero, 0);
         assert_eq!(other.zero, 0);
+        assert!(self.compressed.is_empty());
+        assert!(other.compressed.is_empty());
 
         self.inner == other.inner && self.len == other.len
     }
@@ -70,14 +83,16 @@ impl<T> Storage<T> {
         let mut inner = Vec::with_capacity(visible_lines);
         inner.resize_with(visible_lines, || Row::new(columns));
 
-        Storage { inner, zero: 0, visible_lines, len: visible_lines }
+        let compressed = CompressedRows::new(columns);
+
+        Storage { inner, zero: 0, visible_lines, len: visible_lines, compressed }
     }
 
     /// Increase the number of lines in the buffer.
     #[inline]
     pub fn grow_visible_lines(&mut self, next: usize)
     where
-        T: Clone + Default,
+        T: GridCell + Clone + Default + PartialEq,
     {
         // Number of lines the buffer needs to grow.
         let growage = next - self.visible_lines;
@@ -85,6 +100,15 @@ impl<T> Storage<T> {
         let columns = self[Line(0)].len();
         self.initialize(growage, columns);
 
+        // Move the newest compressed lines into the new uncompressed lines, to make sure the new
+        // lines are still the oldest lines in the buffer.
+        let thawed = min(growage, self.compressed.len());
+        for i in 0..thawed {
+            let index = self.ring_index(self.len - growage + i);
+            self.inner[index] = self.compressed.pop_newest().unwrap();
+            self.compressed.push_oldest(&Row::new(columns));
+        }
+
         // Update visible lines.
         self.visible_lines = next;
     }
@@ -94,15 +118,26 @@ impl<T> Storage<T> {
     pub fn shrink_visible_lines(&mut self, next: usize) {
         // Shrink the size without removing any lines.
         let shrinkage = self.visible_lines - next;
-        self.shrink_lines(shrinkage);
+        self.shrink_uncompressed_lines(shrinkage);
 
         // Update visible lines.
         self.visible_lines = next;
     }
 
     /// Shrink the number of lines in the buffer.
+    ///
+    /// This will always remove the oldest lines first.
     #[inline]
     pub fn shrink_lines(&mut self, shrinkage: usize) {
+        let compressed = min(shrinkage, self.compressed.len());
+        self.compressed.remove_oldest(compressed);
+
+        self.shrink_uncompressed_lines(shrinkage - compressed);
+    }
+
+    /// Shrink the number of uncompressed lines in the buffer.
+    #[inline]
+    fn shrink_uncompressed_lines(&mut self, shrinkage: usize) {
         self.len -= shrinkage;
 
         // Free memory.
@@ -137,7 +172,121 @@ impl<T> Storage<T> {
 
     #[inline]
     pub fn len(&self) -> usize {
-        self.len
+        self.len + self.compressed.len()
+    }
+
+    /// Number of compressed lines in the buffer.
+    #[inline]
+    pub fn compressed_len(&self) -> usize {
+        self.compressed.len()
+    }
+
+    /// Compress all history lines exceeding the total of `uncompressed_lines`.
+    ///
+    /// Visible lines are never compressed.
+    pub fn compress(&mut self, uncompressed_lines: usize)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        let uncompressed_lines = max(uncompressed_lines, self.visible_lines);
+        while self.len > uncompressed_lines {
+            let index = self.ring_index(self.len - 1);
+            self.compressed.push_newest(&self.inner[index]);
+            self.len -= 1;
+        }
+
+        // Free memory.
+        if self.inner.len() > self.len + MAX_CACHE_SIZE {
+            self.truncate();
+        }
+    }
+
+    /// Move all compressed lines back into the uncompressed buffer.
+    pub fn decompress(&mut self)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        if self.compressed.is_empty() {
+            return;
+        }
+
+        self.truncate();
+
+        self.inner.reserve(self.compressed.len());
+        while let Some(row) = self.compressed.pop_newest() {
+            self.inner.push(row);
+            self.len += 1;
+        }
+    }
+
+    /// Start moving compressed lines into a temporary history file.
+    #[inline]
+    pub fn enable_spill(&mut self) -> io::Result<()> {
+        self.compressed.enable_spill()
+    }
+
+    /// Move all lines from the history file back into memory and remove the file.
+    #[inline]
+    pub fn disable_spill(&mut self)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        self.compressed.disable_spill();
+    }
+
+    /// Check if compressed lines are moved into a history file.
+    #[inline]
+    pub fn is_spilling(&self) -> bool {
+        self.compressed.is_spilling()
+    }
+
+    /// Move compressed lines exceeding the total of `memory_lines` into the history file.
+    ///
+    /// This has no effect unless a history file has been created using [`Storage::enable_spill`].
+    #[inline]
+    pub fn spill(&mut self, memory_lines: usize)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        self.compressed.spill(memory_lines.saturating_sub(self.len));
+    }
+
+    /// Remove up to `count` of the oldest compressed lines.
+    ///
+    /// Returns the number of lines which have been removed.
+    #[inline]
+    pub fn remove_compressed(&mut self, count: usize) -> usize {
+        let count = min(count, self.compressed.len());
+        self.compressed.remove_oldest(count);
+        count
+    }
+
+    /// Drop the expanded version of compressed lines.
+    ///
+    /// Lines inside the `visible` range will stay expanded. The range is relative to the bottom of
+    /// the buffer, so the bottommost visible line is at index `0`.
+    #[inline]
+    pub fn collapse_compressed(&mut self, visible: Range<usize>)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        let start = visible.start.saturating_sub(self.len);
+        let end = visible.end.saturating_sub(self.len);
+        self.compressed.collapse(start..end);
+    }
+
+    /// Time of the last write to a row, without expanding compressed rows.
+    #[inline]
+    pub fn timestamp(&self, index: Line) -> Timestamp
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        let positive = self.positive_index(index);
+        if positive >= self.len {
+            return self.compressed.timestamp(positive - self.len);
+        }
+
+        self.inner[self.ring_index(positive)].timestamp
     }
 
     /// Swap implementation for Row<T>.
@@ -149,7 +298,8 @@ impl<T> Storage<T> {
     /// instructions. This implementation achieves the swap in only 8 movups
     /// instructions.
     pub fn swap(&mut self, a: Line, b: Line) {
-        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * 4);
+        const QWORDS: usize = mem::size_of::<Row<()>>() / mem::size_of::<usize>();
+        debug_assert_eq!(mem::size_of::<Row<T>>(), mem::size_of::<usize>() * QWORDS);
 
         let a = self.compute_index(a);
         let b = self.compute_index(b);
@@ -165,7 +315,7 @@ impl<T> Storage<T> {
             //
             // The optimizer unrolls this loop and vectorizes it.
             let mut tmp: MaybeUninit<usize>;
-            for i in 0..4 {
+            for i in 0..QWORDS as isize {
                 tmp = *a_ptr.offset(i);
                 *a_ptr.offset(i) = *b_ptr.offset(i);
                 *b_ptr.offset(i) = tmp;
@@ -193,14 +343,23 @@ impl<T> Storage<T> {
     }
 
     /// Update the raw storage buffer.
+    ///
+    /// Compressed lines are kept above the new buffer and expanded to its width.
     #[inline]
-    pub fn replace_inner(&mut self, vec: Vec<Row<T>>) {
+    pub fn replace_inner(&mut self, vec: Vec<Row<T>>)
+    where
+        T: GridCell + Clone + Default + PartialEq,
+    {
+        if let Some(row) = vec.first() {
+            self.compressed.set_columns(row.len());
+        }
+
         self.len = vec.len();
         self.inner = vec;
         self.zero = 0;
     }
 
-    /// Remove all rows from storage.
+    /// Remove all uncompressed rows from storage.
     #[inline]
     pub fn take_all(&mut self) -> Vec<Row<T>> {
         self.truncate();
@@ -216,12 +375,24 @@ impl<T> Storage<T> {
     /// Compute actual index in underlying storage given the requested index.
     #[inline]
     fn compute_index(&self, requested: Line) -> usize {
-        debug_assert!(requested.0 < self.visible_lines as i32);
-
-        let positive = -(requested - self.visible_lines).0 as usize - 1;
+        let positive = self.positive_index(requested);
 
         debug_assert!(positive < self.len);
 
+        self.ring_index(positive)
+    }
+
+    /// Compute the offset of a line from the bottom of the buffer.
+    #[inline]
+    fn positive_index(&self, requested: Line) -> usize {
+        debug_assert!(requested.0 < self.visible_lines as i32);
+
+        -(requested - self.visible_lines).0 as usize - 1
+    }
+
+    /// Compute actual index in underlying storage given the offset from the bottom of the buffer.
+    #[inline]
+    fn ring_index(&self, positive: usize) -> usize {
         let zeroed = self.zero + positive;
 
         // Use if/else instead of remainder here to improve performance.
@@ -247,20 +418,30 @@ impl<T> Storage<T> {
     }
 }
 
-impl<T> Index<Line> for Storage<T> {
+impl<T: GridCell + Clone + Default + PartialEq> Index<Line> for Storage<T> {
     type Output = Row<T>;
 
     #[inline]
     fn index(&self, index: Line) -> &Self::Output {
-        let index = self.compute_index(index);
+        let positive = self.positive_index(index);
+        if positive >= self.len {
+            return self.compressed.get(positive - self.len);
+        }
+
+        let index = self.ring_index(positive);
         &self.inner[index]
     }
 }
 
-impl<T> IndexMut<Line> for Storage<T> {
+impl<T: GridCell + Clone + Default + PartialEq> IndexMut<Line> for Storage<T> {
     #[inline]
     fn index_mut(&mut self, index: Line) -> &mut Self::Output {
-        let index = self.compute_index(index);
+        let positive = self.positive_index(index);
+        if positive >= self.len {
+            return self.compressed.get_mut(positive - self.len);
+        }
+
+        let index = self.ring_index(positive);
         &mut self.inner[index]
     }
 }
@@ -289,6 +470,13 @@ mod tests {
         fn flags_mut(&mut self) -> &mut Flags {
             unimplemented!();
         }
+        fn character(&self) -> char {
+            *self
+        }
+
+        fn set_character(&mut self, c: char) {
+            *self = c;
+        }
     }
 
     #[test]
@@ -356,6 +544,7 @@ mod tests {
             zero: 0,
             visible_lines: 3,
             len: 3,
+            compressed: Default::default(),
         };
 
         // Grow buffer.
@@ -367,6 +556,7 @@ mod tests {
             zero: 0,
             visible_lines: 4,
             len: 4,
+            compressed: Default::default(),
         };
         expected.inner.append(&mut vec![filled_row('\0'); MAX_CACHE_SIZE]);
 
@@ -397,6 +587,7 @@ mod tests {
             zero: 1,
             visible_lines: 3,
             len: 3,
+            compressed: Default::default(),
         };
 
         // Grow buffer.
@@ -408,6 +599,7 @@ mod tests {
             zero: 0,
             visible_lines: 4,
             len: 4,
+            compressed: Default::default(),
         };
         expected.inner.append(&mut vec![filled_row('\0'); MAX_CACHE_SIZE]);
 
@@ -435,6 +627,7 @@ mod tests {
             zero: 1,
             visible_lines: 3,
             len: 3,
+            compressed: Default::default(),
         };
 
         // Shrink buffer.
@@ -446,6 +639,7 @@ mod tests {
             zero: 1,
             visible_lines: 2,
             len: 2,
+            compressed: Default::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -471,6 +665,7 @@ mod tests {
             zero: 0,
             visible_lines: 3,
             len: 3,
+            compressed: Default::default(),
         };
 
         // Shrink buffer.
@@ -482,6 +677,7 @@ mod tests {
             zero: 0,
             visible_lines: 2,
             len: 2,
+            compressed: Default::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -520,6 +716,7 @@ mod tests {
             zero: 2,
             visible_lines: 6,
             len: 6,
+            compressed: Default::default(),
         };
 
         // Shrink buffer.
@@ -538,6 +735,7 @@ mod tests {
             zero: 2,
             visible_lines: 2,
             len: 2,
+            compressed: Default::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -572,6 +770,7 @@ mod tests {
             zero: 2,
             visible_lines: 1,
             len: 2,
+            compressed: Default::default(),
         };
 
         // Truncate buffer.
@@ -583,6 +782,7 @@ mod tests {
             zero: 0,
             visible_lines: 1,
             len: 2,
+            compressed: Default::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -607,6 +807,7 @@ mod tests {
             zero: 2,
             visible_lines: 1,
             len: 2,
+            compressed: Default::default(),
         };
 
         // Truncate buffer.
@@ -618,6 +819,7 @@ mod tests {
             zero: 0,
             visible_lines: 1,
             len: 2,
+            compressed: Default::default(),
         };
         assert_eq!(storage.visible_lines, expected.visible_lines);
         assert_eq!(storage.inner, expected.inner);
@@ -664,6 +866,7 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 6,
+            compressed: Default::default(),
         };
 
         // Shrink buffer.
@@ -682,6 +885,7 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 3,
+            compressed: Default::default(),
         };
         assert_eq!(storage.inner, shrinking_expected.inner);
         assert_eq!(storage.zero, shrinking_expected.zero);
@@ -703,6 +907,7 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 4,
+            compressed: Default::default(),
         };
 
         assert_eq!(storage.inner, growing_expected.inner);
@@ -725,6 +930,7 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 6,
+            compressed: Default::default(),
         };
 
         // Initialize additional lines.
@@ -742,7 +948,13 @@ mod tests {
         ];
         let expected_init_size = std::cmp::max(init_size, MAX_CACHE_SIZE);
         expected_inner.append(&mut vec![filled_row('\0'); expected_init_size]);
-        let expected_storage = Storage { inner: expected_inner, zero: 0, visible_lines: 0, len: 9 };
+        let expected_storage = Storage {
+            inner: expected_inner,
+            zero: 0,
+            visible_lines: 0,
+            len: 9,
+            compressed: Default::default(),
+        };
 
         assert_eq!(storage.len, expected_storage.len);
         assert_eq!(storage.zero, expected_storage.zero);
@@ -756,6 +968,7 @@ mod tests {
             zero: 2,
             visible_lines: 0,
             len: 3,
+            compressed: Default::default(),
         };
 
         storage.rotate(2);
@@ -20,6 +20,13 @@ impl GridCell for usize {
     fn flags_mut(&mut self) -> &mut Flags {
         unimplemented!();
     }
+    fn character(&self) -> char {
+        unimplemented!();
+    }
+
+    fn set_character(&mut self, _c: char) {
+        unimplemented!();
+    }
 }
 
 // Scroll up moves lines upward.
@@ -348,6 +355,72 @@ fn shrink_reflow_disabled() {
     assert_eq!(grid[Line(0)][Column(1)], cell('2'));
 }
 
+#[test]
+fn scroll_up_compressed() {
+    let mut grid = Grid::<Cell>::new(2, 3, 5);
+    grid.update_compression(Some(1));
+
+    for c in '0'..='5' {
+        grid[Line(1)][Column(0)] = cell(c);
+        grid.scroll_up(&(Line(0)..Line(2)), 1);
+    }
+
+    assert_eq!(grid.total_lines(), 7);
+    assert_eq!(grid.raw.compressed_len(), 4);
+    for (i, c) in ('0'..='5').enumerate() {
+        assert_eq!(grid[Line(i as i32 - 5)][Column(0)], cell(c));
+    }
+
+    grid.resize(true, 2, 2);
+
+    assert_eq!(grid.total_lines(), 7);
+    assert_eq!(grid[Line(-5)].len(), 2);
+    for (i, c) in ('0'..='5').enumerate() {
+        assert_eq!(grid[Line(i as i32 - 5)][Column(0)], cell(c));
+    }
+
+    grid.update_compression(None);
+
+    assert_eq!(grid.raw.compressed_len(), 0);
+    for (i, c) in ('0'..='5').enumerate() {
+        assert_eq!(grid[Line(i as i32 - 5)][Column(0)], cell(c));
+    }
+}
+
+#[test]
+fn scroll_up_spilled() {
+    let mut grid = Grid::<Cell>::new(2, 3, i32::MAX as usize);
+    grid.enable_spill(10).unwrap();
+
+    let digit = |i: usize| char::from_digit((i % 10) as u32, 10).unwrap();
+    for i in 0..1000 {
+        grid[Line(1)][Column(0)] = cell(digit(i));
+        grid.scroll_up(&(Line(0)..Line(2)), 1);
+    }
+
+    assert_eq!(grid.total_lines(), 1002);
+    assert!(grid.raw.compressed_len() > 900);
+    for i in 0..1000 {
+        assert_eq!(grid[Line(i as i32 - 999)][Column(0)], cell(digit(i)));
+    }
+
+    grid.resize(true, 4, 3);
+
+    assert_eq!(grid.total_lines(), 1002);
+    for i in 0..1000 {
+        assert_eq!(grid[Line(i as i32 - 997)][Column(0)], cell(digit(i)));
+    }
+
+    grid.update_history(100);
+    grid.disable_spill();
+
+    assert_eq!(grid.total_lines(), 104);
+    assert_eq!(grid.raw.compressed_len(), 0);
+    for i in 900..1000 {
+        assert_eq!(grid[Line(i as i32 - 997)][Column(0)], cell(digit(i)));
+    }
+}
+
 // https://github.com/rust-lang/rust-clippy/pull/6375
 #[allow(clippy::all)]
 fn cell(c: char) -> Cell {
@@ -7,13 +7,14 @@
 
 use std::cmp::min;
 use std::mem;
-use std::ops::{Bound, Range, RangeBounds};
+use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
 
 use crate::ansi::CursorShape;
 use crate::grid::{Dimensions, GridCell, Indexed};
 use crate::index::{Boundary, Column, Line, Point, Side};
 use crate::term::cell::{Cell, Flags};
 use crate::term::Term;
+use crate::vi_mode::TextObject;
 
 /// A Point and side within that point.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -382,6 +383,90 @@ impl Selection {
     }
 }
 
+/// Quotes which can delimit a semantic selection level.
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+/// Brackets which can delimit a semantic selection level.
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// Maximum number of bracket pairs inspected while looking for an enclosing pair.
+const MAX_BRACKET_PAIRS: usize = 100;
+
+/// Grow a range to the next larger semantic unit containing it.
+///
+/// The units are the word, the text inside and including the enclosing quotes or brackets, the
+/// wrapped line and the paragraph. Command output is not a unit, since the terminal doesn't know
+/// where prompts start. Returns `None` if no unit is larger than `range`.
+pub fn expand_semantic<T>(
+    term: &Term<T>,
+    range: &RangeInclusive<Point>,
+) -> Option<RangeInclusive<Point>> {
+    let (start, end) = (*range.start(), *range.end());
+
+    let mut candidates = Vec::new();
+    candidates.extend(TextObject::Word.range(term, start, true));
+
+    for quote in QUOTES {
+        for inner in [true, false] {
+            candidates.extend(TextObject::Quote(quote).range(term, start, inner));
+        }
+    }
+
+    for (open, close) in BRACKETS {
+        candidates.extend(enclosing_brackets(term, range, open, close));
+    }
+
+    candidates.push(term.line_search_left(start)..=term.line_search_right(end));
+
+    let paragraph_start = TextObject::Paragraph.range(term, start, true).map(|r| *r.start());
+    let paragraph_end = TextObject::Paragraph.range(term, end, true).map(|r| *r.end());
+    if let (Some(paragraph_start), Some(paragraph_end)) = (paragraph_start, paragraph_end) {
+        candidates.push(paragraph_start..=paragraph_end);
+    }
+
+    // Pick the smallest unit which is strictly larger than the current range.
+    let columns = term.columns() as i64;
+    let size = |range: &RangeInclusive<Point>| {
+        let lines = (range.end().line - range.start().line).0 as i64;
+        lines * columns + range.end().column.0 as i64 - range.start().column.0 as i64
+    };
+    candidates
+        .into_iter()
+        .filter(|candidate| {
+            *candidate.start() <= start && *candidate.end() >= end && candidate != range
+        })
+        .min_by_key(size)
+}
+
+/// Inner and outer range of the innermost bracket pair containing `range`.
+fn enclosing_brackets<T>(
+    term: &Term<T>,
+    range: &RangeInclusive<Point>,
+    open: char,
+    close: char,
+) -> Vec<RangeInclusive<Point>> {
+    let mut point = *range.start();
+    for _ in 0..MAX_BRACKET_PAIRS {
+        let outer = match TextObject::Bracket(open, close).range(term, point, false) {
+            Some(outer) => outer,
+            None => break,
+        };
+
+        if outer.start() <= range.start() && outer.end() >= range.end() {
+            let inner = TextObject::Bracket(open, close).range(term, point, true);
+            return inner.into_iter().chain(Some(outer)).collect();
+        }
+
+        // Continue with the brackets before this pair.
+        if *outer.start() <= Point::new(term.topmost_line(), Column(0)) {
+            break;
+        }
+        point = outer.start().sub(term, Boundary::Grid, 1);
+    }
+
+    Vec::new()
+}
+
 /// Tests for selection.
 ///
 /// There are comments on all of the tests describing the selection. Pictograms
@@ -397,7 +482,7 @@ mod tests {
 
     use crate::config::Config;
     use crate::index::{Column, Point, Side};
-    use crate::term::test::TermSize;
+    use crate::term::test::{mock_term, TermSize};
     use crate::term::Term;
 
     fn term(height: usize, width: usize) -> Term<()> {
@@ -663,4 +748,22 @@ mod tests {
         assert!(!selection.intersects_range(..=Line(2)));
         assert!(!selection.intersects_range(Line(7)..=Line(8)));
     }
+
+    #[test]
+    fn expand_semantic_levels() {
+        let term = mock_term("ab foo(bar \"baz qux\") end\r\nnext");
+        let point = |column| Point::new(Line(0), Column(column));
+
+        let mut range = point(12)..=point(12);
+        let mut levels = Vec::new();
+        while let Some(expanded) = expand_semantic(&term, &range) {
+            levels.push((expanded.start().column.0, expanded.end().column.0));
+            range = expanded;
+        }
+
+        assert_eq!(levels, [(12, 14), (12, 18), (11, 19), (7, 19), (6, 20), (0, 24), (0, 24)]);
+
+        // The last level is the paragraph, which includes the next line.
+        assert_eq!(range, point(0)..=Point::new(Line(1), Column(24)));
+    }
 }
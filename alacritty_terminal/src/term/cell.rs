@@ -231,6 +231,16 @@ impl GridCell for Cell {
         &mut self.flags
     }
 
+    #[inline]
+    fn character(&self) -> char {
+        self.c
+    }
+
+    #[inline]
+    fn set_character(&mut self, c: char) {
+        self.c = c;
+    }
+
     #[inline]
     fn reset(&mut self, template: &Self) {
         *self = Cell { bg: template.bg, ..Cell::default() };
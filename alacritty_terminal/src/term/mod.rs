@@ -5,20 +5,20 @@ use std::sync::Arc;
 use std::{cmp, mem, ptr, slice, str};
 
 use bitflags::bitflags;
-use log::{debug, trace};
+use log::{debug, error, trace};
 use unicode_width::UnicodeWidthChar;
 
 use crate::ansi::{
     self, Attr, CharsetIndex, Color, CursorShape, CursorStyle, Handler, NamedColor, StandardCharset,
 };
-use crate::config::Config;
+use crate::config::{Config, HistoryBackend, Scrolling};
 use crate::event::{Event, EventListener};
-use crate::grid::{Dimensions, Grid, GridIterator, Scroll};
+use crate::grid::{self, Dimensions, Grid, GridIterator, Scroll, Timestamp};
 use crate::index::{self, Boundary, Column, Direction, Line, Point, Side};
 use crate::selection::{Selection, SelectionRange, SelectionType};
 use crate::term::cell::{Cell, Flags, Hyperlink, LineLength};
 use crate::term::color::{Colors, Rgb};
-use crate::vi_mode::{ViModeCursor, ViMotion};
+use crate::vi_mode::{self, ViMarks, ViModeCursor, ViMotion};
 
 pub mod cell;
 pub mod color;
@@ -241,6 +241,13 @@ impl TermDamageState {
     }
 }
 
+/// Position of the terminal content at a point in time, see [`Term::scroll_marker`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScrollMarker {
+    grid_swaps: u64,
+    scrolled_lines: u64,
+}
+
 pub struct Term<T> {
     /// Terminal focus controlling the cursor shape.
     pub is_focused: bool,
@@ -248,6 +255,12 @@ pub struct Term<T> {
     /// Cursor for keyboard selection.
     pub vi_mode_cursor: ViModeCursor,
 
+    /// Vi mode marks and jump list of the active grid.
+    pub vi_marks: ViMarks,
+
+    /// Vi mode marks and jump list of the inactive grid.
+    inactive_vi_marks: ViMarks,
+
     pub selection: Option<Selection>,
 
     /// Currently active grid.
@@ -262,6 +275,9 @@ pub struct Term<T> {
     /// primary grid. Otherwise it is the alternate screen buffer.
     inactive_grid: Grid<Cell>,
 
+    /// Number of times the active grid has been swapped.
+    grid_swaps: u64,
+
     /// Index into `charsets`, pointing to what ASCII is currently being mapped to.
     active_charset: CharsetIndex,
 
@@ -302,6 +318,12 @@ pub struct Term<T> {
 
     /// Information about damaged cells.
     damage: TermDamageState,
+
+    /// Timestamp for rows written by the PTY.
+    ///
+    /// This is only refreshed on line feeds and carriage returns, to avoid querying the system
+    /// time for every character.
+    timestamp: Timestamp,
 }
 
 impl<T> Term<T> {
@@ -331,8 +353,8 @@ impl<T> Term<T> {
         let num_cols = dimensions.columns();
         let num_lines = dimensions.screen_lines();
 
-        let history_size = config.scrolling.history() as usize;
-        let grid = Grid::new(num_lines, num_cols, history_size);
+        let mut grid = Grid::new(num_lines, num_cols, 0);
+        configure_history(&mut grid, config.scrolling);
         let alt = Grid::new(num_lines, num_cols, 0);
 
         let tabs = TabStops::new(grid.columns());
@@ -345,8 +367,11 @@ impl<T> Term<T> {
         Term {
             grid,
             inactive_grid: alt,
+            grid_swaps: 0,
             active_charset: Default::default(),
             vi_mode_cursor: Default::default(),
+            vi_marks: Default::default(),
+            inactive_vi_marks: Default::default(),
             tabs,
             mode: Default::default(),
             scroll_region,
@@ -361,6 +386,7 @@ impl<T> Term<T> {
             title_stack: Vec::new(),
             selection: None,
             damage,
+            timestamp: grid::timestamp_now(),
         }
     }
 
@@ -453,11 +479,12 @@ impl<T> Term<T> {
 
         self.event_proxy.send_event(title_event);
 
-        if self.mode.contains(TermMode::ALT_SCREEN) {
-            self.inactive_grid.update_history(config.scrolling.history() as usize);
+        let primary_grid = if self.mode.contains(TermMode::ALT_SCREEN) {
+            &mut self.inactive_grid
         } else {
-            self.grid.update_history(config.scrolling.history() as usize);
-        }
+            &mut self.grid
+        };
+        configure_history(primary_grid, config.scrolling);
 
         // Damage everything on config updates.
         self.mark_fully_damaged();
@@ -584,6 +611,24 @@ impl<T> Term<T> {
         &self.grid
     }
 
+    /// Position of the active grid's content, to follow it while the terminal is unlocked.
+    pub fn scroll_marker(&self) -> ScrollMarker {
+        ScrollMarker { grid_swaps: self.grid_swaps, scrolled_lines: self.grid.scrolled_lines() }
+    }
+
+    /// Number of lines the content has moved up since `marker` was taken.
+    ///
+    /// Returns `None` if the active grid has been swapped since then, since positions taken from
+    /// the other grid no longer refer to the same content.
+    pub fn scrolled_since(&self, marker: ScrollMarker) -> Option<i32> {
+        if marker.grid_swaps != self.grid_swaps {
+            return None;
+        }
+
+        let delta = self.grid.scrolled_lines().checked_sub(marker.scrolled_lines)?;
+        i32::try_from(delta).ok()
+    }
+
     /// Mutable access to the raw grid data structure.
     pub fn grid_mut(&mut self) -> &mut Grid<Cell> {
         &mut self.grid
@@ -626,6 +671,10 @@ impl<T> Term<T> {
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
 
+        // Move marks with the content.
+        self.vi_marks.shift(&self.grid, delta);
+        self.inactive_vi_marks.shift(&self.inactive_grid, 0);
+
         // Clamp vi cursor to viewport.
         let vi_point = self.vi_mode_cursor.point;
         let viewport_top = Line(-(self.grid.display_offset() as i32));
@@ -658,9 +707,12 @@ impl<T> Term<T> {
 
             // Reset alternate screen contents.
             self.inactive_grid.reset_region(..);
+            self.inactive_vi_marks = Default::default();
         }
 
         mem::swap(&mut self.grid, &mut self.inactive_grid);
+        mem::swap(&mut self.vi_marks, &mut self.inactive_vi_marks);
+        self.grid_swaps += 1;
         self.mode ^= TermMode::ALT_SCREEN;
         self.selection = None;
         self.mark_fully_damaged();
@@ -689,6 +741,9 @@ impl<T> Term<T> {
             *line = cmp::min(*line + lines, region.end - 1);
         }
 
+        // Scroll vi mode marks.
+        self.vi_marks.rotate(&self.grid, &region, -(lines as i32));
+
         // Scroll between origin and bottom
         self.grid.scroll_down(&region, lines);
         self.mark_fully_damaged();
@@ -718,6 +773,9 @@ impl<T> Term<T> {
         if (top <= *line) && region.end > *line {
             *line = cmp::max(*line - lines, top);
         }
+
+        // Scroll vi mode marks.
+        self.vi_marks.rotate(&self.grid, &region, lines as i32);
         self.mark_fully_damaged();
     }
 
@@ -778,8 +836,33 @@ impl<T> Term<T> {
         }
 
         // Move cursor.
+        let old_point = self.vi_mode_cursor.point;
         self.vi_mode_cursor = self.vi_mode_cursor.motion(self, motion);
         self.vi_mode_recompute_selection();
+
+        // Record long distance motions in the jump list.
+        let is_jump = matches!(
+            motion,
+            ViMotion::Bracket
+                | ViMotion::ParagraphUp
+                | ViMotion::ParagraphDown
+                | ViMotion::Top
+                | ViMotion::Bottom
+        );
+        if is_jump && self.vi_mode_cursor.point != old_point {
+            self.vi_marks.push_jump(old_point);
+        }
+    }
+
+    /// Move vi cursor to the first occupied cell of a line, recording the jump in the jump list.
+    pub fn vi_goto_line(&mut self, line: Line)
+    where
+        T: EventListener,
+    {
+        let line = cmp::max(cmp::min(line, self.bottommost_line()), self.topmost_line());
+        let point = vi_mode::first_occupied_in_line(self, line)
+            .unwrap_or_else(|| Point::new(line, Column(0)));
+        self.vi_jump(point);
     }
 
     /// Move vi cursor to a point in the grid.
@@ -797,6 +880,58 @@ impl<T> Term<T> {
         self.vi_mode_recompute_selection();
     }
 
+    /// Move vi cursor to a point in the grid, recording the jump in the jump list.
+    #[inline]
+    pub fn vi_jump(&mut self, point: Point)
+    where
+        T: EventListener,
+    {
+        self.vi_marks.push_jump(self.vi_mode_cursor.point);
+        self.vi_goto_point(point);
+    }
+
+    /// Move vi cursor to the mark `name`.
+    ///
+    /// When `linewise` is set, the cursor is moved to the first occupied cell in the mark's line.
+    pub fn vi_goto_mark(&mut self, name: char, linewise: bool)
+    where
+        T: EventListener,
+    {
+        let mut point = match self.vi_marks.get(name) {
+            Some(point) => point,
+            None => return,
+        };
+
+        if linewise {
+            point = vi_mode::first_occupied_in_line(self, point.line)
+                .unwrap_or_else(|| Point::new(point.line, Column(0)));
+        }
+
+        self.vi_jump(point);
+    }
+
+    /// Move vi cursor to the previous position in the jump list.
+    #[inline]
+    pub fn vi_jump_older(&mut self)
+    where
+        T: EventListener,
+    {
+        if let Some(point) = self.vi_marks.jump_older(self.vi_mode_cursor.point) {
+            self.vi_goto_point(point);
+        }
+    }
+
+    /// Move vi cursor to the next position in the jump list.
+    #[inline]
+    pub fn vi_jump_newer(&mut self)
+    where
+        T: EventListener,
+    {
+        if let Some(point) = self.vi_marks.jump_newer() {
+            self.vi_goto_point(point);
+        }
+    }
+
     /// Update the active selection to match the vi mode cursor position.
     #[inline]
     fn vi_mode_recompute_selection(&mut self) {
@@ -1044,6 +1179,9 @@ impl<T: EventListener> Handler for Term<T> {
             self.grid.cursor.template.flags.remove(Flags::WIDE_CHAR_SPACER);
         }
 
+        let line = self.grid.cursor.point.line;
+        self.grid[line].timestamp = self.timestamp;
+
         if self.grid.cursor.point.column + 1 < columns {
             self.grid.cursor.point.column += 1;
         } else {
@@ -1255,6 +1393,7 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn carriage_return(&mut self) {
         trace!("Carriage return");
+        self.timestamp = grid::timestamp_now();
         let new_col = 0;
         let line = self.grid.cursor.point.line.0 as usize;
         self.damage_line(line, new_col, self.grid.cursor.point.column.0);
@@ -1266,6 +1405,14 @@ impl<T: EventListener> Handler for Term<T> {
     #[inline]
     fn linefeed(&mut self) {
         trace!("Linefeed");
+        self.timestamp = grid::timestamp_now();
+
+        // Record empty lines, which never received any input.
+        let line = self.grid.cursor.point.line;
+        if self.grid[line].timestamp == 0 {
+            self.grid[line].timestamp = self.timestamp;
+        }
+
         let next = self.grid.cursor.point.line + 1;
         if next == self.scroll_region.end {
             self.scroll_up(1);
@@ -1617,6 +1764,9 @@ impl<T: EventListener> Handler for Term<T> {
                     self.vi_mode_cursor.point.line.grid_clamp(self, Boundary::Cursor);
 
                 self.selection = self.selection.take().filter(|s| !s.intersects_range(..Line(0)));
+
+                // Drop marks which were in the history.
+                self.vi_marks.shift(&self.grid, 0);
             },
             // We have no history to clear.
             ansi::ClearMode::Saved => (),
@@ -1643,6 +1793,7 @@ impl<T: EventListener> Handler for Term<T> {
     fn reset_state(&mut self) {
         if self.mode.contains(TermMode::ALT_SCREEN) {
             mem::swap(&mut self.grid, &mut self.inactive_grid);
+            self.grid_swaps += 1;
         }
         self.active_charset = Default::default();
         self.cursor_style = None;
@@ -1654,6 +1805,8 @@ impl<T: EventListener> Handler for Term<T> {
         self.title = None;
         self.selection = None;
         self.vi_mode_cursor = Default::default();
+        self.vi_marks = Default::default();
+        self.inactive_vi_marks = Default::default();
 
         // Preserve vi mode across resets.
         self.mode &= TermMode::VI;
@@ -1955,6 +2108,30 @@ impl<T: EventListener> Handler for Term<T> {
     }
 }
 
+/// Apply the scrollback history configuration to the primary grid.
+fn configure_history(grid: &mut Grid<Cell>, scrolling: Scrolling) {
+    let history_size = scrolling.history() as usize;
+
+    // History is unlimited when lines are moved to disk, up to the largest `Line` offset.
+    let spilling = scrolling.history_backend == HistoryBackend::File
+        && match grid.enable_spill(history_size) {
+            Ok(()) => true,
+            Err(err) => {
+                error!("Unable to create scrollback history file: {}", err);
+                false
+            },
+        };
+
+    if spilling {
+        grid.update_history(i32::MAX as usize);
+    } else {
+        grid.update_history(history_size);
+        grid.disable_spill();
+    }
+
+    grid.update_compression(scrolling.compress_after.map(|lines| lines as usize));
+}
+
 /// Terminal version for escape sequence reports.
 ///
 /// This returns the current terminal version as a unique number based on alacritty_terminal's
@@ -2425,6 +2602,27 @@ mod tests {
         assert_eq!(term.grid()[cursor].c, '▒');
     }
 
+    #[test]
+    fn input_sets_line_timestamp() {
+        let size = TermSize::new(7, 17);
+        let mut term = Term::new(&Config::default(), &size, VoidListener);
+        assert_eq!(term.grid().timestamp(Line(0)), 0);
+
+        term.input('a');
+        let timestamp = term.grid().timestamp(Line(0));
+        assert_ne!(timestamp, 0);
+        assert_eq!(term.grid().timestamp(Line(1)), 0);
+
+        // Line timestamps are kept when the line is rotated into history.
+        term.linefeed();
+        term.input('b');
+        for _ in 0..size.screen_lines {
+            term.linefeed();
+        }
+        assert_eq!(term.grid().timestamp(Line(-2)), timestamp);
+        assert!(term.grid().timestamp(Line(-1)) >= timestamp);
+    }
+
     #[test]
     fn clearing_viewport_keeps_history_position() {
         let size = TermSize::new(10, 20);
@@ -2541,6 +2739,28 @@ mod tests {
         assert_eq!(term.grid, scrolled_grid);
     }
 
+    #[test]
+    fn scroll_marker_tracks_active_grid() {
+        let size = TermSize::new(5, 3);
+        let mut term = Term::new(&Config::default(), &size, VoidListener);
+
+        let marker = term.scroll_marker();
+        term.grid.scroll_up(&(Line(0)..Line(3)), 2);
+        assert_eq!(term.scrolled_since(marker), Some(2));
+
+        // Positions taken on the primary screen are lost on the alternate screen.
+        let marker = term.scroll_marker();
+        term.swap_alt();
+        assert_eq!(term.scrolled_since(marker), None);
+
+        // The alternate grid counts scrolled lines separately.
+        let alt_marker = term.scroll_marker();
+        term.grid.scroll_up(&(Line(0)..Line(3)), 1);
+        term.swap_alt();
+        assert_eq!(term.scrolled_since(alt_marker), None);
+        assert_eq!(term.scrolled_since(marker), None);
+    }
+
     #[test]
     fn vi_cursor_keep_pos_on_scrollback_buffer() {
         let size = TermSize::new(5, 10);
@@ -1,7 +1,9 @@
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::mem;
 use std::ops::RangeInclusive;
 
+use bitflags::bitflags;
+use regex::RegexBuilder;
 use regex_automata::{dense, DenseDFA, Error as RegexError, DFA};
 
 use crate::grid::{BidirectionalIterator, Dimensions, GridIterator, Indexed};
@@ -12,8 +14,29 @@ use crate::term::Term;
 /// Used to match equal brackets, when performing a bracket-pair selection.
 const BRACKET_PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
 
+/// Characters with a special meaning in regexes.
+const REGEX_META_CHARACTERS: &str = r"\.+*?()|[]{}^$#&-~";
+
 pub type Match = RangeInclusive<Point>;
 
+bitflags! {
+    /// Modifiers changing how a search regex is matched.
+    pub struct SearchFlags: u8 {
+        /// Ignore case, unless the regex contains uppercase characters.
+        const SMART_CASE = 0b001;
+        /// Match the regex text literally, without any special characters.
+        const LITERAL    = 0b010;
+        /// Only match entire words.
+        const WHOLE_WORD = 0b100;
+    }
+}
+
+impl Default for SearchFlags {
+    fn default() -> Self {
+        Self::SMART_CASE
+    }
+}
+
 /// Terminal regex search state.
 #[derive(Clone, Debug)]
 pub struct RegexSearch {
@@ -26,17 +49,48 @@ pub struct RegexSearch {
     left_fdfa: DenseDFA<Vec<usize>, usize>,
     /// Locate end of match searching left.
     left_rdfa: DenseDFA<Vec<usize>, usize>,
+
+    /// Require a word boundary in front of matches.
+    word_start: bool,
+    /// Require a word boundary after matches.
+    word_end: bool,
+
+    /// Require word boundaries next to word characters at both ends of matches.
+    whole_word: bool,
+
+    /// Regex the DFAs were built from, used for extracting capture groups.
+    pattern: String,
+    /// Whether the regex ignores case.
+    case_insensitive: bool,
 }
 
 impl RegexSearch {
     /// Build the forward and backward search DFAs.
     pub fn new(search: &str) -> Result<RegexSearch, RegexError> {
+        Self::with_flags(search, SearchFlags::default())
+    }
+
+    /// Build the search DFAs, matching the regex according to `flags`.
+    pub fn with_flags(search: &str, flags: SearchFlags) -> Result<RegexSearch, RegexError> {
+        let escaped;
+        let search = if flags.contains(SearchFlags::LITERAL) {
+            escaped = escape(search);
+            &escaped
+        } else {
+            search
+        };
+
+        // Word boundaries are not supported by the DFAs, so they're checked after matching.
+        let (search, word_start, word_end) = strip_word_boundaries(search);
+        let whole_word = flags.contains(SearchFlags::WHOLE_WORD);
+
         // Check case info for smart case
         let has_uppercase = search.chars().any(|c| c.is_uppercase());
+        let case_insensitive = flags.contains(SearchFlags::SMART_CASE) && !has_uppercase;
 
         // Create Regex DFAs for all search directions.
         let mut builder = dense::Builder::new();
-        let builder = builder.case_insensitive(!has_uppercase);
+        let builder = builder.case_insensitive(case_insensitive);
 
         let left_fdfa = builder.clone().reverse(true).build(search)?;
         let left_rdfa = builder.clone().anchored(true).longest_match(true).build(search)?;
@@ -44,8 +98,122 @@ impl RegexSearch {
         let right_fdfa = builder.clone().build(search)?;
         let right_rdfa = builder.anchored(true).longest_match(true).reverse(true).build(search)?;
 
-        Ok(RegexSearch { right_fdfa, right_rdfa, left_fdfa, left_rdfa })
+        Ok(RegexSearch {
+            right_fdfa,
+            right_rdfa,
+            left_fdfa,
+            left_rdfa,
+            word_start,
+            word_end,
+            whole_word,
+            pattern: search.into(),
+            case_insensitive,
+        })
+    }
+
+    /// Names of the regex's named capture groups, with the text they matched in `text`.
+    ///
+    /// Groups which didn't participate in the match have no text. No group has any text if `text`
+    /// isn't matched by the entire regex.
+    pub fn captures(&self, text: &str) -> Vec<(String, Option<String>)> {
+        let regex = RegexBuilder::new(&format!("^(?:{})$", self.pattern))
+            .case_insensitive(self.case_insensitive)
+            .build();
+        let regex = match regex {
+            Ok(regex) => regex,
+            Err(_) => return Vec::new(),
+        };
+
+        let captures = regex.captures(text);
+        regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(index, name)| {
+                let group = captures.as_ref().and_then(|captures| captures.get(index));
+                Some((name?.to_string(), group.map(|group| group.as_str().to_string())))
+            })
+            .collect()
+    }
+
+    /// Check the word boundaries around a match.
+    fn is_bounded<T>(&self, term: &Term<T>, regex_match: &Match) -> bool {
+        let (start, end) = (*regex_match.start(), *regex_match.end());
+
+        // Like `\b`, whole words only require boundaries next to word characters.
+        let word_start = self.word_start || (self.whole_word && is_word_char(term.grid()[start].c));
+        let word_end = self.word_end || (self.whole_word && is_word_char(term.grid()[end].c));
+
+        (!word_start || is_word_boundary(term, start, Direction::Left))
+            && (!word_end || is_word_boundary(term, end, Direction::Right))
+    }
+}
+
+/// Escape all regex meta characters in `text`.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if REGEX_META_CHARACTERS.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Check if a character is part of a word, as defined by `\b`.
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Remove `\b` from the start and end of a regex.
+///
+/// Returns the remaining regex and whether a word boundary was stripped from the start and end.
+fn strip_word_boundaries(search: &str) -> (&str, bool, bool) {
+    let (search, word_start) = match search.strip_prefix(r"\b") {
+        Some(stripped) if !stripped.is_empty() => (stripped, true),
+        _ => (search, false),
+    };
+
+    // Ignore escaped backslashes in front of the `b`.
+    let word_end = search.strip_suffix('b').map_or(false, |stripped| {
+        let backslashes = stripped.len() - stripped.trim_end_matches('\\').len();
+        backslashes % 2 == 1 && stripped.len() > 1
+    });
+
+    if word_end {
+        (&search[..search.len() - 2], word_start, true)
+    } else {
+        (search, word_start, false)
+    }
+}
+
+/// Check if there is a word boundary between `point` and the adjacent cell in `direction`.
+fn is_word_boundary<T>(term: &Term<T>, point: Point, direction: Direction) -> bool {
+    let (point, neighbor) = match direction {
+        Direction::Right => {
+            let point = term.expand_wide(point, direction);
+            (point, point.add(term, Boundary::Grid, 1))
+        },
+        Direction::Left => (point, point.sub(term, Boundary::Grid, 1)),
+    };
+
+    // Words only continue across line ends when the line is wrapped.
+    let line = min(point.line, neighbor.line);
+    let wrapped = term.grid()[line][term.last_column()].flags.contains(Flags::WRAPLINE);
+    if neighbor == point || (neighbor.line != point.line && !wrapped) {
+        return true;
     }
+
+    let is_word = |point: Point| {
+        let cell = &term.grid()[point];
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            is_word_char(term.grid()[point.line][point.column - 1].c)
+        } else {
+            is_word_char(cell.c)
+        }
+    };
+
+    is_word(point) != is_word(neighbor)
 }
 
 impl<T> Term<T> {
@@ -480,27 +648,32 @@ impl<'a, T> Iterator for RegexIter<'a, T> {
     type Item = Match;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.done {
-            return None;
-        }
+        loop {
+            if self.done {
+                return None;
+            }
 
-        // Since the end itself might be a single cell match, we search one more time.
-        if self.point == self.end {
-            self.done = true;
-        }
+            // Since the end itself might be a single cell match, we search one more time.
+            if self.point == self.end {
+                self.done = true;
+            }
 
-        let regex_match = self.next_match()?;
+            let regex_match = self.next_match()?;
 
-        self.point = *regex_match.end();
-        if self.point == self.end {
-            // Stop when the match terminates right on the end limit.
-            self.done = true;
-        } else {
-            // Move the new search origin past the match.
-            self.skip();
-        }
+            self.point = *regex_match.end();
+            if self.point == self.end {
+                // Stop when the match terminates right on the end limit.
+                self.done = true;
+            } else {
+                // Move the new search origin past the match.
+                self.skip();
+            }
 
-        Some(regex_match)
+            // Skip matches which don't satisfy the regex's word boundaries.
+            if self.dfas.is_bounded(self.term, &regex_match) {
+                return Some(regex_match);
+            }
+        }
     }
 }
 
@@ -848,4 +1021,96 @@ mod tests {
         let match_end = Point::new(Line(1), Column(2));
         assert_eq!(term.regex_search_left(&dfas, start, end), Some(match_start..=match_end));
     }
+
+    #[test]
+    fn word_boundaries() {
+        #[rustfmt::skip]
+        let term = mock_term("\
+            foobar foo_ foo\r\n\
+            x-foo\
+        ");
+
+        let dfas = RegexSearch::new(r"\bfoo\b").unwrap();
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(1), Column(4));
+        let matches: Vec<_> = RegexIter::new(start, end, Direction::Right, &term, &dfas).collect();
+        let first_match = Point::new(Line(0), Column(12))..=Point::new(Line(0), Column(14));
+        let second_match = Point::new(Line(1), Column(2))..=Point::new(Line(1), Column(4));
+        assert_eq!(matches, [first_match, second_match]);
+
+        // Escaped backslashes are not word boundaries.
+        assert_eq!(strip_word_boundaries(r"\bfoo\\b"), (r"foo\\b", true, false));
+        assert_eq!(strip_word_boundaries(r"foo\\\b"), (r"foo\\", false, true));
+        assert_eq!(strip_word_boundaries(r"\b"), (r"\b", false, false));
+    }
+
+    #[test]
+    fn escape_regex() {
+        assert_eq!(escape("a.b*c"), r"a\.b\*c");
+        assert_eq!(escape("[x]-y"), r"\[x\]\-y");
+        assert!(RegexSearch::new(&escape(r"\.+*?()|[]{}^$#&-~")).is_ok());
+    }
+
+    #[test]
+    fn search_flags() {
+        let term = mock_term("a.b axb Foo_bar (foo) foo");
+        let start = Point::new(Line(0), Column(0));
+        let end = Point::new(Line(0), Column(24));
+        let columns = |regex: &str, flags| {
+            let dfas = RegexSearch::with_flags(regex, flags).unwrap();
+            RegexIter::new(start, end, Direction::Right, &term, &dfas)
+                .map(|regex_match| (regex_match.start().column.0, regex_match.end().column.0))
+                .collect::<Vec<_>>()
+        };
+
+        // Literal matching escapes all special characters.
+        assert_eq!(columns("a.b", SearchFlags::empty()), [(0, 2), (4, 6)]);
+        assert_eq!(columns("a.b", SearchFlags::LITERAL), [(0, 2)]);
+
+        // Smart case only ignores case without uppercase characters.
+        assert_eq!(columns("foo", SearchFlags::empty()), [(17, 19), (22, 24)]);
+        assert_eq!(columns("foo", SearchFlags::SMART_CASE), [(8, 10), (17, 19), (22, 24)]);
+        assert_eq!(columns("Foo", SearchFlags::SMART_CASE), [(8, 10)]);
+
+        // Whole words only require boundaries next to word characters.
+        assert_eq!(columns("foo", SearchFlags::WHOLE_WORD), [(17, 19), (22, 24)]);
+        assert_eq!(columns("(foo", SearchFlags::WHOLE_WORD | SearchFlags::LITERAL), [(16, 19)]);
+        assert_eq!(columns("Foo", SearchFlags::WHOLE_WORD), []);
+    }
+
+    #[test]
+    fn named_captures() {
+        let captures = |regex: &str, text: &str| RegexSearch::new(regex).unwrap().captures(text);
+        let pair = |name: &str, text: &str| (name.to_string(), Some(text.to_string()));
+        let unset = |name: &str| (name.to_string(), None);
+
+        let regex = r"(?P<file>[^:\s]+):(?P<line>\d+)(:(?P<column>\d+))?";
+        assert_eq!(
+            captures(regex, "src/main.rs:12:5"),
+            [pair("file", "src/main.rs"), pair("line", "12"), pair("column", "5")]
+        );
+
+        // Skipped optional groups aren't captured.
+        assert_eq!(
+            captures(regex, "src/main.rs:12"),
+            [pair("file", "src/main.rs"), pair("line", "12"), unset("column")]
+        );
+
+        // Greedy groups leave the shortest possible text to the following parts.
+        assert_eq!(captures(r".*(?P<digits>\d+)", "ab123"), [pair("digits", "3")]);
+        assert_eq!(
+            captures(r"\b(?P<outer>a(?P<inner>b+))c\b", "abbc"),
+            [pair("outer", "abb"), pair("inner", "bb")]
+        );
+
+        // Groups in alternations and repetitions capture their last match.
+        assert_eq!(
+            captures(r"(?P<scheme>https?)://(?P<host>[a-z.]+)|(?P<path>/[^ ]+)", "/tmp/log"),
+            [unset("scheme"), unset("host"), pair("path", "/tmp/log")]
+        );
+        assert_eq!(captures(r"(?:(?P<part>[a-z]+)/)+x", "a/bc/x"), [pair("part", "bc")]);
+
+        // Nothing is captured without an entire match.
+        assert_eq!(captures(r"(?P<line>\d+)", "12a"), [unset("line")]);
+    }
 }
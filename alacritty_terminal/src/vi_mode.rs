@@ -1,9 +1,11 @@
 use std::cmp::min;
+use std::collections::VecDeque;
+use std::ops::{Range, RangeInclusive};
 
 use alacritty_config_derive::ConfigDeserialize;
 
 use crate::event::EventListener;
-use crate::grid::{Dimensions, GridCell};
+use crate::grid::{BidirectionalIterator, Dimensions, GridCell};
 use crate::index::{Boundary, Column, Direction, Line, Point, Side};
 use crate::term::cell::Flags;
 use crate::term::Term;
@@ -49,6 +51,14 @@ pub enum ViMotion {
     WordRightEnd,
     /// Move to opposing bracket.
     Bracket,
+    /// Move to the empty line above the current paragraph.
+    ParagraphUp,
+    /// Move to the empty line below the current paragraph.
+    ParagraphDown,
+    /// Move to the first line of the scrollback history.
+    Top,
+    /// Move to the last line of the terminal.
+    Bottom,
 }
 
 /// Cursor tracking vi mode position.
@@ -150,6 +160,18 @@ impl ViModeCursor {
                 self.point = word(term, self.point, Direction::Right, Side::Right);
             },
             ViMotion::Bracket => self.point = term.bracket_search(self.point).unwrap_or(self.point),
+            ViMotion::ParagraphUp => self.point = paragraph(term, self.point, Direction::Left),
+            ViMotion::ParagraphDown => self.point = paragraph(term, self.point, Direction::Right),
+            ViMotion::Top => {
+                let line = term.topmost_line();
+                let col = first_occupied_in_line(term, line).unwrap_or_default().column;
+                self.point = Point::new(line, col);
+            },
+            ViMotion::Bottom => {
+                let line = term.bottommost_line();
+                let col = first_occupied_in_line(term, line).unwrap_or_default().column;
+                self.point = Point::new(line, col);
+            },
         }
 
         term.scroll_to_point(self.point);
@@ -173,6 +195,249 @@ impl ViModeCursor {
     }
 }
 
+/// Search for a character within the current line, like f/t/F/T in vi.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InlineSearch {
+    /// Character which is searched for.
+    pub character: char,
+
+    /// Direction of the search.
+    pub direction: Direction,
+
+    /// Stop right before the character instead of on top of it.
+    pub stop_short: bool,
+}
+
+impl InlineSearch {
+    /// Find the point this search moves to, starting at `point`.
+    ///
+    /// When `repeat` is set, short searches skip the character directly next to `point`, so
+    /// repeating them does not get stuck in front of the same character.
+    pub fn find<T>(&self, term: &Term<T>, point: Point, repeat: bool) -> Option<Point> {
+        let skip = if self.stop_short && repeat { 2 } else { 1 };
+        let row = &term.grid()[point.line];
+        let is_target = |column: &usize| row[Column(*column)].c == self.character;
+
+        let mut column = match self.direction {
+            Direction::Right => (point.column.0 + skip..term.columns()).find(is_target)?,
+            Direction::Left => (0..=point.column.0.checked_sub(skip)?).rev().find(is_target)?,
+        };
+
+        if self.stop_short {
+            column = match self.direction {
+                Direction::Right => column - 1,
+                Direction::Left => column + 1,
+            };
+        }
+
+        Some(Point::new(point.line, Column(column)))
+    }
+
+    /// Same search in the opposite direction.
+    #[must_use]
+    pub fn reversed(mut self) -> Self {
+        self.direction = self.direction.opposite();
+        self
+    }
+}
+
+/// Text objects, like `iw` or `a(` in vi.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextObject {
+    /// Semantically separated word.
+    Word,
+    /// Whitespace separated word.
+    WideWord,
+    /// Text between a pair of quotes.
+    Quote(char),
+    /// Text between a pair of brackets.
+    Bracket(char, char),
+    /// Lines between empty lines.
+    Paragraph,
+}
+
+impl TextObject {
+    /// Text object identified by a vi key, like `w` for `iw`.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'w' => Some(Self::Word),
+            'W' => Some(Self::WideWord),
+            'p' => Some(Self::Paragraph),
+            '"' | '\'' | '`' => Some(Self::Quote(c)),
+            '(' | ')' | 'b' => Some(Self::Bracket('(', ')')),
+            '[' | ']' => Some(Self::Bracket('[', ']')),
+            '{' | '}' | 'B' => Some(Self::Bracket('{', '}')),
+            '<' | '>' => Some(Self::Bracket('<', '>')),
+            _ => None,
+        }
+    }
+
+    /// Check if the text object always covers entire lines.
+    pub fn is_linewise(self) -> bool {
+        self == Self::Paragraph
+    }
+
+    /// Range of the text object around `point`.
+    ///
+    /// Inner text objects exclude the surrounding whitespace, quotes or brackets.
+    pub fn range<T>(
+        self,
+        term: &Term<T>,
+        point: Point,
+        inner: bool,
+    ) -> Option<RangeInclusive<Point>> {
+        match self {
+            Self::Word => Some(word_object(term, point, inner, false)),
+            Self::WideWord => Some(word_object(term, point, inner, true)),
+            Self::Quote(quote) => quote_object(term, point, quote, inner),
+            Self::Bracket(open, close) => bracket_object(term, point, (open, close), inner),
+            Self::Paragraph => Some(paragraph_object(term, point, inner)),
+        }
+    }
+}
+
+/// Number of vi mode marks, one for each lowercase ASCII letter.
+const MARK_COUNT: usize = 26;
+
+/// Maximum number of positions stored in the jump list.
+const MAX_JUMPS: usize = 100;
+
+/// Vi mode marks and jump list.
+///
+/// All positions are anchored to grid rows, so they must be rotated together with the content
+/// of the grid.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct ViMarks {
+    marks: [Option<Point>; MARK_COUNT],
+    jumps: VecDeque<Point>,
+
+    /// Current position in the jump list, equal to its length when not traversing it.
+    jump_index: usize,
+}
+
+impl ViMarks {
+    /// Set the mark `name` to `point`.
+    ///
+    /// Returns `false` if `name` is not a valid mark name.
+    pub fn set(&mut self, name: char, point: Point) -> bool {
+        match mark_index(name) {
+            Some(index) => {
+                self.marks[index] = Some(point);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Position of the mark `name`.
+    pub fn get(&self, name: char) -> Option<Point> {
+        mark_index(name).and_then(|index| self.marks[index])
+    }
+
+    /// Iterate over all marks which are set.
+    pub fn iter(&self) -> impl Iterator<Item = (char, Point)> + '_ {
+        self.marks
+            .iter()
+            .zip('a'..='z')
+            .filter_map(|(point, name)| point.map(|point| (name, point)))
+    }
+
+    /// Record a jump away from `point`.
+    ///
+    /// This discards all positions newer than the current position in the jump list.
+    pub fn push_jump(&mut self, point: Point) {
+        self.jumps.truncate(self.jump_index);
+
+        if self.jumps.back() != Some(&point) {
+            if self.jumps.len() >= MAX_JUMPS {
+                self.jumps.pop_front();
+            }
+            self.jumps.push_back(point);
+        }
+
+        self.jump_index = self.jumps.len();
+    }
+
+    /// Move to the previous position in the jump list.
+    ///
+    /// When not traversing the jump list yet, `current` is added to it, so it can be returned to.
+    pub fn jump_older(&mut self, current: Point) -> Option<Point> {
+        if self.jump_index >= self.jumps.len() {
+            self.push_jump(current);
+            self.jump_index = self.jumps.len() - 1;
+        }
+
+        self.jump_index = self.jump_index.checked_sub(1)?;
+        self.jumps.get(self.jump_index).copied()
+    }
+
+    /// Move to the next position in the jump list.
+    pub fn jump_newer(&mut self) -> Option<Point> {
+        if self.jump_index + 1 >= self.jumps.len() {
+            return None;
+        }
+
+        self.jump_index += 1;
+        self.jumps.get(self.jump_index).copied()
+    }
+
+    /// Move all positions within `range` by `delta` lines.
+    ///
+    /// Positions which are moved outside of `range` or the grid are dropped.
+    pub fn rotate<D: Dimensions>(&mut self, dimensions: &D, range: &Range<Line>, delta: i32) {
+        let topmost_line = dimensions.topmost_line();
+        self.retain_map(|mut point| {
+            if (point.line >= range.start || range.start == 0) && point.line < range.end {
+                point.line -= delta;
+
+                let outside_range = point.line < range.start && range.start != 0;
+                if outside_range || point.line >= range.end || point.line < topmost_line {
+                    return None;
+                }
+            }
+
+            Some(point)
+        });
+    }
+
+    /// Move all positions by `delta` lines, dropping everything outside of the grid.
+    pub fn shift<D: Dimensions>(&mut self, dimensions: &D, delta: i32) {
+        let topmost_line = dimensions.topmost_line();
+        let bottommost_line = dimensions.bottommost_line();
+        let last_column = dimensions.last_column();
+        self.retain_map(|mut point| {
+            point.line += delta;
+            point.column = min(point.column, last_column);
+            (point.line >= topmost_line && point.line <= bottommost_line).then(|| point)
+        });
+    }
+
+    /// Update all positions, removing the ones for which `f` returns `None`.
+    fn retain_map<F: FnMut(Point) -> Option<Point>>(&mut self, mut f: F) {
+        for mark in &mut self.marks {
+            *mark = mark.and_then(&mut f);
+        }
+
+        let mut jumps = VecDeque::with_capacity(self.jumps.len());
+        let mut removed = 0;
+        for (index, point) in self.jumps.drain(..).enumerate() {
+            match f(point) {
+                Some(point) => jumps.push_back(point),
+                None if index < self.jump_index => removed += 1,
+                None => (),
+            }
+        }
+
+        self.jumps = jumps;
+        self.jump_index -= removed;
+    }
+}
+
+/// Index of a mark in the list of marks.
+fn mark_index(name: char) -> Option<usize> {
+    name.is_ascii_lowercase().then(|| name as usize - 'a' as usize)
+}
+
 /// Find next end of line to move to.
 fn last<T>(term: &Term<T>, mut point: Point) -> Point {
     // Expand across wide cells.
@@ -331,8 +596,189 @@ fn word<T: EventListener>(
     point
 }
 
+/// Move to the next empty line after the paragraph, like {/} in vi.
+fn paragraph<T>(term: &Term<T>, point: Point, direction: Direction) -> Point {
+    let (step, limit) = match direction {
+        Direction::Left => (-1, term.topmost_line()),
+        Direction::Right => (1, term.bottommost_line()),
+    };
+
+    // Skip empty lines in front of the paragraph.
+    let mut line = point.line;
+    while line != limit && is_empty_line(term, line) {
+        line += step;
+    }
+
+    // Move to the first empty line after the paragraph.
+    while line != limit && !is_empty_line(term, line) {
+        line += step;
+    }
+
+    Point::new(line, Column(0))
+}
+
+/// Range of a word text object.
+fn word_object<T>(term: &Term<T>, point: Point, inner: bool, wide: bool) -> RangeInclusive<Point> {
+    let point = term.expand_wide(point, Direction::Left);
+    let cell = &term.grid()[point];
+
+    let (mut start, mut end) = if is_space(term, point) {
+        let is_space = |point| is_space(term, point);
+        (
+            expand_while(term, point, Direction::Left, is_space),
+            expand_while(term, point, Direction::Right, is_space),
+        )
+    } else if wide {
+        let is_word = |point| !is_space(term, point);
+        (
+            expand_while(term, point, Direction::Left, is_word),
+            expand_while(term, point, Direction::Right, is_word),
+        )
+    } else if term.semantic_escape_chars().contains(cell.c) {
+        (point, point)
+    } else {
+        (term.semantic_search_left(point), term.semantic_search_right(point))
+    };
+
+    // Include trailing whitespace, or leading whitespace if there is none.
+    if !inner {
+        let is_space = |point| is_space(term, point);
+        let trailing = expand_while(term, end, Direction::Right, is_space);
+        if trailing == end {
+            start = expand_while(term, start, Direction::Left, is_space);
+        } else {
+            end = trailing;
+        }
+    }
+
+    start..=end
+}
+
+/// Range of a quote text object.
+///
+/// Quotes are paired up from the start of the line, using the first pair which ends at or after
+/// `point`.
+fn quote_object<T>(
+    term: &Term<T>,
+    point: Point,
+    quote: char,
+    inner: bool,
+) -> Option<RangeInclusive<Point>> {
+    let row = &term.grid()[point.line];
+    let quotes: Vec<_> =
+        (0..term.columns()).filter(|&column| row[Column(column)].c == quote).collect();
+
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| close >= point.column.0)?;
+
+    let (start, end) = if inner { (open + 1, close.checked_sub(1)?) } else { (open, close) };
+    (start <= end)
+        .then(|| Point::new(point.line, Column(start))..=Point::new(point.line, Column(end)))
+}
+
+/// Maximum number of lines searched for the opening bracket of a bracket text object.
+const MAX_BRACKET_LINES: i32 = 1000;
+
+/// Range of a bracket text object.
+fn bracket_object<T>(
+    term: &Term<T>,
+    point: Point,
+    (open, close): (char, char),
+    inner: bool,
+) -> Option<RangeInclusive<Point>> {
+    // Find the unmatched opening bracket at or before the point.
+    let start = match term.grid()[point].c {
+        c if c == open => point,
+        c if c == close => term.bracket_search(point)?,
+        _ => {
+            let limit = point.line - MAX_BRACKET_LINES;
+            let mut iter = term.grid().iter_from(point);
+            let mut depth = 0;
+            loop {
+                let cell = iter.prev().filter(|cell| cell.point.line >= limit)?;
+                if cell.c == close {
+                    depth += 1;
+                } else if cell.c == open && depth == 0 {
+                    break cell.point;
+                } else if cell.c == open {
+                    depth -= 1;
+                }
+            }
+        },
+    };
+    let end = term.bracket_search(start)?;
+
+    if inner {
+        let (start, end) = (start.add(term, Boundary::Grid, 1), end.sub(term, Boundary::Grid, 1));
+        (start <= end).then(|| start..=end)
+    } else {
+        Some(start..=end)
+    }
+}
+
+/// Range of a paragraph text object.
+///
+/// Paragraphs include the empty lines after them, unless only the inner paragraph is requested.
+fn paragraph_object<T>(term: &Term<T>, point: Point, inner: bool) -> RangeInclusive<Point> {
+    // Find all adjacent lines which are equally empty.
+    let block = |line: Line| {
+        let empty = is_empty_line(term, line);
+        let mut start = line;
+        while start > term.topmost_line() && is_empty_line(term, start - 1) == empty {
+            start -= 1;
+        }
+        let mut end = line;
+        while end < term.bottommost_line() && is_empty_line(term, end + 1) == empty {
+            end += 1;
+        }
+        (start, end)
+    };
+
+    let (mut start, mut end) = block(point.line);
+
+    // Include the following block, or the preceding one if there is none.
+    if !inner {
+        if end < term.bottommost_line() {
+            end = block(end + 1).1;
+        } else if start > term.topmost_line() {
+            start = block(start - 1).0;
+        }
+    }
+
+    Point::new(start, Column(0))..=Point::new(end, term.last_column())
+}
+
+/// Expand from `point` while the adjacent cells in `direction` match `predicate`.
+///
+/// The expansion stops at the end of the line, unless the line is wrapped.
+fn expand_while<T, F>(term: &Term<T>, mut point: Point, direction: Direction, predicate: F) -> Point
+where
+    F: Fn(Point) -> bool,
+{
+    loop {
+        let next = advance(term, point, direction);
+        let wrap_point = Point::new(min(point.line, next.line), term.last_column());
+        if next == point || (next.line != point.line && !is_wrap(term, wrap_point)) {
+            return point;
+        }
+
+        if !predicate(next) {
+            return point;
+        }
+
+        point = next;
+    }
+}
+
+/// Check if a line has no occupied cells.
+fn is_empty_line<T>(term: &Term<T>, line: Line) -> bool {
+    first_occupied_in_line(term, line).is_none()
+}
+
 /// Find first non-empty cell in line.
-fn first_occupied_in_line<T>(term: &Term<T>, line: Line) -> Option<Point> {
+pub(crate) fn first_occupied_in_line<T>(term: &Term<T>, line: Line) -> Option<Point> {
     (0..term.columns())
         .map(|col| Point::new(line, Column(col)))
         .find(|&point| !is_space(term, point))
@@ -818,4 +1264,147 @@ mod tests {
         cursor = cursor.scroll(&term, -20);
         assert_eq!(cursor.point, Point::new(Line(19), Column(0)));
     }
+
+    #[test]
+    fn marks_follow_content() {
+        let mut term = term();
+        term.grid_mut().update_history(5);
+
+        term.vi_marks.set('a', Point::new(Line(4), Column(3)));
+        term.vi_marks.set('b', Point::new(Line(15), Column(0)));
+        assert!(!term.vi_marks.set('A', Point::new(Line(0), Column(0))));
+
+        // Scroll content by 10 lines.
+        for _ in 0..29 {
+            term.linefeed();
+        }
+
+        // Marks rotated out of the history are dropped.
+        assert_eq!(term.vi_marks.get('a'), None);
+        assert_eq!(term.vi_marks.get('b'), Some(Point::new(Line(5), Column(0))));
+        let marks: Vec<_> = term.vi_marks.iter().collect();
+        assert_eq!(marks, [('b', Point::new(Line(5), Column(0)))]);
+    }
+
+    #[test]
+    fn jump_list() {
+        let mut marks = ViMarks::default();
+        let point = |line| Point::new(Line(line), Column(0));
+
+        marks.push_jump(point(1));
+        marks.push_jump(point(2));
+
+        assert_eq!(marks.jump_older(point(3)), Some(point(2)));
+        assert_eq!(marks.jump_older(point(2)), Some(point(1)));
+        assert_eq!(marks.jump_older(point(1)), None);
+        assert_eq!(marks.jump_newer(), Some(point(2)));
+        assert_eq!(marks.jump_newer(), Some(point(3)));
+        assert_eq!(marks.jump_newer(), None);
+
+        // New jumps discard newer positions.
+        marks.jump_older(point(3));
+        marks.push_jump(point(4));
+        assert_eq!(marks.jump_older(point(5)), Some(point(4)));
+        assert_eq!(marks.jump_older(point(4)), Some(point(1)));
+    }
+
+    fn write_line(term: &mut Term<VoidListener>, line: i32, text: &str) {
+        for (column, c) in text.chars().enumerate() {
+            term.grid_mut()[Line(line)][Column(column)].c = c;
+        }
+    }
+
+    #[test]
+    fn motion_paragraph() {
+        let mut term = term();
+        write_line(&mut term, 2, "x");
+        write_line(&mut term, 3, "x");
+        write_line(&mut term, 6, "x");
+
+        let mut cursor = ViModeCursor::new(Point::new(Line(2), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::ParagraphDown);
+        assert_eq!(cursor.point, Point::new(Line(4), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::ParagraphDown);
+        assert_eq!(cursor.point, Point::new(Line(7), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::ParagraphUp);
+        assert_eq!(cursor.point, Point::new(Line(5), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::ParagraphUp);
+        assert_eq!(cursor.point, Point::new(Line(1), Column(0)));
+
+        cursor = cursor.motion(&mut term, ViMotion::ParagraphUp);
+        assert_eq!(cursor.point, Point::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn motion_top_bottom() {
+        let mut term = term();
+        write_line(&mut term, 19, "  x");
+
+        let mut cursor = ViModeCursor::new(Point::new(Line(5), Column(5)));
+
+        cursor = cursor.motion(&mut term, ViMotion::Bottom);
+        assert_eq!(cursor.point, Point::new(Line(19), Column(2)));
+
+        cursor = cursor.motion(&mut term, ViMotion::Top);
+        assert_eq!(cursor.point, Point::new(Line(0), Column(0)));
+    }
+
+    #[test]
+    fn inline_search() {
+        let mut term = term();
+        write_line(&mut term, 0, "a,b,c");
+
+        let point = Point::new(Line(0), Column(0));
+        let mut search =
+            InlineSearch { character: ',', direction: Direction::Right, stop_short: false };
+        assert_eq!(search.find(&term, point, false), Some(Point::new(Line(0), Column(1))));
+
+        search.stop_short = true;
+        assert_eq!(search.find(&term, point, false), Some(point));
+        assert_eq!(search.find(&term, point, true), Some(Point::new(Line(0), Column(2))));
+
+        let point = Point::new(Line(0), Column(4));
+        search = search.reversed();
+        assert_eq!(search.find(&term, point, false), Some(Point::new(Line(0), Column(4))));
+        assert_eq!(search.find(&term, point, true), Some(Point::new(Line(0), Column(2))));
+
+        search.character = 'x';
+        assert_eq!(search.find(&term, point, false), None);
+    }
+
+    #[test]
+    fn text_objects() {
+        let size = TermSize::new(30, 20);
+        let mut term = Term::new(&Config::default(), &size, VoidListener);
+        write_line(&mut term, 0, "foo bar  \"baz\" (a (b) c)");
+        let point = |column| Point::new(Line(0), Column(column));
+
+        let word = TextObject::Word;
+        assert_eq!(word.range(&term, point(5), true), Some(point(4)..=point(6)));
+        assert_eq!(word.range(&term, point(5), false), Some(point(4)..=point(8)));
+        assert_eq!(word.range(&term, point(7), true), Some(point(7)..=point(8)));
+
+        let quote = TextObject::Quote('"');
+        assert_eq!(quote.range(&term, point(0), true), Some(point(10)..=point(12)));
+        assert_eq!(quote.range(&term, point(11), false), Some(point(9)..=point(13)));
+        assert_eq!(quote.range(&term, point(15), false), None);
+
+        let bracket = TextObject::Bracket('(', ')');
+        assert_eq!(bracket.range(&term, point(17), true), Some(point(16)..=point(22)));
+        assert_eq!(bracket.range(&term, point(19), false), Some(point(18)..=point(20)));
+        assert_eq!(bracket.range(&term, point(22), false), Some(point(15)..=point(23)));
+        assert_eq!(bracket.range(&term, point(0), false), None);
+
+        write_line(&mut term, 1, "x");
+        write_line(&mut term, 3, "x");
+        let paragraph = TextObject::Paragraph;
+        let lines =
+            |start, end| Point::new(Line(start), Column(0))..=Point::new(Line(end), Column(29));
+        assert_eq!(paragraph.range(&term, point(0), true), Some(lines(0, 1)));
+        assert_eq!(paragraph.range(&term, point(0), false), Some(lines(0, 2)));
+    }
 }